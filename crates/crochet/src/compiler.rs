@@ -0,0 +1,630 @@
+use error_stack::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+pub use crochet_ast::values::Program;
+pub use crochet_codegen::js::{JsOptions, JsxRuntime, ModuleFormat};
+pub use crochet_infer::{Context, Trace};
+
+use crochet_codegen::d_ts::codegen_d_ts;
+use crochet_codegen::js::codegen_js_with_options;
+use crochet_dts::parse_dts::{describe_error, parse_dts};
+use crochet_infer::{exports_of, infer_prog, lib_exports, Scope};
+use crochet_parser::{parse_partial, SyntaxError};
+
+use crate::config::Config;
+use crate::config_error::ConfigError;
+use crate::diagnostics::{self, Diagnostic, FileDiagnostics};
+use crate::module_graph::{Exports, Module, ModuleGraph, ModuleKind};
+use crate::resolve::{normalize, OsFs};
+use crate::vfs::VirtualFs;
+
+/// The extension used by crochet modules.
+pub const SOURCE_EXT: &str = "crochet";
+
+/// A source or lib passed to the compiler.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Input {
+    /// Text that isn't backed by a file, `name` is only used when reporting
+    /// diagnostics.
+    Text {
+        name: String,
+        text: String,
+    },
+    File(PathBuf),
+}
+
+impl Input {
+    pub fn text(name: impl Into<String>, text: impl Into<String>) -> Self {
+        Input::Text {
+            name: name.into(),
+            text: text.into(),
+        }
+    }
+
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Input::File(path.into())
+    }
+
+    /// The name used for this input in diagnostics.
+    pub fn name(&self) -> String {
+        match self {
+            Input::Text { name, .. } => name.to_owned(),
+            Input::File(path) => path.to_string_lossy().into_owned(),
+        }
+    }
+
+    fn read(&self) -> io::Result<String> {
+        match self {
+            Input::Text { text, .. } => Ok(text.to_owned()),
+            Input::File(path) => fs::read_to_string(path),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CompileOptions {
+    /// .d.ts files with declarations that are available to all sources.
+    pub libs: Vec<Input>,
+    pub js: JsOptions,
+    pub warnings_as_errors: bool,
+    /// Only type check, without generating any code.
+    pub no_emit: bool,
+    /// Record every step taken while inferring types, see `CompileOutput::trace`.
+    pub trace: bool,
+}
+
+impl CompileOptions {
+    /// Returns the options described by crochet.toml.
+    pub fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        Ok(CompileOptions {
+            libs: config.lib_paths()?.into_iter().map(Input::File).collect(),
+            js: config.js_options(),
+            warnings_as_errors: config.strict.warnings_as_errors,
+            ..CompileOptions::default()
+        })
+    }
+
+    pub fn with_lib(mut self, lib: Input) -> Self {
+        self.libs.push(lib);
+        self
+    }
+
+    pub fn with_js_options(mut self, js: JsOptions) -> Self {
+        self.js = js;
+        self
+    }
+
+    pub fn with_warnings_as_errors(mut self, warnings_as_errors: bool) -> Self {
+        self.warnings_as_errors = warnings_as_errors;
+        self
+    }
+
+    pub fn with_no_emit(mut self, no_emit: bool) -> Self {
+        self.no_emit = no_emit;
+        self
+    }
+
+    pub fn with_trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        self
+    }
+}
+
+/// How long each phase of compilation took, in milliseconds.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Timings {
+    pub parse_lib: f64,
+    pub parse: f64,
+    pub infer: f64,
+    pub codegen: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct CompileOutput {
+    /// Only generated if there weren't any errors.
+    pub js: Option<String>,
+    /// Only generated if there weren't any errors.
+    pub dts: Option<String>,
+    /// Problems with the libs come first, followed by the ones in the source.
+    /// Warnings are included even if compilation succeeded.
+    pub diagnostics: Vec<FileDiagnostics>,
+    /// The statements that could be parsed, with their inferred types.
+    pub program: Program,
+    /// The context after type checking, `None` if the libs couldn't be
+    /// loaded or if type checking failed.
+    pub ctx: Option<Context>,
+    /// Only recorded if `CompileOptions::trace` is set, it's recorded even if
+    /// type checking failed.
+    pub trace: Option<Trace>,
+    pub timings: Timings,
+}
+
+impl CompileOutput {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(FileDiagnostics::has_errors)
+    }
+}
+
+/// The result of compiling all of the modules in a `VirtualFs`.
+#[derive(Clone, Debug)]
+pub struct VfsOutput {
+    /// Problems with the libs in `options`, they're reported once here
+    /// instead of being repeated in each of the modules.
+    pub lib_diagnostics: Vec<FileDiagnostics>,
+    /// The output for each `.crochet` module, keyed by path.
+    pub modules: BTreeMap<String, CompileOutput>,
+}
+
+impl VfsOutput {
+    pub fn has_errors(&self) -> bool {
+        self.lib_diagnostics.iter().any(FileDiagnostics::has_errors)
+            || self.modules.values().any(CompileOutput::has_errors)
+    }
+}
+
+/// The exports of the modules that have been compiled so far, they're used to
+/// type check the modules that import them, see `Compiler::compile_module`.
+#[derive(Clone, Debug, Default)]
+pub struct CompiledModules {
+    exports: Exports,
+    /// Problems with `.d.ts` files are reported by each module that imports
+    /// them.
+    declaration_diagnostics: HashMap<String, FileDiagnostics>,
+}
+
+impl CompiledModules {
+    /// Whether the module at `path` has been compiled.
+    pub fn contains(&self, path: &str) -> bool {
+        self.exports.contains_key(path) || self.declaration_diagnostics.contains_key(path)
+    }
+}
+
+/// Compiles `.crochet` sources to JavaScript and TypeScript declarations.
+/// The libs are loaded once when the compiler is created so that it can be
+/// reused for any number of sources.
+///
+/// ```no_run
+/// use crochet::compiler::{CompileOptions, Compiler, Input};
+///
+/// let options = CompileOptions::default().with_lib(Input::file("lib.es5.d.ts"));
+/// let output = Compiler::new(options).compile_file("main.crochet");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Compiler {
+    options: CompileOptions,
+    /// The globals declared in all of the libs, `None` if any of them failed
+    /// to load.
+    lib_ctx: Option<Context>,
+    lib_diagnostics: Vec<FileDiagnostics>,
+    lib_time: f64,
+}
+
+impl Compiler {
+    pub fn new(options: CompileOptions) -> Self {
+        let start = Instant::now();
+        let mut lib_ctx = Context::default();
+        let mut lib_diagnostics: Vec<FileDiagnostics> = vec![];
+
+        for lib in &options.libs {
+            let name = lib.name();
+            let text = match lib.read() {
+                Ok(text) => text,
+                Err(err) => {
+                    let diagnostic = Diagnostic::error(format!("failed to read lib: {err}"));
+                    lib_diagnostics.push(FileDiagnostics::new(name, "", vec![diagnostic]));
+                    continue;
+                }
+            };
+            match parse_dts(&text) {
+                Ok(ctx) => merge_lib(&mut lib_ctx, &ctx),
+                Err(err) => {
+                    let (message, span) = describe_error(&err);
+                    let diagnostic = Diagnostic::error(format!("failed to parse lib: {message}"))
+                        .with_primary(span, "");
+                    lib_diagnostics.push(FileDiagnostics::new(name, text, vec![diagnostic]));
+                }
+            }
+        }
+
+        Compiler {
+            lib_ctx: lib_diagnostics.is_empty().then_some(lib_ctx),
+            lib_diagnostics,
+            lib_time: elapsed_ms(start),
+            options,
+        }
+    }
+
+    /// Compiles every `.crochet` module in `fs`, modules can import each
+    /// other as well as `.d.ts` files.  Only the libs in `options` are
+    /// available to every module, the declarations in the `.d.ts` files in
+    /// `fs` are only available to the modules that import them.
+    pub fn compile_vfs(fs: &VirtualFs, options: CompileOptions) -> VfsOutput {
+        let mut compiler = Compiler::new(options);
+        let lib_diagnostics = mem::take(&mut compiler.lib_diagnostics);
+
+        let graph = ModuleGraph::build(fs, fs.sources().map(|(path, _)| path));
+        let modules = compiler.compile_graph(&graph);
+
+        VfsOutput {
+            lib_diagnostics,
+            modules,
+        }
+    }
+
+    pub fn options(&self) -> &CompileOptions {
+        &self.options
+    }
+
+    /// Problems with the libs, nothing is type checked if there are any.
+    pub fn lib_diagnostics(&self) -> &[FileDiagnostics] {
+        &self.lib_diagnostics
+    }
+
+    /// The globals declared in the libs, `None` if any of them failed to load.
+    pub fn lib_ctx(&self) -> Option<&Context> {
+        self.lib_ctx.as_ref()
+    }
+
+    pub fn compile_file(&self, path: impl AsRef<Path>) -> CompileOutput {
+        self.compile(&Input::file(path.as_ref()))
+    }
+
+    pub fn compile_source(&self, name: &str, text: &str) -> CompileOutput {
+        self.compile(&Input::text(name, text))
+    }
+
+    /// Compiles a single input.  The modules that a file imports are
+    /// compiled first so that their exports can be type checked, their output
+    /// isn't returned.
+    pub fn compile(&self, input: &Input) -> CompileOutput {
+        let (name, src) = match input {
+            Input::Text { name, text } => (name, text),
+            Input::File(path) => {
+                let path = path.to_string_lossy();
+                let graph = ModuleGraph::build(&OsFs, [&path]);
+                if let Some(output) = self.compile_graph(&graph).remove(&normalize(&path)) {
+                    return output;
+                }
+
+                let mut output = self.empty_output();
+                for (path, err) in &graph.errors {
+                    let diagnostic = Diagnostic::error(err.to_string());
+                    output
+                        .diagnostics
+                        .push(FileDiagnostics::new(path, "", vec![diagnostic]));
+                }
+                return output;
+            }
+        };
+
+        let start = Instant::now();
+        let (program, syntax_errors) = parse_partial(src);
+        let parse_time = elapsed_ms(start);
+
+        let (mut output, _) = self.compile_program(name, src, program, &syntax_errors, |_| ());
+        output.timings.parse = parse_time;
+        output
+    }
+
+    /// Compiles each of the `.crochet` modules in `graph` in dependency order
+    /// and returns their output keyed by path.
+    pub fn compile_graph(&self, graph: &ModuleGraph) -> BTreeMap<String, CompileOutput> {
+        let mut compiled = CompiledModules::default();
+        graph
+            .modules()
+            .iter()
+            .filter_map(|module| {
+                let output = self.compile_module(module, &mut compiled)?;
+                Some((module.path.to_owned(), output))
+            })
+            .collect()
+    }
+
+    /// Compiles a single module from a `ModuleGraph`, the modules it imports
+    /// must already be in `compiled`.  The module is added to `compiled` so
+    /// that the modules importing it can be compiled next.  Modules can be
+    /// compiled again, e.g. after they change, as long as the modules that
+    /// import them are compiled again afterwards.
+    ///
+    /// `.d.ts` modules don't have any output, their problems are reported by
+    /// the modules that import them.
+    pub fn compile_module(
+        &self,
+        module: &Module,
+        compiled: &mut CompiledModules,
+    ) -> Option<CompileOutput> {
+        match &module.kind {
+            ModuleKind::Source {
+                program,
+                syntax_errors,
+            } => {
+                let (mut output, module_exports) = self.compile_program(
+                    &module.path,
+                    &module.src,
+                    program.to_owned(),
+                    syntax_errors,
+                    |ctx| module.bind_imports(&compiled.exports, ctx),
+                );
+                output.timings.parse = module.parse_time;
+                for import in &module.imports {
+                    let path = import.path.as_deref().unwrap_or_default();
+                    if let Some(diagnostics) = compiled.declaration_diagnostics.get(path) {
+                        output.diagnostics.push(diagnostics.to_owned());
+                    }
+                }
+                match module_exports {
+                    Some(module_exports) => {
+                        compiled
+                            .exports
+                            .insert(module.path.to_owned(), module_exports);
+                    }
+                    None => {
+                        compiled.exports.remove(&module.path);
+                    }
+                }
+                Some(output)
+            }
+            ModuleKind::Declarations => {
+                match parse_dts(&module.src) {
+                    Ok(ctx) => {
+                        compiled.declaration_diagnostics.remove(&module.path);
+                        compiled
+                            .exports
+                            .insert(module.path.to_owned(), lib_exports(&ctx));
+                    }
+                    Err(err) => {
+                        let (message, span) = describe_error(&err);
+                        let diagnostic = Diagnostic::error(format!("failed to parse: {message}"))
+                            .with_primary(span, "");
+                        compiled.exports.remove(&module.path);
+                        compiled.declaration_diagnostics.insert(
+                            module.path.to_owned(),
+                            FileDiagnostics::new(&module.path, &module.src, vec![diagnostic]),
+                        );
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    fn empty_output(&self) -> CompileOutput {
+        CompileOutput {
+            js: None,
+            dts: None,
+            diagnostics: self.lib_diagnostics.to_owned(),
+            program: Program { body: vec![] },
+            ctx: None,
+            trace: None,
+            timings: Timings {
+                parse_lib: self.lib_time,
+                ..Timings::default()
+            },
+        }
+    }
+
+    /// Type checks and generates code for a program that's already been
+    /// parsed.  `bind_imports` is called with the context the program is
+    /// inferred in before inference starts.  Along with the output, the
+    /// program's exports are returned if the libs could be loaded.  Modules
+    /// that fail to type check still have exports so that the modules that
+    /// import them don't report the same problems again.
+    fn compile_program(
+        &self,
+        name: &str,
+        src: &str,
+        mut program: Program,
+        syntax_errors: &[SyntaxError],
+        bind_imports: impl FnOnce(&mut Context),
+    ) -> (CompileOutput, Option<Scope>) {
+        let mut output = self.empty_output();
+        let mut exports: Option<Scope> = None;
+
+        // Statements that parsed cleanly are still type checked so that all
+        // of the problems in the input are reported at once.
+        let mut diagnostics: Vec<Diagnostic> = syntax_errors.iter().map(Diagnostic::from).collect();
+
+        // There's nothing to type check against if the libs failed to load.
+        let start = Instant::now();
+        if let Some(lib_ctx) = &self.lib_ctx {
+            let mut ctx = lib_ctx.to_owned();
+            bind_imports(&mut ctx);
+            if self.options.trace {
+                ctx.enable_trace();
+            }
+            let result = infer_prog(&mut program, &mut ctx);
+            output.trace = ctx.take_trace();
+            let warnings = ctx.take_warnings();
+            exports = Some(exports_of(&program, &ctx));
+            match result {
+                Ok(ctx) => output.ctx = Some(ctx),
+                Err(report) => diagnostics.append(&mut diagnostics::from_type_errors(&report)),
+            }
+            diagnostics.extend(warnings.iter().map(|warning| {
+                let diagnostic = Diagnostic::from(warning);
+                if self.options.warnings_as_errors {
+                    diagnostic.deny()
+                } else {
+                    diagnostic
+                }
+            }));
+        }
+        output.timings.infer = elapsed_ms(start);
+
+        if !diagnostics.is_empty() {
+            output
+                .diagnostics
+                .push(FileDiagnostics::new(name, src, diagnostics));
+        }
+
+        let start = Instant::now();
+        if let (Some(ctx), false, false) = (&output.ctx, output.has_errors(), self.options.no_emit)
+        {
+            output.js = Some(codegen_js_with_options(&program, &self.options.js));
+            output.dts = Some(codegen_d_ts(&program, ctx));
+        }
+        output.timings.codegen = elapsed_ms(start);
+
+        output.program = program;
+        (output, exports)
+    }
+}
+
+/// Adds all of the declarations from `lib_ctx` to the global scope of `ctx`.
+pub fn merge_lib(ctx: &mut Context, lib_ctx: &Context) {
+    for scope in &lib_ctx.scopes {
+        for (name, b) in &scope.values {
+            ctx.insert_binding(name.to_owned(), b.to_owned());
+        }
+        for (name, t) in &scope.types {
+            ctx.insert_type(name.to_owned(), t.to_owned());
+        }
+        for (name, namespace) in &scope.namespaces {
+            ctx.insert_namespace(name.to_owned(), namespace.as_ref().to_owned());
+        }
+    }
+
+    // Keep type variable ids unique across all of the libs we've merged.
+    let count = ctx.state.count.get().max(lib_ctx.state.count.get());
+    ctx.state.count.set(count);
+}
+
+pub(crate) fn elapsed_ms(start: Instant) -> f64 {
+    start.elapsed().as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn missing_files_are_diagnostics() {
+        let options = CompileOptions::default().with_lib(Input::file("missing.d.ts"));
+        let output = Compiler::new(options).compile_file("missing.crochet");
+
+        assert!(output.has_errors());
+        assert_eq!(output.js, None);
+        assert!(output.ctx.is_none());
+
+        let paths: Vec<_> = output
+            .diagnostics
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["missing.d.ts", "missing.crochet"]);
+        assert!(output.diagnostics[0].diagnostics[0]
+            .message
+            .starts_with("failed to read lib: "));
+    }
+
+    #[test]
+    fn compile_each_module_in_a_vfs() {
+        let options =
+            CompileOptions::default().with_lib(Input::text("lib.d.ts", "interface Foo {"));
+        let fs = VirtualFs::from_iter([
+            ("main.crochet", "let x = 5;"),
+            ("utils.crochet", "let y = 10;"),
+        ]);
+        let output = Compiler::compile_vfs(&fs, options);
+
+        assert!(output.has_errors());
+        assert_eq!(output.lib_diagnostics.len(), 1);
+        assert_eq!(output.lib_diagnostics[0].path, "lib.d.ts");
+
+        let paths: Vec<_> = output.modules.keys().map(String::as_str).collect();
+        assert_eq!(paths, vec!["main.crochet", "utils.crochet"]);
+        for module in output.modules.values() {
+            assert!(module.diagnostics.is_empty());
+            assert_eq!(module.js, None);
+        }
+    }
+
+    #[test]
+    fn declarations_are_only_available_to_modules_that_import_them() {
+        let fs = VirtualFs::from_iter([
+            ("broken.d.ts", "interface Foo {"),
+            ("point.d.ts", "declare let origin: {x: number, y: number};"),
+            (
+                "main.crochet",
+                r#"import {origin} from "./point"; export let x = origin.x;"#,
+            ),
+            ("utils.crochet", "export let y = origin.y;"),
+            ("shapes.crochet", r#"import {Foo} from "./broken";"#),
+        ]);
+        let output = Compiler::compile_vfs(&fs, CompileOptions::default());
+
+        assert!(output.lib_diagnostics.is_empty());
+        assert!(!output.modules["main.crochet"].has_errors());
+
+        let utils = &output.modules["utils.crochet"];
+        assert_eq!(utils.diagnostics.len(), 1);
+        assert_eq!(
+            utils.diagnostics[0].diagnostics[0].message,
+            "Can't find value: origin"
+        );
+
+        // Problems with a `.d.ts` file are only reported by the modules that
+        // import it, after their own problems.
+        let shapes = &output.modules["shapes.crochet"];
+        let paths: Vec<_> = shapes
+            .diagnostics
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["shapes.crochet", "broken.d.ts"]);
+    }
+
+    #[test]
+    fn modules_are_checked_with_their_imports() {
+        let fs = VirtualFs::from_iter([
+            ("a.crochet", r#"import {b} from "./b"; export let a = 5;"#),
+            ("b.crochet", r#"import {a} from "./a"; export let b = 10;"#),
+            (
+                "main.crochet",
+                r#"import {add} from "./math"; export let sum = add(1, 2);"#,
+            ),
+            (
+                "math.crochet",
+                "export let add = (a: number, b: number) => a + b;",
+            ),
+        ]);
+        let output = Compiler::compile_vfs(&fs, CompileOptions::default());
+
+        let main = &output.modules["main.crochet"];
+        assert!(!main.has_errors());
+        let ctx = main.ctx.as_ref().unwrap();
+        assert_eq!(ctx.lookup_value("sum").unwrap().to_string(), "number");
+
+        let b = &output.modules["b.crochet"];
+        let messages: Vec<_> = b.diagnostics[0]
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec!["Import cycle: a.crochet -> b.crochet -> a.crochet"]
+        );
+    }
+
+    #[test]
+    fn type_check_only_with_a_trace() {
+        let options = CompileOptions::default()
+            .with_no_emit(true)
+            .with_trace(true);
+        let src = "let id = (x) => x; let y = id(5);";
+        let output = Compiler::new(options).compile_source("main.crochet", src);
+
+        assert!(!output.has_errors());
+        assert_eq!(output.js, None);
+        assert_eq!(output.dts, None);
+        assert!(!output.trace.unwrap().events.is_empty());
+    }
+}
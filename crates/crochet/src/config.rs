@@ -0,0 +1,306 @@
+use error_stack::{Report, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crochet_codegen::js::{JsOptions, JsxRuntime, ModuleFormat};
+
+use crate::config_error::ConfigError;
+use crate::resolve::{resolve, OsFs};
+
+pub const CONFIG_FILE_NAME: &str = "crochet.toml";
+
+/// The contents of the file written by `crochet init`.
+pub const STARTER_CONFIG: &str = r#"# Directories containing .crochet sources
+src = ["src"]
+
+# Globs, relative to this file, used to select sources inside of `src`
+include = ["**/*.crochet"]
+exclude = []
+
+# .d.ts files with declarations that are available to all sources
+libs = []
+
+# Packages in node_modules whose .d.ts files should be loaded, e.g. "@types/react"
+lib_packages = []
+
+# Where to write .js and .d.ts files, defaults to next to each source
+# out_dir = "dist"
+
+[codegen]
+# "esm" or "commonjs"
+module = "esm"
+# "automatic" uses react/jsx-runtime, "classic" uses React.createElement
+jsx = "automatic"
+
+[strict]
+# Treat warnings as errors
+warnings_as_errors = false
+"#;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ModuleConfig {
+    #[default]
+    Esm,
+    CommonJs,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JsxConfig {
+    #[default]
+    Automatic,
+    Classic,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct CodegenConfig {
+    pub module: ModuleConfig,
+    pub jsx: JsxConfig,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct StrictConfig {
+    pub warnings_as_errors: bool,
+}
+
+/// A project manifest, i.e. the contents of a crochet.toml file.  All paths
+/// are relative to `root`, the directory containing the manifest.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(skip)]
+    pub root: PathBuf,
+    pub src: Vec<PathBuf>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub libs: Vec<PathBuf>,
+    pub lib_packages: Vec<String>,
+    pub out_dir: Option<PathBuf>,
+    pub codegen: CodegenConfig,
+    pub strict: StrictConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            root: PathBuf::new(),
+            src: vec![PathBuf::from("src")],
+            include: vec![String::from("**/*.crochet")],
+            exclude: vec![],
+            libs: vec![],
+            lib_packages: vec![],
+            out_dir: None,
+            codegen: CodegenConfig::default(),
+            strict: StrictConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn parse(src: &str, root: &Path) -> Result<Config, ConfigError> {
+        let mut config: Config = toml::from_str(src)
+            .map_err(|err| Report::new(ConfigError).attach_printable(err.to_string()))?;
+        config.root = root.to_owned();
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let src = fs::read_to_string(path).map_err(|err| {
+            Report::new(ConfigError)
+                .attach_printable(format!("can't read {}: {err}", path.display()))
+        })?;
+        let root = path.parent().unwrap_or_else(|| Path::new(""));
+        Config::parse(&src, root)
+    }
+
+    /// Looks for a crochet.toml in `dir` and each of its ancestors.
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Returns all of the sources in the `src` directories that match
+    /// `include` and don't match `exclude`, sorted by path.
+    pub fn sources(&self) -> Result<Vec<PathBuf>, ConfigError> {
+        let include = build_glob_set(&self.include)?;
+        let exclude = build_glob_set(&self.exclude)?;
+
+        let mut paths: Vec<PathBuf> = vec![];
+        for dir in &self.src {
+            walk_dir(&self.root.join(dir), &mut paths)?;
+        }
+
+        let mut sources: Vec<PathBuf> = paths
+            .into_iter()
+            .filter(|path| {
+                let rel_path = path.strip_prefix(&self.root).unwrap_or(path);
+                include.is_match(rel_path) && !exclude.is_match(rel_path)
+            })
+            .collect();
+        sources.sort();
+        sources.dedup();
+
+        Ok(sources)
+    }
+
+    /// Returns the paths of all lib .d.ts files including those from
+    /// `lib_packages`.
+    pub fn lib_paths(&self) -> Result<Vec<PathBuf>, ConfigError> {
+        let mut paths: Vec<PathBuf> = self.libs.iter().map(|lib| self.root.join(lib)).collect();
+        for package in &self.lib_packages {
+            paths.push(resolve_types_package(&self.root, package)?);
+        }
+        Ok(paths)
+    }
+
+    pub fn out_dir(&self) -> Option<PathBuf> {
+        self.out_dir.as_ref().map(|out_dir| self.root.join(out_dir))
+    }
+
+    pub fn js_options(&self) -> JsOptions {
+        JsOptions {
+            module: match self.codegen.module {
+                ModuleConfig::Esm => ModuleFormat::Esm,
+                ModuleConfig::CommonJs => ModuleFormat::CommonJs,
+            },
+            jsx_runtime: match self.codegen.jsx {
+                JsxConfig::Automatic => JsxRuntime::Automatic,
+                JsxConfig::Classic => JsxRuntime::Classic,
+            },
+        }
+    }
+}
+
+fn build_glob_set(globs: &[String]) -> Result<GlobSet, ConfigError> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        let glob = Glob::new(glob).map_err(|err| {
+            Report::new(ConfigError).attach_printable(format!("invalid glob: {err}"))
+        })?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|err| Report::new(ConfigError).attach_printable(err.to_string()))
+}
+
+fn walk_dir(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), ConfigError> {
+    let read_error = |err: std::io::Error| {
+        Report::new(ConfigError).attach_printable(format!(
+            "can't read source directory {}: {err}",
+            dir.display()
+        ))
+    };
+    let entries = fs::read_dir(dir).map_err(read_error)?;
+    for entry in entries {
+        let path = entry.map_err(read_error)?.path();
+        if path.is_dir() {
+            walk_dir(&path, paths)?;
+        } else {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Finds the .d.ts entry point for `package` in the node_modules directories
+/// above `root`, the same way that imports of the package are resolved.
+fn resolve_types_package(root: &Path, package: &str) -> Result<PathBuf, ConfigError> {
+    let from = root.join(CONFIG_FILE_NAME);
+    match resolve(&OsFs, &from.to_string_lossy(), package) {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => Err(Report::new(ConfigError)
+            .attach_printable(format!("can't find types for package: {package}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn empty_config_uses_defaults() {
+        let config = Config::parse("", Path::new("project")).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                root: PathBuf::from("project"),
+                ..Config::default()
+            }
+        );
+    }
+
+    #[test]
+    fn starter_config_is_valid() {
+        let config = Config::parse(STARTER_CONFIG, Path::new("")).unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn codegen_options() {
+        let src = r#"
+        [codegen]
+        module = "commonjs"
+        jsx = "classic"
+        "#;
+        let config = Config::parse(src, Path::new("")).unwrap();
+        let options = config.js_options();
+        assert_eq!(options.module, ModuleFormat::CommonJs);
+        assert_eq!(options.jsx_runtime, JsxRuntime::Classic);
+    }
+
+    #[test]
+    fn unknown_fields_are_errors() {
+        assert!(Config::parse("sources = [\"src\"]", Path::new("")).is_err());
+    }
+
+    #[test]
+    fn include_and_exclude_globs() {
+        let root = std::env::temp_dir().join("crochet_config_include_and_exclude_globs");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src/generated")).unwrap();
+        for path in ["src/a.crochet", "src/b.ts", "src/generated/c.crochet"] {
+            fs::write(root.join(path), "").unwrap();
+        }
+
+        let src = r#"exclude = ["src/generated/**"]"#;
+        let config = Config::parse(src, &root).unwrap();
+        assert_eq!(config.sources().unwrap(), vec![root.join("src/a.crochet")]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn lib_packages_are_resolved_like_imports() {
+        let root = std::env::temp_dir().join("crochet_config_lib_packages");
+        let _ = fs::remove_dir_all(&root);
+        let package_dir = root.join("node_modules/@types/react");
+        fs::create_dir_all(&package_dir).unwrap();
+        fs::write(
+            package_dir.join("package.json"),
+            r#"{"types": "react.d.ts"}"#,
+        )
+        .unwrap();
+        fs::write(package_dir.join("react.d.ts"), "").unwrap();
+
+        let src = r#"lib_packages = ["@types/react", "missing"]"#;
+        let config = Config::parse(src, &root).unwrap();
+        assert!(config.lib_paths().is_err());
+
+        let src = r#"lib_packages = ["@types/react"]"#;
+        let config = Config::parse(src, &root).unwrap();
+        assert_eq!(
+            config.lib_paths().unwrap(),
+            vec![package_dir.join("react.d.ts")]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
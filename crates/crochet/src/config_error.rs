@@ -2,12 +2,12 @@ use error_stack::Context;
 use std::fmt;
 
 #[derive(Debug, PartialEq, Eq)]
-pub struct CompileError;
+pub struct ConfigError;
 
-impl fmt::Display for CompileError {
+impl fmt::Display for ConfigError {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(fmt, "CompileError")
+        write!(fmt, "ConfigError")
     }
 }
 
-impl Context for CompileError {}
+impl Context for ConfigError {}
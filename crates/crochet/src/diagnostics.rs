@@ -0,0 +1,698 @@
+use crochet_ast::values::Span;
+use crochet_infer::{span_of_type, TypeError, TypeErrorKind, Warning, WarningKind};
+use crochet_parser::{ParseError, SyntaxError};
+use error_stack::{AttachmentKind, FrameKind, Report};
+use serde::Serialize;
+use std::fmt::Write;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Label {
+            span,
+            message: message.into(),
+        }
+    }
+}
+
+/// A problem with the source code that should be reported to the user.
+/// `primary` points at the code that caused the problem, `secondary` labels
+/// point at related code, e.g. where a conflicting type came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Option<String>,
+    pub message: String,
+    pub primary: Option<Label>,
+    pub secondary: Vec<Label>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            code: None,
+            message: message.into(),
+            primary: None,
+            secondary: vec![],
+            notes: vec![],
+        }
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            ..Diagnostic::error(message)
+        }
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    pub fn with_primary(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.primary = Some(Label::new(span, message));
+        self
+    }
+
+    pub fn with_secondary(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.secondary.push(Label::new(span, message));
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Turns a warning into an error, this is used for
+    /// `strict.warnings_as_errors`.
+    pub fn deny(self) -> Self {
+        match self.severity {
+            Severity::Warning => Diagnostic {
+                severity: Severity::Error,
+                ..self
+            }
+            .with_note("warnings are errors because `strict.warnings_as_errors` is set"),
+            Severity::Error => self,
+        }
+    }
+}
+
+/// The diagnostics for a single file along with its source, which is needed
+/// to render them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileDiagnostics {
+    pub path: String,
+    pub src: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl FileDiagnostics {
+    pub fn new(
+        path: impl Into<String>,
+        src: impl Into<String>,
+        diagnostics: Vec<Diagnostic>,
+    ) -> Self {
+        FileDiagnostics {
+            path: path.into(),
+            src: src.into(),
+            diagnostics,
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(Diagnostic::is_error)
+    }
+
+    pub fn render(&self, options: &RenderOptions) -> String {
+        render(&self.diagnostics, &self.path, &self.src, options)
+    }
+
+    pub fn to_json(&self) -> Vec<JsonDiagnostic> {
+        to_json(&self.diagnostics, &self.path, &self.src)
+    }
+}
+
+impl From<&TypeError> for Diagnostic {
+    fn from(error: &TypeError) -> Self {
+        let mut diagnostic = Diagnostic::error(error.kind.to_string()).with_code(error.code());
+
+        if let Some(span) = &error.span {
+            diagnostic = diagnostic.with_primary(span.to_owned(), primary_message(&error.kind));
+        }
+
+        let primary_span = error.span.to_owned();
+        let mut seen: Vec<Span> = primary_span.into_iter().collect();
+        let mut add_secondary = |diagnostic: Diagnostic, t, message: String| match span_of_type(t) {
+            Some(span) if !seen.contains(&span) => {
+                seen.push(span.to_owned());
+                diagnostic.with_secondary(span, message)
+            }
+            _ => diagnostic,
+        };
+
+        match &error.kind {
+            TypeErrorKind::TypeMismatch { expected, .. }
+            | TypeErrorKind::MutabilityMismatch { expected, .. } => add_secondary(
+                diagnostic,
+                expected,
+                format!("expected `{expected}` because of this"),
+            ),
+            _ => error
+                .related_types()
+                .into_iter()
+                .fold(diagnostic, |diagnostic, t| {
+                    add_secondary(diagnostic, t, format!("this has type `{t}`"))
+                }),
+        }
+    }
+}
+
+fn primary_message(kind: &TypeErrorKind) -> String {
+    match kind {
+        TypeErrorKind::TypeMismatch { expected, actual }
+        | TypeErrorKind::MutabilityMismatch { expected, actual } => {
+            format!("expected `{expected}`, found `{actual}`")
+        }
+        TypeErrorKind::UnboundIdentifier { .. } => String::from("not found in this scope"),
+        TypeErrorKind::NotCallable { t } => format!("`{t}` can't be called"),
+        TypeErrorKind::AssignToImmutable { .. } => {
+            String::from("cannot assign to an immutable binding")
+        }
+        _ => String::new(),
+    }
+}
+
+/// Returns one diagnostic for each type error in `report`.  The printable
+/// messages attached to an error are included as notes.
+pub fn from_type_errors(report: &Report<TypeError>) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let mut notes: Vec<String> = vec![];
+
+    for frame in report.frames() {
+        match frame.kind() {
+            FrameKind::Attachment(AttachmentKind::Printable(attachment)) => {
+                notes.push(attachment.to_string())
+            }
+            FrameKind::Context(_) => {
+                if let Some(error) = frame.downcast_ref::<TypeError>() {
+                    let diagnostic = notes
+                        .drain(..)
+                        .fold(Diagnostic::from(error), |diagnostic, note| {
+                            diagnostic.with_note(note)
+                        });
+                    diagnostics.push(diagnostic);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    diagnostics
+}
+
+impl From<&SyntaxError> for Diagnostic {
+    fn from(error: &SyntaxError) -> Self {
+        let label = error.hint.to_owned().unwrap_or_default();
+        Diagnostic::error(error.kind.to_string()).with_primary(error.span.to_owned(), label)
+    }
+}
+
+impl From<&Warning> for Diagnostic {
+    fn from(warning: &Warning) -> Self {
+        let label = match &warning.kind {
+            WarningKind::IrrefutableIfLet => "this pattern always matches",
+            WarningKind::UnreachableMatchArm => "an earlier arm always matches",
+            WarningKind::UnusedBinding { .. } => "prefix with `_` if this is intentional",
+            WarningKind::ShadowedBinding { .. } => "this shadows the earlier binding",
+        };
+        let diagnostic = Diagnostic::warning(warning.kind.to_string())
+            .with_code(warning.code())
+            .with_primary(warning.span.to_owned(), label);
+
+        match &warning.kind {
+            WarningKind::ShadowedBinding { name, previous } => diagnostic.with_secondary(
+                previous.to_owned(),
+                format!("`{name}` was first bound here"),
+            ),
+            _ => diagnostic,
+        }
+    }
+}
+
+/// Returns one diagnostic for each syntax error in `report`.
+pub fn from_parse_error(report: &Report<ParseError>) -> Vec<Diagnostic> {
+    let diagnostics: Vec<_> = report
+        .frames()
+        .filter_map(|frame| frame.downcast_ref::<SyntaxError>())
+        .map(Diagnostic::from)
+        .collect();
+
+    if !diagnostics.is_empty() {
+        return diagnostics;
+    }
+
+    let mut messages = report.frames().filter_map(|frame| match frame.kind() {
+        FrameKind::Attachment(AttachmentKind::Printable(attachment)) => {
+            Some(attachment.to_string())
+        }
+        _ => None,
+    });
+
+    let message = messages
+        .next()
+        .unwrap_or_else(|| report.current_context().to_string());
+
+    vec![
+        messages.fold(Diagnostic::error(message), |diagnostic, note| {
+            diagnostic.with_note(note)
+        }),
+    ]
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RenderOptions {
+    /// Use ANSI escape codes to colour the output.
+    pub color: bool,
+}
+
+struct Style {
+    color: bool,
+}
+
+impl Style {
+    fn paint(&self, code: &str, text: &str) -> String {
+        match self.color {
+            true => format!("\x1b[{code}m{text}\x1b[0m"),
+            false => text.to_owned(),
+        }
+    }
+
+    fn severity(&self, severity: Severity, text: &str) -> String {
+        match severity {
+            Severity::Error => self.paint("1;31", text),
+            Severity::Warning => self.paint("1;33", text),
+        }
+    }
+
+    fn gutter(&self, text: &str) -> String {
+        self.paint("1;34", text)
+    }
+
+    fn bold(&self, text: &str) -> String {
+        self.paint("1", text)
+    }
+}
+
+/// Converts a byte offset into a 1-based (line, column) pair.  Columns are
+/// counted in chars.
+pub fn line_col(src: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(src.len());
+    let before = &src[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    let col = before[line_start..].chars().count() + 1;
+    (line, col)
+}
+
+/// Renders `diagnostics` in the style of a compiler error message with the
+/// relevant lines from `src` underlined.  `path` is only used for display.
+pub fn render(
+    diagnostics: &[Diagnostic],
+    path: &str,
+    src: &str,
+    options: &RenderOptions,
+) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| render_diagnostic(diagnostic, path, src, options))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn render_diagnostic(
+    diagnostic: &Diagnostic,
+    path: &str,
+    src: &str,
+    options: &RenderOptions,
+) -> String {
+    let style = Style {
+        color: options.color,
+    };
+    let lines: Vec<&str> = src.split('\n').collect();
+    let mut out = String::new();
+
+    let header = match &diagnostic.code {
+        Some(code) => format!("{}[{code}]", diagnostic.severity.as_str()),
+        None => diagnostic.severity.as_str().to_owned(),
+    };
+    let _ = writeln!(
+        out,
+        "{}{}",
+        style.severity(diagnostic.severity, &header),
+        style.bold(&format!(": {}", diagnostic.message))
+    );
+
+    // (line, start col, end col, message, is primary) for each label
+    let mut markers: Vec<(usize, usize, usize, &str, bool)> = vec![];
+    let labels = diagnostic
+        .primary
+        .iter()
+        .map(|label| (label, true))
+        .chain(diagnostic.secondary.iter().map(|label| (label, false)));
+    for (label, is_primary) in labels {
+        let (line, start_col) = line_col(src, label.span.start);
+        let (end_line, end_col) = line_col(src, label.span.end);
+        let end_col = match end_line == line {
+            true => end_col.max(start_col + 1),
+            // Multi-line spans are underlined to the end of their first line.
+            false => lines[line - 1].chars().count() + 1,
+        };
+        markers.push((line, start_col, end_col, &label.message, is_primary));
+    }
+
+    let max_line = markers.iter().map(|marker| marker.0).max().unwrap_or(0);
+    let width = max_line.to_string().len();
+    let pad = " ".repeat(width);
+
+    match &diagnostic.primary {
+        Some(label) => {
+            let (line, col) = line_col(src, label.span.start);
+            let _ = writeln!(out, "{pad}{} {path}:{line}:{col}", style.gutter("-->"));
+        }
+        None => {
+            let _ = writeln!(out, "{pad}{} {path}", style.gutter("-->"));
+        }
+    }
+
+    if !markers.is_empty() {
+        let _ = writeln!(out, "{pad} {}", style.gutter("|"));
+
+        let mut line_numbers: Vec<usize> = markers.iter().map(|marker| marker.0).collect();
+        line_numbers.sort_unstable();
+        line_numbers.dedup();
+
+        let mut prev_line: Option<usize> = None;
+        for line in line_numbers {
+            if let Some(prev_line) = prev_line {
+                if line > prev_line + 1 {
+                    let _ = writeln!(out, "{}", style.gutter("..."));
+                }
+            }
+            prev_line = Some(line);
+
+            let text = lines[line - 1].trim_end_matches('\r');
+            let number = format!("{line:>width$}");
+            let _ = writeln!(out, "{} {text}", style.gutter(&format!("{number} |")));
+
+            let mut line_markers: Vec<_> =
+                markers.iter().filter(|marker| marker.0 == line).collect();
+            line_markers.sort_by_key(|marker| (!marker.4, marker.1));
+            for (_, start_col, end_col, message, is_primary) in line_markers {
+                let ch = match is_primary {
+                    true => "^",
+                    false => "-",
+                };
+                let underline = ch.repeat(end_col - start_col);
+                let marker = match message.is_empty() {
+                    true => underline,
+                    false => format!("{underline} {message}"),
+                };
+                let marker = match is_primary {
+                    true => style.severity(diagnostic.severity, &marker),
+                    false => style.gutter(&marker),
+                };
+                let _ = writeln!(
+                    out,
+                    "{pad} {} {}{}",
+                    style.gutter("|"),
+                    " ".repeat(start_col - 1),
+                    marker
+                );
+            }
+        }
+    }
+
+    for note in &diagnostic.notes {
+        let _ = writeln!(
+            out,
+            "{pad} {} {}: {note}",
+            style.gutter("="),
+            style.bold("note")
+        );
+    }
+
+    out
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct JsonPosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct JsonRange {
+    pub start: JsonPosition,
+    pub end: JsonPosition,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct JsonSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct JsonLocation {
+    pub file: String,
+    pub message: String,
+    pub span: JsonSpan,
+    pub range: JsonRange,
+}
+
+/// The machine readable form of a `Diagnostic`, used by `--format json` and
+/// the wasm `CompileResult`.  Line and column numbers start at 1, `span` is
+/// the byte range in the source.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct JsonDiagnostic {
+    pub file: String,
+    pub severity: Severity,
+    pub code: Option<String>,
+    pub message: String,
+    pub span: Option<JsonSpan>,
+    pub range: Option<JsonRange>,
+    pub related: Vec<JsonLocation>,
+    pub notes: Vec<String>,
+}
+
+fn json_range(src: &str, span: &Span) -> JsonRange {
+    let (start_line, start_col) = line_col(src, span.start);
+    let (end_line, end_col) = line_col(src, span.end);
+    JsonRange {
+        start: JsonPosition {
+            line: start_line,
+            column: start_col,
+        },
+        end: JsonPosition {
+            line: end_line,
+            column: end_col,
+        },
+    }
+}
+
+impl JsonDiagnostic {
+    pub fn new(diagnostic: &Diagnostic, path: &str, src: &str) -> Self {
+        let primary = diagnostic.primary.as_ref();
+        JsonDiagnostic {
+            file: path.to_owned(),
+            severity: diagnostic.severity,
+            code: diagnostic.code.to_owned(),
+            message: diagnostic.message.to_owned(),
+            span: primary.map(|label| JsonSpan {
+                start: label.span.start,
+                end: label.span.end,
+            }),
+            range: primary.map(|label| json_range(src, &label.span)),
+            related: diagnostic
+                .secondary
+                .iter()
+                .map(|label| JsonLocation {
+                    file: path.to_owned(),
+                    message: label.message.to_owned(),
+                    span: JsonSpan {
+                        start: label.span.start,
+                        end: label.span.end,
+                    },
+                    range: json_range(src, &label.span),
+                })
+                .collect(),
+            notes: diagnostic.notes.to_owned(),
+        }
+    }
+}
+
+pub fn to_json(diagnostics: &[Diagnostic], path: &str, src: &str) -> Vec<JsonDiagnostic> {
+    diagnostics
+        .iter()
+        .map(|diagnostic| JsonDiagnostic::new(diagnostic, path, src))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const SRC: &str = "declare let add: (a: number, b: number) => number;\nadd(\"hello\", 5);\n";
+
+    #[test]
+    fn shadowed_binding_points_at_both_bindings() {
+        let warning = Warning::new(
+            WarningKind::ShadowedBinding {
+                name: String::from("x"),
+                previous: 4..5,
+            },
+            &(21..22),
+        );
+        let diagnostic = Diagnostic::from(&warning);
+
+        assert!(!diagnostic.is_error());
+        assert_eq!(diagnostic.code.as_deref(), Some("W0004"));
+        assert_eq!(diagnostic.message, "`x` shadows an earlier binding");
+        assert_eq!(
+            diagnostic.secondary,
+            vec![Label::new(4..5, "`x` was first bound here")]
+        );
+    }
+
+    #[test]
+    fn line_col_counts_from_one() {
+        assert_eq!(line_col(SRC, 0), (1, 1));
+        assert_eq!(line_col(SRC, 55), (2, 5));
+        assert_eq!(line_col("", 10), (1, 1));
+    }
+
+    #[test]
+    fn render_primary_and_secondary_labels() {
+        let diagnostic = Diagnostic::error("Can't unify \"hello\" with number")
+            .with_code("E0004")
+            .with_primary(55..62, "expected `number`, found `\"hello\"`")
+            .with_secondary(21..27, "expected `number` because of this")
+            .with_note("Unification failure");
+
+        let output = render(
+            &[diagnostic],
+            "main.crochet",
+            SRC,
+            &RenderOptions::default(),
+        );
+
+        assert_eq!(
+            output,
+            r#"error[E0004]: Can't unify "hello" with number
+ --> main.crochet:2:5
+  |
+1 | declare let add: (a: number, b: number) => number;
+  |                      ------ expected `number` because of this
+2 | add("hello", 5);
+  |     ^^^^^^^ expected `number`, found `"hello"`
+  = note: Unification failure
+"#
+        );
+    }
+
+    #[test]
+    fn render_without_a_span() {
+        let diagnostic = Diagnostic::error("failed to parse: 'let = 5'");
+
+        let output = render(
+            &[diagnostic],
+            "main.crochet",
+            SRC,
+            &RenderOptions::default(),
+        );
+
+        assert_eq!(
+            output,
+            "error: failed to parse: 'let = 5'\n --> main.crochet\n"
+        );
+    }
+
+    #[test]
+    fn render_with_color() {
+        let diagnostic = Diagnostic::warning("unused variable: `x`").with_primary(0..7, "");
+
+        let output = render(
+            &[diagnostic],
+            "main.crochet",
+            SRC,
+            &RenderOptions { color: true },
+        );
+
+        assert!(output.starts_with("\x1b[1;33mwarning\x1b[0m"));
+        assert!(output.contains("\x1b[1;33m^^^^^^^\x1b[0m"));
+    }
+
+    #[test]
+    fn type_error_to_diagnostic() {
+        let error = TypeError::with_span(
+            TypeErrorKind::UnboundIdentifier {
+                name: String::from("foo"),
+            },
+            &(4..7),
+        );
+
+        let diagnostic = Diagnostic::from(&error);
+
+        assert_eq!(
+            diagnostic,
+            Diagnostic::error("Can't find value: foo")
+                .with_code("E0001")
+                .with_primary(4..7, "not found in this scope")
+        );
+    }
+
+    #[test]
+    fn diagnostic_to_json() {
+        let diagnostic = Diagnostic::error("Can't unify \"hello\" with number")
+            .with_code("E0004")
+            .with_primary(55..62, "expected `number`, found `\"hello\"`")
+            .with_secondary(21..27, "expected `number` because of this");
+
+        let json = serde_json::to_value(to_json(&[diagnostic], "main.crochet", SRC)).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "file": "main.crochet",
+                "severity": "error",
+                "code": "E0004",
+                "message": "Can't unify \"hello\" with number",
+                "span": { "start": 55, "end": 62 },
+                "range": {
+                    "start": { "line": 2, "column": 5 },
+                    "end": { "line": 2, "column": 12 }
+                },
+                "related": [{
+                    "file": "main.crochet",
+                    "message": "expected `number` because of this",
+                    "span": { "start": 21, "end": 27 },
+                    "range": {
+                        "start": { "line": 1, "column": 22 },
+                        "end": { "line": 1, "column": 28 }
+                    }
+                }],
+                "notes": []
+            }])
+        );
+    }
+}
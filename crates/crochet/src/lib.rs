@@ -1,14 +1,30 @@
-use error_stack::{Result, ResultExt};
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::ffi::{CStr, CString};
 use std::os::raw::{c_char, c_void};
 use std::str;
 
-use crochet_dts::parse_dts::parse_dts;
-use crochet_infer::*;
+pub mod compiler;
+pub mod config;
+mod config_error;
+pub mod diagnostics;
+pub mod module_graph;
+pub mod resolve;
+pub mod sarif;
+pub mod vfs;
 
-mod compile_error;
+pub use crate::compiler::{
+    CompileOptions, CompileOutput, CompiledModules, Compiler, Input, Timings, VfsOutput,
+};
+pub use crate::config::Config;
+pub use crate::config_error::ConfigError;
+use crate::diagnostics::{Diagnostic, FileDiagnostics, JsonDiagnostic};
+pub use crate::module_graph::ModuleGraph;
+pub use crate::vfs::VirtualFs;
 
-use crate::compile_error::CompileError;
+/// The file names used for `input` and `lib` when reporting diagnostics.
+const INPUT_PATH: &str = "input.crochet";
+const LIB_PATH: &str = "lib.d.ts";
 
 #[repr(C)]
 pub struct WasmString {
@@ -16,11 +32,63 @@ pub struct WasmString {
     pub length: u32,
 }
 
+/// Returned by `compile` and `compile_files`, it must be freed with `free_compile_result`.
 #[repr(C)]
 pub struct CompileResult {
-    js: WasmString,
-    dts: WasmString,
-    error: WasmString,
+    /// A `CompilePayload` serialized as JSON.
+    payload: WasmString,
+}
+
+/// The result of compiling a single input.  `js` and `dts` are only
+/// provided if there weren't any errors, `diagnostics` includes the
+/// warnings either way.
+#[derive(Clone, Debug, Serialize)]
+pub struct CompilePayload {
+    pub js: Option<String>,
+    pub dts: Option<String>,
+    pub diagnostics: Vec<JsonDiagnostic>,
+    pub timings: Timings,
+}
+
+impl From<CompileOutput> for CompilePayload {
+    fn from(output: CompileOutput) -> Self {
+        CompilePayload {
+            diagnostics: output
+                .diagnostics
+                .iter()
+                .flat_map(FileDiagnostics::to_json)
+                .collect(),
+            js: output.js,
+            dts: output.dts,
+            timings: output.timings,
+        }
+    }
+}
+
+/// The result of compiling several files with `compile_files`.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct VfsPayload {
+    /// Problems that aren't specific to one of the modules, e.g. a `.d.ts`
+    /// file that couldn't be parsed.
+    pub diagnostics: Vec<JsonDiagnostic>,
+    pub modules: BTreeMap<String, CompilePayload>,
+}
+
+impl From<VfsOutput> for VfsPayload {
+    fn from(output: VfsOutput) -> Self {
+        VfsPayload {
+            diagnostics: output
+                .lib_diagnostics
+                .iter()
+                .flat_map(FileDiagnostics::to_json)
+                .collect(),
+            modules: output
+                .modules
+                .into_iter()
+                .map(|(path, output)| (path, CompilePayload::from(output)))
+                .collect(),
+        }
+    }
 }
 
 // A hacky way to allocate / deallocate memory in rust stable.
@@ -45,23 +113,27 @@ pub unsafe extern "C" fn deallocate(ptr: *mut c_void, length: usize) {
     std::mem::drop(Vec::from_raw_parts(ptr, 0, length));
 }
 
-fn _compile(input: &str, lib: &str) -> Result<(String, String), CompileError> {
-    let mut program = match crochet_parser::parse(input).change_context(CompileError) {
-        Ok(program) => program,
-        Err(error) => return Err(error),
-    };
-
-    let js = crochet_codegen::js::codegen_js(&program);
+fn _compile(input: &str, lib: &str) -> CompilePayload {
+    let options = CompileOptions::default().with_lib(Input::text(LIB_PATH, lib));
+    let output = Compiler::new(options).compile_source(INPUT_PATH, input);
+    CompilePayload::from(output)
+}
 
-    // TODO: return errors as part of CompileResult
-    let mut ctx = parse_dts(lib).unwrap();
-    let ctx = match infer_prog(&mut program, &mut ctx).change_context(CompileError) {
-        Ok(ctx) => ctx,
-        Err(error) => return Err(error),
+/// `files` is a JSON object mapping paths to their contents.
+fn _compile_files(files: &str, lib: &str) -> VfsPayload {
+    let fs: VirtualFs = match serde_json::from_str(files) {
+        Ok(fs) => fs,
+        Err(err) => {
+            let diagnostic = Diagnostic::error(format!("invalid file map: {err}"));
+            return VfsPayload {
+                diagnostics: FileDiagnostics::new("", "", vec![diagnostic]).to_json(),
+                ..VfsPayload::default()
+            };
+        }
     };
-    let dts = crochet_codegen::d_ts::codegen_d_ts(&program, &ctx);
 
-    Ok((js, dts))
+    let options = CompileOptions::default().with_lib(Input::text(LIB_PATH, lib));
+    VfsPayload::from(Compiler::compile_vfs(&fs, options))
 }
 
 unsafe fn string_to_wasm_string(input: &str) -> WasmString {
@@ -70,46 +142,97 @@ unsafe fn string_to_wasm_string(input: &str) -> WasmString {
     WasmString { offset, length }
 }
 
+/// Frees the string that was leaked by `string_to_wasm_string`.
+unsafe fn drop_wasm_string(wasm_string: WasmString) {
+    if !wasm_string.offset.is_null() {
+        drop(CString::from_raw(wasm_string.offset as *mut c_char));
+    }
+}
+
+/// Compiles `input` against the declarations in `lib`, both are expected to
+/// be null terminated UTF-8 strings.  The result must be freed with
+/// `free_compile_result`.
 ///
 /// # Safety
 ///
+/// `input` and `lib` must be valid pointers to null terminated strings.
 #[no_mangle]
-pub unsafe extern "C" fn compile(input: *const c_char, lib: *const c_char) -> *const CompileResult {
+pub unsafe extern "C" fn compile(input: *const c_char, lib: *const c_char) -> *mut CompileResult {
     let input = CStr::from_ptr(input).to_str().unwrap();
     let lib = CStr::from_ptr(lib).to_str().unwrap();
 
-    match _compile(input, lib) {
-        Ok((js, dts)) => {
-            let result = CompileResult {
-                js: string_to_wasm_string(&js),
-                dts: string_to_wasm_string(&dts),
-                error: string_to_wasm_string(""),
-            };
-            Box::into_raw(Box::new(result))
-        }
-        Err(error) => {
-            let result = CompileResult {
-                js: string_to_wasm_string(""),
-                dts: string_to_wasm_string(""),
-                error: string_to_wasm_string(&error.to_string()),
-            };
-            Box::into_raw(Box::new(result))
-        }
+    let payload = serde_json::to_string(&_compile(input, lib)).unwrap();
+    let result = CompileResult {
+        payload: string_to_wasm_string(&payload),
+    };
+    Box::into_raw(Box::new(result))
+}
+
+/// Compiles every `.crochet` file in `files` against the declarations in
+/// `lib`, the `.d.ts` files in `files` are only available to the modules
+/// that import them.  `files` is a JSON object that maps paths to their
+/// contents, the payload of the result is a `VfsPayload`.  The result must be
+/// freed with `free_compile_result`.
+///
+/// # Safety
+///
+/// `files` and `lib` must be valid pointers to null terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn compile_files(
+    files: *const c_char,
+    lib: *const c_char,
+) -> *mut CompileResult {
+    let files = CStr::from_ptr(files).to_str().unwrap();
+    let lib = CStr::from_ptr(lib).to_str().unwrap();
+
+    let payload = serde_json::to_string(&_compile_files(files, lib)).unwrap();
+    let result = CompileResult {
+        payload: string_to_wasm_string(&payload),
+    };
+    Box::into_raw(Box::new(result))
+}
+
+/// Frees a result returned by `compile` or `compile_files`.
+///
+/// # Safety
+///
+/// `result` must have been returned by `compile` and not freed already.
+#[no_mangle]
+pub unsafe extern "C" fn free_compile_result(result: *mut CompileResult) {
+    if result.is_null() {
+        return;
     }
+    let result = Box::from_raw(result);
+    drop_wasm_string(result.payload);
 }
 
+/// Frees a string returned by one of the other exports, e.g. `parse`.
+///
+/// # Safety
+///
+/// `string` must have been returned by this module and not freed already.
 #[no_mangle]
+pub unsafe extern "C" fn free_string(string: *mut WasmString) {
+    if string.is_null() {
+        return;
+    }
+    drop_wasm_string(*Box::from_raw(string));
+}
+
+/// Returns the tree-sitter syntax tree for `c_buf`, the result must be freed
+/// with `free_string`.
 ///
 /// # Safety
 ///
-pub unsafe extern "C" fn parse(c_buf: *const c_char) -> *const WasmString {
+/// `c_buf` must be a valid pointer to a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn parse(c_buf: *const c_char) -> *mut WasmString {
     let mut parser = tree_sitter::Parser::new();
     parser
         .set_language(tree_sitter_crochet::language())
         .expect("Error loading crochet language");
 
     let str_slice: &str = CStr::from_ptr(c_buf).to_str().unwrap();
-    println!("str_slice = {str_slice}");
     let tree = parser.parse(str_slice, None).unwrap();
 
     let root = tree.root_node();
@@ -121,3 +244,34 @@ pub unsafe extern "C" fn parse(c_buf: *const c_char) -> *const WasmString {
 #[no_mangle]
 #[cfg(target_family = "wasm")]
 pub extern "C" fn _start() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::Severity;
+
+    #[test]
+    fn lib_that_fails_to_parse_is_a_diagnostic() {
+        let payload = _compile("let x = 5;", "interface Foo {");
+
+        assert_eq!(payload.js, None);
+        assert_eq!(payload.dts, None);
+        assert_eq!(payload.diagnostics.len(), 1);
+
+        let diagnostic = &payload.diagnostics[0];
+        assert_eq!(diagnostic.file, LIB_PATH);
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert!(diagnostic.message.starts_with("failed to parse lib: "));
+    }
+
+    #[test]
+    fn invalid_file_map_is_a_diagnostic() {
+        let payload = _compile_files("[]", "");
+
+        assert!(payload.modules.is_empty());
+        assert_eq!(payload.diagnostics.len(), 1);
+        assert!(payload.diagnostics[0]
+            .message
+            .starts_with("invalid file map: "));
+    }
+}
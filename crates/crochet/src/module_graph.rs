@@ -0,0 +1,340 @@
+use std::collections::{BTreeSet, HashMap};
+use std::io;
+use std::time::Instant;
+
+use crochet_ast::values::{Program, Span, Statement};
+use crochet_infer::{Context, Scope, TypeErrorKind};
+use crochet_parser::{parse_partial, SyntaxError};
+
+use crate::compiler::{elapsed_ms, SOURCE_EXT};
+use crate::resolve::{normalize, resolve, FileSystem};
+
+/// The exports of each module that's been checked, keyed by path.
+pub type Exports = HashMap<String, Scope>;
+
+/// An `import` (or `export ... from`) in a module.
+#[derive(Clone, Debug)]
+pub struct Import {
+    pub specifier: String,
+    /// The span of the specifier string.
+    pub span: Span,
+    /// The file the specifier resolved to, `None` if it couldn't be found.
+    pub path: Option<String>,
+    /// The modules leading back to this one if importing `path` would create
+    /// a cycle, starting and ending with this module.
+    pub cycle: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug)]
+pub enum ModuleKind {
+    /// A `.crochet` module along with the statements that could be parsed.
+    Source {
+        program: Program,
+        syntax_errors: Vec<SyntaxError>,
+    },
+    /// A `.d.ts` file, everything it declares is exported.
+    Declarations,
+}
+
+#[derive(Clone, Debug)]
+pub struct Module {
+    pub path: String,
+    pub src: String,
+    pub kind: ModuleKind,
+    pub imports: Vec<Import>,
+    /// How long it took to parse the module, in milliseconds.  `.d.ts` files
+    /// aren't parsed until they're compiled so this is always 0 for them.
+    pub parse_time: f64,
+}
+
+impl Module {
+    /// Makes the `exports` of the modules this one imports available to the
+    /// `import` statements in it.  Imports that couldn't be resolved, or
+    /// whose module hasn't been checked, are left for `infer_prog` to report.
+    pub fn bind_imports(&self, exports: &Exports, ctx: &mut Context) {
+        for import in &self.imports {
+            if let Some(cycle) = &import.cycle {
+                let kind = TypeErrorKind::ImportCycle {
+                    cycle: cycle.to_owned(),
+                };
+                ctx.insert_module_error(import.specifier.to_owned(), kind);
+            } else if let Some(module_exports) = import.path.as_ref().and_then(|p| exports.get(p)) {
+                ctx.insert_module(import.specifier.to_owned(), module_exports.to_owned());
+            }
+        }
+    }
+}
+
+/// All of the modules that can be reached by following imports from a set of
+/// entry points, sorted so that each module comes after the modules it
+/// imports.
+#[derive(Debug, Default)]
+pub struct ModuleGraph {
+    modules: Vec<Module>,
+    index: HashMap<String, usize>,
+    /// Entry points that couldn't be read.
+    pub errors: Vec<(String, io::Error)>,
+}
+
+impl ModuleGraph {
+    pub fn build<I>(fs: &dyn FileSystem, entries: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        ModuleGraph::default().rebuild(fs, entries, &[])
+    }
+
+    /// Builds the graph again after the files in `changed` have been
+    /// modified.  Only those files are read and parsed again, the other
+    /// modules are reused from this graph.  Imports are always resolved
+    /// again since adding or removing a file can change what they resolve to.
+    pub fn rebuild<I>(self, fs: &dyn FileSystem, entries: I, changed: &[String]) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let changed: Vec<String> = changed.iter().map(|path| normalize(path)).collect();
+        let mut prev: HashMap<String, Module> = self
+            .modules
+            .into_iter()
+            .filter(|module| !changed.contains(&module.path))
+            .map(|module| (module.path.to_owned(), module))
+            .collect();
+
+        let mut graph = ModuleGraph::default();
+        for entry in entries {
+            let mut stack: Vec<String> = vec![];
+            graph.visit(fs, normalize(entry.as_ref()), &mut stack, &mut prev);
+        }
+        graph
+    }
+
+    /// Depth-first search that adds the modules that `path` imports before
+    /// adding `path` itself.  `stack` holds the modules currently being
+    /// visited, importing one of them again means there's a cycle.  Modules
+    /// in `prev` are reused instead of being read and parsed.
+    fn visit(
+        &mut self,
+        fs: &dyn FileSystem,
+        path: String,
+        stack: &mut Vec<String>,
+        prev: &mut HashMap<String, Module>,
+    ) {
+        if self.index.contains_key(&path) {
+            return;
+        }
+
+        let (src, kind, parse_time) = match prev.remove(&path) {
+            Some(module) => (module.src, module.kind, module.parse_time),
+            None => {
+                let src = match fs.read(&path) {
+                    Ok(src) => src,
+                    Err(err) => {
+                        self.errors.push((path, err));
+                        return;
+                    }
+                };
+
+                let start = Instant::now();
+                let kind = match path.ends_with(&format!(".{SOURCE_EXT}")) {
+                    true => {
+                        let (program, syntax_errors) = parse_partial(&src);
+                        ModuleKind::Source {
+                            program,
+                            syntax_errors,
+                        }
+                    }
+                    false => ModuleKind::Declarations,
+                };
+                (src, kind, elapsed_ms(start))
+            }
+        };
+
+        let mut imports = match &kind {
+            ModuleKind::Source { program, .. } => imports_of(program),
+            ModuleKind::Declarations => vec![],
+        };
+
+        stack.push(path.to_owned());
+        for import in &mut imports {
+            import.path = resolve(fs, &path, &import.specifier);
+            if let Some(dep) = &import.path {
+                match stack.iter().position(|visiting| visiting == dep) {
+                    Some(start) => {
+                        let mut cycle = stack[start..].to_vec();
+                        cycle.push(dep.to_owned());
+                        import.cycle = Some(cycle);
+                    }
+                    None => self.visit(fs, dep.to_owned(), stack, prev),
+                }
+            }
+        }
+        stack.pop();
+
+        self.index.insert(path.to_owned(), self.modules.len());
+        self.modules.push(Module {
+            path,
+            src,
+            kind,
+            imports,
+            parse_time,
+        });
+    }
+
+    /// The modules in dependency order.
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    pub fn get(&self, path: &str) -> Option<&Module> {
+        self.index.get(&normalize(path)).map(|i| &self.modules[*i])
+    }
+
+    /// The modules that import `path`, directly or indirectly.
+    pub fn dependents(&self, path: &str) -> BTreeSet<String> {
+        let mut dependents: BTreeSet<String> = BTreeSet::new();
+        let mut queue = vec![normalize(path)];
+        while let Some(path) = queue.pop() {
+            for module in &self.modules {
+                let imports_path = module
+                    .imports
+                    .iter()
+                    .any(|import| import.path.as_ref() == Some(&path));
+                if imports_path && dependents.insert(module.path.to_owned()) {
+                    queue.push(module.path.to_owned());
+                }
+            }
+        }
+        dependents
+    }
+}
+
+fn imports_of(program: &Program) -> Vec<Import> {
+    program
+        .body
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Statement::Import { source, .. }
+            | Statement::Export {
+                source: Some(source),
+                ..
+            } => Some(Import {
+                specifier: source.value.to_owned(),
+                span: source.span.to_owned(),
+                path: None,
+                cycle: None,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::VirtualFs;
+    use pretty_assertions::assert_eq;
+
+    fn paths(graph: &ModuleGraph) -> Vec<&str> {
+        graph
+            .modules()
+            .iter()
+            .map(|module| module.path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn modules_are_sorted_by_dependency() {
+        let fs = VirtualFs::from_iter([
+            (
+                "main.crochet",
+                r#"import {add} from "./math"; import {Point} from "./shapes";"#,
+            ),
+            (
+                "math.crochet",
+                "export let add = (a: number, b: number) => a + b;",
+            ),
+            (
+                "shapes.crochet",
+                r#"export {add} from "./math"; import {Vec} from "vec";"#,
+            ),
+            (
+                "node_modules/vec/index.d.ts",
+                "type Vec = {x: number, y: number};",
+            ),
+        ]);
+        let graph = ModuleGraph::build(&fs, ["./main.crochet"]);
+
+        assert_eq!(
+            paths(&graph),
+            vec![
+                "math.crochet",
+                "node_modules/vec/index.d.ts",
+                "shapes.crochet",
+                "main.crochet"
+            ]
+        );
+        assert_eq!(
+            graph.dependents("math.crochet"),
+            BTreeSet::from([String::from("main.crochet"), String::from("shapes.crochet")])
+        );
+    }
+
+    #[test]
+    fn import_cycles() {
+        let fs = VirtualFs::from_iter([
+            ("a.crochet", r#"import {b} from "./b"; export let a = 5;"#),
+            ("b.crochet", r#"import {a} from "./a"; export let b = 10;"#),
+        ]);
+        let graph = ModuleGraph::build(&fs, ["a.crochet", "b.crochet"]);
+
+        assert_eq!(paths(&graph), vec!["b.crochet", "a.crochet"]);
+        let b = graph.get("b.crochet").unwrap();
+        assert_eq!(
+            b.imports[0].cycle,
+            Some(vec![
+                String::from("a.crochet"),
+                String::from("b.crochet"),
+                String::from("a.crochet")
+            ])
+        );
+        assert_eq!(graph.get("a.crochet").unwrap().imports[0].cycle, None);
+    }
+
+    #[test]
+    fn unreadable_entries_are_errors() {
+        let graph = ModuleGraph::build(&VirtualFs::new(), ["missing.crochet"]);
+
+        assert!(graph.modules().is_empty());
+        assert_eq!(graph.errors.len(), 1);
+        assert_eq!(graph.errors[0].0, "missing.crochet");
+    }
+
+    #[test]
+    fn only_changed_modules_are_read_again() {
+        let mut fs = VirtualFs::from_iter([
+            ("main.crochet", r#"import {add} from "./math";"#),
+            ("math.crochet", "export let add = (a, b) => a + b;"),
+        ]);
+        let graph = ModuleGraph::build(&fs, ["main.crochet"]);
+
+        fs.insert("main.crochet", r#"import {sub} from "./sub";"#);
+        fs.insert("math.crochet", "export let add = 5;");
+        fs.insert("sub.crochet", "export let sub = (a, b) => a - b;");
+        let graph = graph.rebuild(
+            &fs,
+            ["main.crochet", "math.crochet"],
+            &[String::from("main.crochet")],
+        );
+
+        assert_eq!(
+            paths(&graph),
+            vec!["sub.crochet", "main.crochet", "math.crochet"]
+        );
+        assert_eq!(
+            graph.get("math.crochet").unwrap().src,
+            "export let add = (a, b) => a + b;"
+        );
+    }
+}
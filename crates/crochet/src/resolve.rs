@@ -0,0 +1,276 @@
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::compiler::SOURCE_EXT;
+
+/// The files that modules are loaded from.  Paths use `/` as the separator.
+pub trait FileSystem {
+    fn read(&self, path: &str) -> io::Result<String>;
+    fn is_file(&self, path: &str) -> bool;
+}
+
+/// The real file system.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OsFs;
+
+impl FileSystem for OsFs {
+    fn read(&self, path: &str) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn is_file(&self, path: &str) -> bool {
+        Path::new(path).is_file()
+    }
+}
+
+/// The fields of package.json that are used to find a package's types.
+#[derive(Debug, Default, Deserialize)]
+struct PackageJson {
+    types: Option<String>,
+    typings: Option<String>,
+}
+
+/// Resolves the `specifier` in an import in the module at `from` to the path
+/// of the file it refers to.
+///
+/// Relative specifiers can leave off the extension, in which case `.crochet`
+/// is tried before `.d.ts`, and can refer to a directory with an `index`
+/// module.  Other specifiers are packages which are looked for in the
+/// `node_modules` directories above `from`.  A package's declarations come
+/// from the `types` (or `typings`) field in its package.json, its
+/// `index.d.ts`, or from the matching `@types/*` package.
+pub fn resolve(fs: &dyn FileSystem, from: &str, specifier: &str) -> Option<String> {
+    let dir = dirname(from);
+    if specifier.starts_with("./") || specifier.starts_with("../") {
+        return resolve_module(fs, &join(dir, specifier));
+    }
+    if specifier.starts_with('/') {
+        return resolve_module(fs, &normalize(specifier));
+    }
+
+    let (name, subpath) = split_package(specifier);
+    // `@types/scope__name` has the declarations for `@scope/name`.
+    let types_name = match name.strip_prefix('@') {
+        Some(scoped) => scoped.replacen('/', "__", 1),
+        None => name.to_owned(),
+    };
+
+    let mut dir = dir;
+    loop {
+        let node_modules = join(dir, "node_modules");
+        for package_dir in [
+            join(&node_modules, name),
+            join(&node_modules, &format!("@types/{types_name}")),
+        ] {
+            if let Some(path) = resolve_package(fs, &package_dir, subpath) {
+                return Some(path);
+            }
+        }
+        if dir.is_empty() || dir == "/" {
+            return None;
+        }
+        dir = dirname(dir);
+    }
+}
+
+/// Removes `.` and empty segments from `path` and applies `..` segments.
+/// `..` segments that would go above the root of an absolute path are
+/// dropped, leading ones in a relative path are kept.
+pub fn normalize(path: &str) -> String {
+    let root = if path.starts_with('/') { "/" } else { "" };
+    let mut segments: Vec<&str> = vec![];
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => (),
+            ".." => match segments.last() {
+                Some(last) if *last != ".." => {
+                    segments.pop();
+                }
+                _ if root.is_empty() => segments.push(segment),
+                _ => (),
+            },
+            segment => segments.push(segment),
+        }
+    }
+    format!("{root}{}", segments.join("/"))
+}
+
+/// The directory containing `path`, `""` if it's relative and doesn't have
+/// one.
+fn dirname(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) => "/",
+        Some(index) => &path[..index],
+        None => "",
+    }
+}
+
+fn join(dir: &str, path: &str) -> String {
+    if dir.is_empty() {
+        normalize(path)
+    } else {
+        normalize(&format!("{dir}/{path}"))
+    }
+}
+
+/// Splits `specifier` into the package name and the path within it, e.g.
+/// `@scope/name/sub/path` becomes `("@scope/name", Some("sub/path"))`.
+fn split_package(specifier: &str) -> (&str, Option<&str>) {
+    let scope_len = match specifier.starts_with('@') {
+        true => specifier.find('/').map_or(0, |index| index + 1),
+        false => 0,
+    };
+    match specifier[scope_len..].find('/') {
+        Some(index) => {
+            let name_end = scope_len + index;
+            (&specifier[..name_end], Some(&specifier[name_end + 1..]))
+        }
+        None => (specifier, None),
+    }
+}
+
+fn resolve_module(fs: &dyn FileSystem, path: &str) -> Option<String> {
+    let exact = (path.ends_with(&format!(".{SOURCE_EXT}")) || path.ends_with(".d.ts"))
+        .then(|| path.to_owned());
+    exact
+        .into_iter()
+        .chain([
+            format!("{path}.{SOURCE_EXT}"),
+            format!("{path}.d.ts"),
+            format!("{path}/index.{SOURCE_EXT}"),
+            format!("{path}/index.d.ts"),
+        ])
+        .find(|candidate| fs.is_file(candidate))
+}
+
+/// Packages are JavaScript so only their declarations are looked for.
+fn resolve_declarations(fs: &dyn FileSystem, path: &str) -> Option<String> {
+    let path = path.strip_suffix(".d.ts").unwrap_or(path);
+    [format!("{path}.d.ts"), format!("{path}/index.d.ts")]
+        .into_iter()
+        .find(|candidate| fs.is_file(candidate))
+}
+
+fn resolve_package(
+    fs: &dyn FileSystem,
+    package_dir: &str,
+    subpath: Option<&str>,
+) -> Option<String> {
+    if let Some(subpath) = subpath {
+        return resolve_declarations(fs, &join(package_dir, subpath));
+    }
+
+    let package_json = fs
+        .read(&join(package_dir, "package.json"))
+        .ok()
+        .and_then(|json| serde_json::from_str::<PackageJson>(&json).ok());
+    if let Some(types) = package_json.and_then(|package| package.types.or(package.typings)) {
+        let path = join(package_dir, &types);
+        if fs.is_file(&path) {
+            return Some(path);
+        }
+        if let Some(path) = resolve_declarations(fs, &path) {
+            return Some(path);
+        }
+    }
+
+    resolve_declarations(fs, &join(package_dir, "index"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::VirtualFs;
+    use pretty_assertions::assert_eq;
+
+    fn fs() -> VirtualFs {
+        VirtualFs::from_iter([
+            ("main.crochet", ""),
+            ("utils/math.crochet", ""),
+            ("utils/strings.d.ts", ""),
+            ("shapes/index.crochet", ""),
+            (
+                "node_modules/react/package.json",
+                r#"{"types": "types/react.d.ts"}"#,
+            ),
+            ("node_modules/react/types/react.d.ts", ""),
+            (
+                "node_modules/lodash/package.json",
+                r#"{"main": "lodash.js"}"#,
+            ),
+            ("node_modules/@types/lodash/index.d.ts", ""),
+            ("node_modules/@types/lodash/fp.d.ts", ""),
+            ("node_modules/@types/babel__core/index.d.ts", ""),
+            (
+                "utils/node_modules/left-pad/package.json",
+                r#"{"typings": "./index"}"#,
+            ),
+            ("utils/node_modules/left-pad/index.d.ts", ""),
+        ])
+    }
+
+    #[test]
+    fn relative_imports() {
+        let fs = fs();
+
+        assert_eq!(
+            resolve(&fs, "main.crochet", "./utils/math"),
+            Some(String::from("utils/math.crochet"))
+        );
+        assert_eq!(
+            resolve(&fs, "utils/math.crochet", "./strings"),
+            Some(String::from("utils/strings.d.ts"))
+        );
+        assert_eq!(
+            resolve(&fs, "utils/math.crochet", "../main.crochet"),
+            Some(String::from("main.crochet"))
+        );
+        assert_eq!(
+            resolve(&fs, "main.crochet", "./shapes"),
+            Some(String::from("shapes/index.crochet"))
+        );
+        assert_eq!(resolve(&fs, "main.crochet", "./missing"), None);
+    }
+
+    #[test]
+    fn packages() {
+        let fs = fs();
+
+        assert_eq!(
+            resolve(&fs, "main.crochet", "react"),
+            Some(String::from("node_modules/react/types/react.d.ts"))
+        );
+        assert_eq!(
+            resolve(&fs, "main.crochet", "lodash"),
+            Some(String::from("node_modules/@types/lodash/index.d.ts"))
+        );
+        assert_eq!(
+            resolve(&fs, "main.crochet", "lodash/fp"),
+            Some(String::from("node_modules/@types/lodash/fp.d.ts"))
+        );
+        assert_eq!(
+            resolve(&fs, "main.crochet", "@babel/core"),
+            Some(String::from("node_modules/@types/babel__core/index.d.ts"))
+        );
+        assert_eq!(
+            resolve(&fs, "utils/math.crochet", "left-pad"),
+            Some(String::from("utils/node_modules/left-pad/index.d.ts"))
+        );
+        assert_eq!(resolve(&fs, "main.crochet", "left-pad"), None);
+    }
+
+    #[test]
+    fn normalize_paths() {
+        assert_eq!(normalize("./a/../b//c"), "b/c");
+        assert_eq!(normalize("/a/./b/../c"), "/a/c");
+        assert_eq!(normalize("../a/../../b"), "../../b");
+        assert_eq!(normalize("/../a"), "/a");
+        assert_eq!(
+            split_package("@scope/name/sub/path"),
+            ("@scope/name", Some("sub/path"))
+        );
+        assert_eq!(split_package("name"), ("name", None));
+    }
+}
@@ -0,0 +1,137 @@
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+
+use crate::diagnostics::{JsonDiagnostic, JsonRange, JsonSpan, Severity};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+fn physical_location(file: &str, span: &JsonSpan, range: &JsonRange) -> Value {
+    json!({
+        "artifactLocation": { "uri": file },
+        "region": {
+            "startLine": range.start.line,
+            "startColumn": range.start.column,
+            "endLine": range.end.line,
+            "endColumn": range.end.column,
+            "byteOffset": span.start,
+            "byteLength": span.end - span.start,
+        }
+    })
+}
+
+fn result(diagnostic: &JsonDiagnostic) -> Value {
+    let level = match diagnostic.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    };
+
+    let mut text = diagnostic.message.to_owned();
+    for note in &diagnostic.notes {
+        text.push_str(&format!("\nnote: {note}"));
+    }
+
+    let location = match (&diagnostic.span, &diagnostic.range) {
+        (Some(span), Some(range)) => physical_location(&diagnostic.file, span, range),
+        _ => json!({ "artifactLocation": { "uri": diagnostic.file } }),
+    };
+
+    let related: Vec<Value> = diagnostic
+        .related
+        .iter()
+        .enumerate()
+        .map(|(id, related)| {
+            json!({
+                "id": id,
+                "message": { "text": related.message },
+                "physicalLocation": physical_location(&related.file, &related.span, &related.range),
+            })
+        })
+        .collect();
+
+    let mut result = json!({
+        "level": level,
+        "message": { "text": text },
+        "locations": [{ "physicalLocation": location }],
+        "relatedLocations": related,
+    });
+    if let Some(code) = &diagnostic.code {
+        result["ruleId"] = json!(code);
+    }
+    result
+}
+
+/// Converts diagnostics into a SARIF 2.1.0 log with a single run, this is
+/// the format GitHub code scanning uses for annotations.
+pub fn to_sarif(diagnostics: &[JsonDiagnostic]) -> Value {
+    let rules: BTreeSet<&str> = diagnostics
+        .iter()
+        .filter_map(|diagnostic| diagnostic.code.as_deref())
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "crochet",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                }
+            },
+            "results": diagnostics.iter().map(result).collect::<Vec<_>>(),
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::{to_json, Diagnostic};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn diagnostics_to_sarif() {
+        let src = "let x = y;\n";
+        let diagnostics = vec![
+            Diagnostic::error("Can't find value: y")
+                .with_code("E0001")
+                .with_primary(8..9, "not found in this scope"),
+            Diagnostic::error("failed to load lib"),
+        ];
+
+        let sarif = to_sarif(&to_json(&diagnostics, "main.crochet", src));
+
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(
+            sarif["runs"][0]["tool"]["driver"]["rules"],
+            json!([{ "id": "E0001" }])
+        );
+        assert_eq!(
+            sarif["runs"][0]["results"][0],
+            json!({
+                "ruleId": "E0001",
+                "level": "error",
+                "message": { "text": "Can't find value: y" },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "main.crochet" },
+                        "region": {
+                            "startLine": 1,
+                            "startColumn": 9,
+                            "endLine": 1,
+                            "endColumn": 10,
+                            "byteOffset": 8,
+                            "byteLength": 1,
+                        }
+                    }
+                }],
+                "relatedLocations": [],
+            })
+        );
+        assert_eq!(
+            sarif["runs"][0]["results"][1]["locations"][0],
+            json!({ "physicalLocation": { "artifactLocation": { "uri": "main.crochet" } } })
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+
+use crate::compiler::SOURCE_EXT;
+use crate::resolve::{self, FileSystem};
+
+/// An in-memory set of files, used to compile several modules at once when
+/// there isn't a real file system, e.g. in the playground.  Paths use `/` as
+/// the separator and are stored without a leading `./` or `/`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(from = "BTreeMap<String, String>", into = "BTreeMap<String, String>")]
+pub struct VirtualFs {
+    files: BTreeMap<String, String>,
+}
+
+impl VirtualFs {
+    pub fn new() -> Self {
+        VirtualFs::default()
+    }
+
+    pub fn insert(&mut self, path: &str, contents: impl Into<String>) {
+        self.files.insert(normalize(path), contents.into());
+    }
+
+    pub fn get(&self, path: &str) -> Option<&str> {
+        self.files.get(&normalize(path)).map(String::as_str)
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.files.contains_key(&normalize(path))
+    }
+
+    /// All of the files, sorted by path.
+    pub fn files(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.files
+            .iter()
+            .map(|(path, contents)| (path.as_str(), contents.as_str()))
+    }
+
+    /// The `.crochet` modules.
+    pub fn sources(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.files()
+            .filter(|(path, _)| path.ends_with(&format!(".{SOURCE_EXT}")))
+    }
+
+    /// The `.d.ts` files.
+    pub fn libs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.files().filter(|(path, _)| path.ends_with(".d.ts"))
+    }
+}
+
+impl FileSystem for VirtualFs {
+    fn read(&self, path: &str) -> io::Result<String> {
+        match self.get(path) {
+            Some(contents) => Ok(contents.to_owned()),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{path} doesn't exist"),
+            )),
+        }
+    }
+
+    fn is_file(&self, path: &str) -> bool {
+        self.contains(path)
+    }
+}
+
+impl From<BTreeMap<String, String>> for VirtualFs {
+    fn from(files: BTreeMap<String, String>) -> Self {
+        files.into_iter().collect()
+    }
+}
+
+impl From<VirtualFs> for BTreeMap<String, String> {
+    fn from(fs: VirtualFs) -> Self {
+        fs.files
+    }
+}
+
+impl<P: AsRef<str>, C: Into<String>> FromIterator<(P, C)> for VirtualFs {
+    fn from_iter<I: IntoIterator<Item = (P, C)>>(iter: I) -> Self {
+        let mut fs = VirtualFs::new();
+        for (path, contents) in iter {
+            fs.insert(path.as_ref(), contents);
+        }
+        fs
+    }
+}
+
+/// Paths are stored relative to the root of the file system, `..` segments
+/// that would go above it are dropped.
+fn normalize(path: &str) -> String {
+    resolve::normalize(&format!("/{path}"))
+        .trim_start_matches('/')
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn fs() -> VirtualFs {
+        VirtualFs::from_iter([
+            ("./main.crochet", "let x = 5;"),
+            ("/utils/math.crochet", ""),
+            ("utils/strings.d.ts", ""),
+            ("README.md", ""),
+        ])
+    }
+
+    #[test]
+    fn paths_are_normalized() {
+        let fs = fs();
+
+        let paths: Vec<_> = fs.files().map(|(path, _)| path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "README.md",
+                "main.crochet",
+                "utils/math.crochet",
+                "utils/strings.d.ts"
+            ]
+        );
+        assert_eq!(fs.get("utils/../main.crochet"), Some("let x = 5;"));
+    }
+
+    #[test]
+    fn sources_and_libs() {
+        let fs = fs();
+
+        let sources: Vec<_> = fs.sources().map(|(path, _)| path).collect();
+        assert_eq!(sources, vec!["main.crochet", "utils/math.crochet"]);
+        let libs: Vec<_> = fs.libs().map(|(path, _)| path).collect();
+        assert_eq!(libs, vec!["utils/strings.d.ts"]);
+    }
+
+    #[test]
+    fn deserialize_from_a_map() {
+        let fs: VirtualFs = serde_json::from_str(r#"{"./main.crochet": "let x = 5;"}"#).unwrap();
+
+        assert_eq!(fs.get("main.crochet"), Some("let x = 5;"));
+    }
+}
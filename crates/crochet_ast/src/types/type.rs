@@ -5,7 +5,7 @@ use std::fmt;
 use crate::types::keyword::TKeyword;
 use crate::types::lam::TLam;
 use crate::types::lit::TLit;
-use crate::types::obj::TObjElem;
+use crate::types::obj::{TObjElem, TProp};
 use crate::types::provenance::Provenance;
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -37,6 +37,29 @@ pub struct TObject {
     pub elems: Vec<TObjElem>,
 }
 
+/// A sum type declared with `enum`.  Each variant is a distinct class at
+/// runtime so there's no need for a discriminant.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TEnum {
+    pub name: String,
+    pub type_args: Option<Vec<Type>>,
+    pub variants: Vec<TEnumVariant>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TEnumVariant {
+    pub name: String,
+    pub payload: Option<Type>,
+}
+
+/// A trait declared with `trait`.  It's used as a bound on type params, a
+/// type satisfies the bound if it has an `impl` of the trait, see `unify`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TTrait {
+    pub name: String,
+    pub methods: Vec<TProp>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TIndexAccess {
     pub object: Box<Type>,
@@ -86,6 +109,8 @@ pub enum TypeKind {
     KeyOf(Box<Type>),
     IndexAccess(TIndexAccess),
     Generic(TGeneric),
+    Enum(TEnum),
+    Trait(TTrait),
 }
 
 #[derive(Derivative)]
@@ -162,6 +187,13 @@ impl fmt::Display for Type {
             TypeKind::This => write!(f, "this"),
             TypeKind::KeyOf(t) => write!(f, "keyof {t}"),
             TypeKind::IndexAccess(TIndexAccess { object, index }) => write!(f, "{object}[{index}]"),
+            TypeKind::Enum(TEnum {
+                name, type_args, ..
+            }) => match type_args {
+                Some(type_args) => write!(f, "{name}<{}>", join(type_args, ", ")),
+                None => write!(f, "{name}"),
+            },
+            TypeKind::Trait(TTrait { name, .. }) => write!(f, "{name}"),
         }
     }
 }
@@ -0,0 +1,58 @@
+use crate::values::expr::{EFnParam, Expr, Lambda};
+use crate::values::ident::Ident;
+use crate::values::span::Span;
+use crate::values::type_ann::TypeAnn;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Class {
+    pub span: Span,
+    pub ident: Ident,
+    /// The class being extended.  Only identifiers are supported since
+    /// instance types are compared by name.
+    pub super_class: Option<Ident>,
+    pub body: Vec<ClassMember>,
+}
+
+impl Class {
+    pub fn constructor(&self) -> Option<&Constructor> {
+        self.body.iter().find_map(|member| match member {
+            ClassMember::Constructor(constructor) => Some(constructor),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClassMember {
+    Constructor(Constructor),
+    Method(ClassMethod),
+    Prop(ClassProp),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constructor {
+    pub span: Span,
+    pub params: Vec<EFnParam>,
+    pub body: Box<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassMethod {
+    pub span: Span,
+    pub key: Ident,
+    pub lambda: Lambda,
+    pub is_static: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassProp {
+    pub span: Span,
+    pub key: Ident,
+    pub value: Option<Box<Expr>>,
+    pub type_ann: Option<TypeAnn>,
+    pub is_static: bool,
+    /// Fields can only be assigned to outside of the constructor if they're
+    /// declared with `mut`.
+    pub is_mutable: bool,
+    pub is_optional: bool,
+}
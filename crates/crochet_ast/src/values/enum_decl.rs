@@ -0,0 +1,21 @@
+use crate::values::ident::Ident;
+use crate::values::span::Span;
+use crate::values::type_ann::{TypeAnn, TypeParam};
+
+/// A sum type, e.g. `enum IpAddr { V4(string), V6(string) }`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Enum {
+    pub span: Span,
+    pub ident: Ident,
+    pub type_params: Option<Vec<TypeParam>>,
+    pub variants: Vec<EnumVariant>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumVariant {
+    pub span: Span,
+    pub ident: Ident,
+    /// Variants without a payload, e.g. `None` in `enum Option<T> { Some(T), None }`,
+    /// are still instances of their own class at runtime.
+    pub payload: Option<TypeAnn>,
+}
@@ -1,9 +1,14 @@
 use crate::types::Type;
+use crate::values::class::Class;
+use crate::values::enum_decl::Enum;
 use crate::values::ident::*;
 use crate::values::jsx::JSXElement;
-use crate::values::lit::Lit;
-use crate::values::pattern::{Pattern, PatternKind};
+use crate::values::lit::{Lit, Str};
+use crate::values::module::{ExportSpecifier, ImportSpecifier};
+use crate::values::pattern::{binding_names, Pattern, PatternKind};
 use crate::values::span::Span;
+use crate::values::struct_decl::Struct;
+use crate::values::trait_decl::{Impl, Trait};
 use crate::values::type_ann::{TypeAnn, TypeParam};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -11,6 +16,70 @@ pub struct Program {
     pub body: Vec<Statement>,
 }
 
+impl Program {
+    /// Programs without any `import` or `export` statements export all of
+    /// their top-level declarations, which is how every program was treated
+    /// before modules were supported.
+    pub fn exports_all(&self) -> bool {
+        !self.body.iter().any(Statement::is_module_decl)
+    }
+
+    /// Returns `(exported, local)` pairs for each value or type that's
+    /// exported by this program, not including re-exports.  Whether a name
+    /// refers to a value, a type, or both isn't known until type checking.
+    pub fn exported_names(&self) -> Vec<(String, String)> {
+        let mut names: Vec<(String, String)> = vec![];
+        for stmt in &self.body {
+            match stmt {
+                Statement::ExportDecl { decl, .. } => match decl.as_ref() {
+                    Statement::VarDecl { pattern, .. } => {
+                        for name in binding_names(pattern) {
+                            names.push((name.to_owned(), name));
+                        }
+                    }
+                    Statement::TypeDecl { id, .. } => {
+                        names.push((id.name.to_owned(), id.name.to_owned()))
+                    }
+                    Statement::ClassDecl { class, .. } => {
+                        let name = &class.ident.name;
+                        names.push((name.to_owned(), name.to_owned()))
+                    }
+                    Statement::EnumDecl { r#enum, .. } => {
+                        let name = &r#enum.ident.name;
+                        names.push((name.to_owned(), name.to_owned()))
+                    }
+                    Statement::StructDecl { r#struct, .. } => {
+                        let name = &r#struct.ident.name;
+                        names.push((name.to_owned(), name.to_owned()))
+                    }
+                    Statement::TraitDecl { r#trait, .. } => {
+                        let name = &r#trait.ident.name;
+                        names.push((name.to_owned(), name.to_owned()))
+                    }
+                    _ => (),
+                },
+                Statement::Export {
+                    specifiers,
+                    source: None,
+                    ..
+                } => {
+                    for specifier in specifiers {
+                        if let ExportSpecifier::Named {
+                            local, exported, ..
+                        } = specifier
+                        {
+                            let exported = exported.as_ref().unwrap_or(local);
+                            names.push((exported.name.to_owned(), local.name.to_owned()));
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        names
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Statement {
     VarDecl {
@@ -27,10 +96,85 @@ pub enum Statement {
         type_ann: TypeAnn,
         type_params: Option<Vec<TypeParam>>,
     },
+    /// Declares both a value, the class' constructor, and a type, the type
+    /// of its instances.
+    ClassDecl {
+        span: Span,
+        class: Class,
+    },
+    /// Like classes, declares both a value, an object with a constructor for
+    /// each variant, and a type, the union of the variants.
+    EnumDecl {
+        span: Span,
+        r#enum: Enum,
+    },
+    /// Declares both a value, the struct's constructor, and a type, the type
+    /// of its instances.
+    StructDecl {
+        span: Span,
+        r#struct: Struct,
+    },
+    /// Declares both a value, an object with the trait's default
+    /// implementations, and a type that can be used as a bound.
+    TraitDecl {
+        span: Span,
+        r#trait: Trait,
+    },
+    /// Adds the trait's methods to instances of a class or struct.
+    ImplDecl {
+        span: Span,
+        r#impl: Impl,
+    },
     Expr {
         span: Span,
         expr: Box<Expr>,
     }, // NOTE: does not include Expr::Let
+    Import {
+        span: Span,
+        specifiers: Vec<ImportSpecifier>,
+        source: Str,
+    },
+    /// `export {foo, bar as baz};`, or a re-export if `source` is provided,
+    /// e.g. `export {foo} from "./foo";`
+    Export {
+        span: Span,
+        specifiers: Vec<ExportSpecifier>,
+        source: Option<Str>,
+    },
+    /// `export let ...`, `export type ...`, `export class ...`,
+    /// `export enum ...`, `export struct ...`, or `export trait ...`, `decl`
+    /// is either a `VarDecl`, a `TypeDecl`, a `ClassDecl`, an `EnumDecl`, a
+    /// `StructDecl`, or a `TraitDecl`.
+    ExportDecl {
+        span: Span,
+        decl: Box<Statement>,
+    },
+}
+
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::VarDecl { span, .. }
+            | Statement::TypeDecl { span, .. }
+            | Statement::ClassDecl { span, .. }
+            | Statement::EnumDecl { span, .. }
+            | Statement::StructDecl { span, .. }
+            | Statement::TraitDecl { span, .. }
+            | Statement::ImplDecl { span, .. }
+            | Statement::Expr { span, .. }
+            | Statement::Import { span, .. }
+            | Statement::Export { span, .. }
+            | Statement::ExportDecl { span, .. } => span.to_owned(),
+        }
+    }
+
+    /// Imports and exports, these are only allowed at the top level.
+    pub fn is_module_decl(&self) -> bool {
+        matches!(
+            self,
+            Statement::Import { .. } | Statement::Export { .. } | Statement::ExportDecl { .. }
+        )
+    }
 }
 
 // #[derive(Debug, Clone, PartialEq, Eq)]
@@ -45,6 +189,54 @@ pub struct App {
     pub args: Vec<ExprOrSpread>,
 }
 
+/// `arg |> func` is the same as `func(arg)`.  The `_` placeholder can be
+/// used to pass `arg` somewhere else, e.g. `arg |> func(a, _)` is the same as
+/// `func(a, arg)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pipe {
+    pub arg: Box<Expr>,
+    pub func: Box<Expr>,
+    /// The rest of the args, `arg` is passed in between them at `placeholder`.
+    pub args: Vec<ExprOrSpread>,
+    pub placeholder: usize,
+}
+
+impl Pipe {
+    /// Returns the application that the pipe is the same as.
+    pub fn to_app(&self) -> App {
+        let mut args = self.args.to_owned();
+        args.insert(
+            self.placeholder,
+            ExprOrSpread {
+                spread: None,
+                expr: self.arg.to_owned(),
+            },
+        );
+        App {
+            lam: self.func.to_owned(),
+            args,
+        }
+    }
+
+    /// The inverse of `to_app`.
+    pub fn from_app(app: App, placeholder: usize) -> Self {
+        let App { lam, mut args } = app;
+        let arg = args.remove(placeholder).expr;
+        Pipe {
+            arg,
+            func: lam,
+            args,
+            placeholder,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct New {
+    pub expr: Box<Expr>,
+    pub args: Vec<ExprOrSpread>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Fix {
     pub expr: Box<Expr>,
@@ -57,6 +249,43 @@ pub struct IfElse {
     pub alternate: Option<Box<Expr>>,
 }
 
+/// A block that evaluates to its last expression, e.g. `do { let x = 5; x }`.
+/// Bindings declared inside the block aren't visible outside of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Do {
+    pub body: Box<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Try {
+    pub body: Box<Expr>,
+    /// The param is optional even when there's a `catch` clause, e.g.
+    /// `try { ... } catch { ... }`.
+    pub catch_param: Option<EFnParam>,
+    pub catch_body: Option<Box<Expr>>,
+    pub finally: Option<Box<Expr>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Throw {
+    pub arg: Box<Expr>,
+}
+
+/// `for (let pattern of expr) { body }`, evaluates to `undefined`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForOf {
+    pub pattern: Pattern,
+    pub expr: Box<Expr>,
+    pub body: Box<Expr>,
+}
+
+/// `while (cond) { body }`, evaluates to `undefined`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct While {
+    pub cond: Box<Expr>,
+    pub body: Box<Expr>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LetExpr {
     pub pat: Pattern,
@@ -262,9 +491,18 @@ pub struct Arm {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ExprKind {
     App(App),
+    Pipe(Pipe),
+    New(New),
     Fix(Fix),
     Ident(Ident),
     IfElse(IfElse),
+    Do(Do),
+    Try(Try),
+    Throw(Throw),
+    ForOf(ForOf),
+    While(While),
+    Break,
+    Continue,
     JSXElement(JSXElement),
     Lambda(Lambda),
     Let(Let),
@@ -277,6 +515,8 @@ pub enum ExprKind {
     Await(Await),
     Tuple(Tuple),
     Member(Member),
+    This,
+    Super,
     Empty,
     TemplateLiteral(TemplateLiteral),
     TaggedTemplateLiteral(TaggedTemplateLiteral),
@@ -289,3 +529,43 @@ pub struct Expr {
     pub kind: ExprKind,
     pub inferred_type: Option<Type>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ident_expr(name: &str) -> Expr {
+        Expr {
+            span: 0..0,
+            kind: ExprKind::Ident(Ident {
+                span: 0..0,
+                name: String::from(name),
+            }),
+            inferred_type: None,
+        }
+    }
+
+    fn arg(name: &str) -> ExprOrSpread {
+        ExprOrSpread {
+            spread: None,
+            expr: Box::from(ident_expr(name)),
+        }
+    }
+
+    #[test]
+    fn pipe_to_app_passes_arg_at_placeholder() {
+        // x |> f(a, _, b)
+        let pipe = Pipe {
+            arg: Box::from(ident_expr("x")),
+            func: Box::from(ident_expr("f")),
+            args: vec![arg("a"), arg("b")],
+            placeholder: 1,
+        };
+
+        let app = pipe.to_app();
+
+        assert_eq!(app.lam, pipe.func);
+        assert_eq!(app.args, vec![arg("a"), arg("x"), arg("b")]);
+        assert_eq!(Pipe::from_app(app, 1), pipe);
+    }
+}
@@ -1,17 +1,27 @@
+pub mod class;
+pub mod enum_decl;
 pub mod expr;
 pub mod ident;
 pub mod jsx;
 pub mod keyword;
 pub mod lit;
+pub mod module;
 pub mod pattern;
 pub mod span;
+pub mod struct_decl;
+pub mod trait_decl;
 pub mod type_ann;
 
+pub use class::*;
+pub use enum_decl::*;
 pub use expr::*;
 pub use ident::*;
 pub use jsx::*;
 pub use keyword::*;
 pub use lit::*;
+pub use module::*;
 pub use pattern::*;
 pub use span::*;
+pub use struct_decl::*;
+pub use trait_decl::*;
 pub use type_ann::*;
@@ -0,0 +1,65 @@
+use crate::values::ident::Ident;
+use crate::values::span::Span;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportSpecifier {
+    /// `import {foo} from "./foo";` or `import {foo as bar} from "./foo";`
+    Named {
+        span: Span,
+        local: Ident,
+        /// The name the binding was exported with if it's been renamed.
+        imported: Option<Ident>,
+        /// `import {type Foo} from "./foo";` or `import type {Foo} from "./foo";`,
+        /// these are removed from the generated JavaScript.
+        is_type: bool,
+    },
+    /// `import * as foo from "./foo";`
+    Namespace {
+        span: Span,
+        local: Ident,
+        is_type: bool,
+    },
+}
+
+impl ImportSpecifier {
+    /// The name this import is bound to in the importing module.
+    pub fn local(&self) -> &Ident {
+        match self {
+            ImportSpecifier::Named { local, .. } => local,
+            ImportSpecifier::Namespace { local, .. } => local,
+        }
+    }
+
+    pub fn is_type(&self) -> bool {
+        match self {
+            ImportSpecifier::Named { is_type, .. } => *is_type,
+            ImportSpecifier::Namespace { is_type, .. } => *is_type,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportSpecifier {
+    /// `export {foo};` or `export {foo as bar};`
+    Named {
+        span: Span,
+        local: Ident,
+        /// The name the binding is exported with if it's been renamed.
+        exported: Option<Ident>,
+        /// `export {type Foo};` or `export type {Foo};`, these are removed
+        /// from the generated JavaScript.
+        is_type: bool,
+    },
+    /// `export * from "./foo";` or `export * as foo from "./foo";`, only
+    /// valid in re-exports.
+    All { span: Span, exported: Option<Ident> },
+}
+
+impl ExportSpecifier {
+    pub fn is_type(&self) -> bool {
+        match self {
+            ExportSpecifier::Named { is_type, .. } => *is_type,
+            ExportSpecifier::All { .. } => false,
+        }
+    }
+}
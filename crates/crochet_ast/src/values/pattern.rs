@@ -18,6 +18,8 @@ pub enum PatternKind {
     Lit(LitPat),
     Is(IsPat),
     Wildcard(WildcardPat),
+    EnumVariant(EnumVariantPat),
+    Struct(StructPat),
     // This can't be used at the top level similar to rest
     // Assign(AssignPat),
 }
@@ -52,6 +54,22 @@ pub struct IsPat {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WildcardPat {}
 
+/// Matches instances of a struct and destructures their fields, e.g.
+/// `User {name, age}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructPat {
+    pub ident: Ident,
+    pub props: Vec<ObjectPatProp>,
+}
+
+/// Matches a single variant of an enum, e.g. `IpAddr.V4(addr)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumVariantPat {
+    pub r#enum: Ident,
+    pub variant: Ident,
+    pub payload: Option<Box<Pattern>>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RestPat {
     pub arg: Box<Pattern>,
@@ -109,6 +127,8 @@ pub fn is_refutable(pat: &Pattern) -> bool {
         // refutable
         PatternKind::Lit(_) => true,
         PatternKind::Is(_) => true,
+        PatternKind::EnumVariant(_) => true,
+        PatternKind::Struct(_) => true,
 
         // refutable if at least one sub-pattern is refutable
         PatternKind::Object(ObjectPat { props, .. }) => props.iter().any(|prop| match prop {
@@ -133,6 +153,72 @@ pub fn is_irrefutable(pat: &Pattern) -> bool {
     !is_refutable(pat)
 }
 
+/// Returns true if any of the bindings introduced by `pat` were declared with
+/// `mut`, e.g. `let mut x = 5` or `let {mut x, y} = point`.
+pub fn has_mutable_binding(pat: &Pattern) -> bool {
+    match &pat.kind {
+        PatternKind::Ident(BindingIdent { mutable, .. }) => *mutable,
+        PatternKind::Is(IsPat { ident, .. }) => ident.mutable,
+        PatternKind::Rest(RestPat { arg }) => has_mutable_binding(arg),
+        PatternKind::EnumVariant(EnumVariantPat { payload, .. }) => match payload {
+            Some(payload) => has_mutable_binding(payload),
+            None => false,
+        },
+        PatternKind::Object(ObjectPat { props, .. })
+        | PatternKind::Struct(StructPat { props, .. }) => props.iter().any(|prop| match prop {
+            ObjectPatProp::KeyValue(KeyValuePatProp { value, .. }) => has_mutable_binding(value),
+            ObjectPatProp::Shorthand(ShorthandPatProp { ident, .. }) => ident.mutable,
+            ObjectPatProp::Rest(RestPat { arg }) => has_mutable_binding(arg),
+        }),
+        PatternKind::Array(ArrayPat { elems, .. }) => elems
+            .iter()
+            .flatten()
+            .any(|elem| has_mutable_binding(&elem.pattern)),
+        PatternKind::Lit(_) | PatternKind::Wildcard(_) => false,
+    }
+}
+
+/// Returns the names of all of the bindings introduced by `pat` in the order
+/// they appear.
+pub fn binding_names(pat: &Pattern) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    collect_binding_names(pat, &mut names);
+    names
+}
+
+fn collect_binding_names(pat: &Pattern, names: &mut Vec<String>) {
+    match &pat.kind {
+        PatternKind::Ident(BindingIdent { name, .. }) => names.push(name.to_owned()),
+        PatternKind::Is(IsPat { ident, .. }) => names.push(ident.name.to_owned()),
+        PatternKind::Rest(RestPat { arg }) => collect_binding_names(arg, names),
+        PatternKind::EnumVariant(EnumVariantPat { payload, .. }) => {
+            if let Some(payload) = payload {
+                collect_binding_names(payload, names)
+            }
+        }
+        PatternKind::Object(ObjectPat { props, .. })
+        | PatternKind::Struct(StructPat { props, .. }) => {
+            for prop in props {
+                match prop {
+                    ObjectPatProp::KeyValue(KeyValuePatProp { value, .. }) => {
+                        collect_binding_names(value, names)
+                    }
+                    ObjectPatProp::Shorthand(ShorthandPatProp { ident, .. }) => {
+                        names.push(ident.name.to_owned())
+                    }
+                    ObjectPatProp::Rest(RestPat { arg }) => collect_binding_names(arg, names),
+                }
+            }
+        }
+        PatternKind::Array(ArrayPat { elems, .. }) => {
+            for elem in elems.iter().flatten() {
+                collect_binding_names(&elem.pattern, names);
+            }
+        }
+        PatternKind::Lit(_) | PatternKind::Wildcard(_) => (),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -300,4 +386,35 @@ mod tests {
         };
         assert!(is_refutable(&is_pat));
     }
+
+    #[test]
+    fn nested_mut_binding_is_mutable() {
+        let kind = PatternKind::Array(ArrayPat {
+            elems: vec![
+                Some(ArrayPatElem {
+                    pattern: ident_pattern("foo"),
+                    init: None,
+                }),
+                Some(ArrayPatElem {
+                    pattern: Pattern {
+                        span: 0..0,
+                        kind: PatternKind::Ident(BindingIdent {
+                            mutable: true,
+                            ..binding_ident("bar")
+                        }),
+                        inferred_type: None,
+                    },
+                    init: None,
+                }),
+            ],
+            optional: false,
+        });
+        let array = Pattern {
+            span: 0..0,
+            kind,
+            inferred_type: None,
+        };
+        assert!(has_mutable_binding(&array));
+        assert!(!has_mutable_binding(&ident_pattern("foo")));
+    }
 }
@@ -0,0 +1,22 @@
+use crate::values::ident::Ident;
+use crate::values::span::Span;
+use crate::values::type_ann::TypeAnn;
+
+/// A nominal object type, e.g. `struct User { name: string, mut age: number }`.
+/// Structs are constructed by passing all of their fields to the struct's
+/// constructor, e.g. `new User({name: "Alice", age: 30})`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Struct {
+    pub span: Span,
+    pub ident: Ident,
+    pub fields: Vec<StructField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructField {
+    pub span: Span,
+    pub key: Ident,
+    pub type_ann: TypeAnn,
+    /// Fields can only be assigned to if they're declared with `mut`.
+    pub is_mutable: bool,
+}
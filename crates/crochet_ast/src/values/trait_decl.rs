@@ -0,0 +1,36 @@
+use crate::values::class::ClassMethod;
+use crate::values::expr::{EFnParam, Expr};
+use crate::values::ident::Ident;
+use crate::values::span::Span;
+use crate::values::type_ann::TypeAnn;
+
+/// A set of methods that types can implement using `impl` blocks, e.g.
+/// `trait Greet { greet(): string; }`.  Traits can be used as bounds on type
+/// params, e.g. `<T extends Greet>(x: T) => x.greet()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trait {
+    pub span: Span,
+    pub ident: Ident,
+    pub methods: Vec<TraitMethod>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraitMethod {
+    pub span: Span,
+    pub key: Ident,
+    pub params: Vec<EFnParam>,
+    pub return_type: TypeAnn,
+    /// The default implementation, used by impls that don't provide their
+    /// own implementation of the method.
+    pub body: Option<Box<Expr>>,
+}
+
+/// Implements a trait for a class or struct, e.g.
+/// `impl Greet for User { greet(): string { this.name } }`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Impl {
+    pub span: Span,
+    pub r#trait: Ident,
+    pub type_ident: Ident,
+    pub methods: Vec<ClassMethod>,
+}
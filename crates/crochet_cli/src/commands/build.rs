@@ -0,0 +1,120 @@
+use clap::Args;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use crochet::compiler::{CompileOutput, CompiledModules, Compiler};
+
+use crate::driver::*;
+
+#[derive(Args, Debug)]
+pub struct BuildArgs {
+    #[command(flatten)]
+    pub project: ProjectArgs,
+
+    /// Directory to write .js and .d.ts files to, overrides `out_dir` in
+    /// crochet.toml, defaults to next to each source
+    #[arg(long)]
+    pub out_dir: Option<PathBuf>,
+
+    /// Rebuild sources whenever they or any of the libs change
+    #[arg(long)]
+    pub watch: bool,
+
+    /// How to report errors, only human readable output is supported with
+    /// --watch
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+pub fn run(args: &BuildArgs) -> ExitCode {
+    if args.watch {
+        return crate::watch::run(args);
+    }
+
+    let mut reporter = Reporter::new(args.format);
+
+    let project = match args.project.resolve() {
+        Ok(project) => project,
+        Err(failure) => {
+            reporter.report(failure);
+            return reporter.finish();
+        }
+    };
+
+    let compiler = Compiler::new(project.compile_options());
+    let lib_failures = lib_failures(&compiler);
+    if !lib_failures.is_empty() {
+        for failure in lib_failures {
+            reporter.report(failure);
+        }
+        return reporter.finish();
+    }
+
+    let out_dir = project.out_dir(args.out_dir.as_deref());
+
+    let (graph, failures) = module_graph(&project.sources);
+    for failure in failures {
+        reporter.report(failure);
+    }
+
+    // Modules are compiled after the modules they import so that the exports
+    // of those modules are known.  Modules that are imported from outside of
+    // the sources are type checked but aren't written out.
+    let mut compiled = CompiledModules::default();
+    for module in graph.modules() {
+        let output = match compile_module(&compiler, module, &mut compiled) {
+            Ok(Some(output)) => output,
+            Ok(None) => continue,
+            Err(failure) => {
+                reporter.report(failure);
+                continue;
+            }
+        };
+        report_output(&mut reporter, module, &output);
+        if let Some(source) = find_source(&project.sources, module) {
+            if let Err(failure) = write_output(source, out_dir.as_deref(), &output) {
+                reporter.report(failure);
+            }
+        }
+    }
+
+    reporter.finish()
+}
+
+/// Returns the path `source` should be written to with the given extension.
+pub fn output_path(source: &Source, out_dir: Option<&Path>, ext: &str) -> PathBuf {
+    let mut path = match out_dir {
+        Some(out_dir) => match source.path.strip_prefix(&source.root) {
+            Ok(rel_path) => out_dir.join(rel_path),
+            Err(_) => out_dir.join(source.path.file_name().unwrap()),
+        },
+        None => source.path.to_owned(),
+    };
+    path.set_extension(ext);
+    path
+}
+
+/// Writes the `.js` and `.d.ts` files for `source`, nothing is written if
+/// there were any errors.
+pub fn write_output(
+    source: &Source,
+    out_dir: Option<&Path>,
+    output: &CompileOutput,
+) -> Result<(), Failure> {
+    let (js, dts) = match (&output.js, &output.dts) {
+        (Some(js), Some(dts)) => (js, dts),
+        _ => return Ok(()),
+    };
+    let js_path = output_path(source, out_dir, "js");
+    let d_ts_path = output_path(source, out_dir, "d.ts");
+
+    for (path, contents) in [(js_path, js), (d_ts_path, dts)] {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| Failure::Io(parent.to_owned(), err))?;
+        }
+        fs::write(&path, contents).map_err(|err| Failure::Io(path.to_owned(), err))?;
+    }
+
+    Ok(())
+}
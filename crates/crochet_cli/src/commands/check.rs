@@ -0,0 +1,66 @@
+use clap::Args;
+use std::process::ExitCode;
+
+use crochet::compiler::{CompiledModules, Compiler};
+
+use crate::driver::*;
+
+#[derive(Args, Debug)]
+pub struct CheckArgs {
+    #[command(flatten)]
+    pub project: ProjectArgs,
+
+    /// How to report errors
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+/// Type checks every source without writing any output.  All failures are
+/// reported before exiting, see `exit_code` for what the exit status means.
+pub fn run(args: &CheckArgs) -> ExitCode {
+    let mut reporter = Reporter::new(args.format);
+
+    let project = match args.project.resolve() {
+        Ok(project) => project,
+        Err(failure) => {
+            reporter.report(failure);
+            return reporter.finish();
+        }
+    };
+
+    let compiler = Compiler::new(project.compile_options().with_no_emit(true));
+    let lib_failures = lib_failures(&compiler);
+    if !lib_failures.is_empty() {
+        for failure in lib_failures {
+            reporter.report(failure);
+        }
+        return reporter.finish();
+    }
+
+    let (graph, failures) = module_graph(&project.sources);
+    for failure in failures {
+        reporter.report(failure);
+    }
+
+    // Modules are checked after the modules they import so that the exports
+    // of those modules are known.
+    let mut compiled = CompiledModules::default();
+    for module in graph.modules() {
+        match compile_module(&compiler, module, &mut compiled) {
+            Ok(Some(output)) => report_output(&mut reporter, module, &output),
+            Ok(None) => (),
+            Err(failure) => reporter.report(failure),
+        }
+    }
+
+    let failed = reporter.failures().len();
+    if failed > 0 && args.format == OutputFormat::Human {
+        eprintln!(
+            "{} of {} files failed to check",
+            failed,
+            project.sources.len()
+        );
+    }
+
+    reporter.finish()
+}
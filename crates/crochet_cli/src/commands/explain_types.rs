@@ -0,0 +1,215 @@
+use clap::Args;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use crochet::compiler::{CompiledModules, Compiler};
+use crochet::diagnostics::line_col;
+use crochet::module_graph::ModuleGraph;
+use crochet::resolve::{normalize, OsFs};
+use crochet_ast::values::Span;
+use crochet_infer::{Subst, Trace, TraceEvent, TraceEventKind};
+
+use crate::driver::*;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// One line per step, nested steps are indented
+    #[default]
+    Human,
+    /// A JSON array with an object for each step
+    Json,
+}
+
+#[derive(Args, Debug)]
+pub struct ExplainTypesArgs {
+    /// The .crochet file to explain
+    pub file: PathBuf,
+
+    /// .d.ts file with declarations that are available to the file, in
+    /// addition to the libs listed in crochet.toml
+    #[arg(long = "lib")]
+    pub libs: Vec<PathBuf>,
+
+    /// Path to crochet.toml, by default it's searched for starting in the
+    /// current directory
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// How to print the trace
+    #[arg(long, value_enum, default_value_t)]
+    pub format: TraceFormat,
+}
+
+/// Type checks a single file while tracing inference and prints every
+/// unification, instantiation, and generalization that was made.  The trace
+/// is printed even if type checking fails since that's usually when it's
+/// needed the most.  The modules the file imports are checked first but
+/// their traces aren't printed.
+pub fn run(args: &ExplainTypesArgs) -> ExitCode {
+    let project_args = ProjectArgs {
+        inputs: vec![args.file.to_owned()],
+        libs: args.libs.to_owned(),
+        config: args.config.to_owned(),
+    };
+    let mut reporter = Reporter::new(OutputFormat::Human);
+
+    let project = match project_args.resolve() {
+        Ok(project) => project,
+        Err(failure) => {
+            reporter.report(failure);
+            return reporter.finish();
+        }
+    };
+
+    let compiler = Compiler::new(
+        project
+            .compile_options()
+            .with_no_emit(true)
+            .with_trace(true),
+    );
+    let lib_failures = lib_failures(&compiler);
+    if !lib_failures.is_empty() {
+        for failure in lib_failures {
+            reporter.report(failure);
+        }
+        return reporter.finish();
+    }
+
+    let path = args.file.as_path();
+    let file = path.to_string_lossy();
+    let mut graph = ModuleGraph::build(&OsFs, [&file]);
+    if let Some((_, err)) = graph.errors.pop() {
+        reporter.report(Failure::Io(path.to_owned(), err));
+        return reporter.finish();
+    }
+
+    // Everything else in the graph is imported by the file, so it's compiled
+    // last.
+    let mut compiled = CompiledModules::default();
+    for module in graph.modules() {
+        let output = match compile_module(&compiler, module, &mut compiled) {
+            Ok(Some(output)) => output,
+            Ok(None) => continue,
+            Err(failure) => {
+                reporter.report(failure);
+                continue;
+            }
+        };
+        if module.path == normalize(&file) {
+            let trace = output.trace.to_owned().unwrap_or_default();
+            print_trace(&trace, path, &module.src, args.format);
+        }
+        report_output(&mut reporter, module, &output);
+    }
+
+    reporter.finish()
+}
+
+fn print_trace(trace: &Trace, path: &Path, src: &str, format: TraceFormat) {
+    match format {
+        TraceFormat::Human => {
+            for event in &trace.events {
+                println!("{}", format_event(event, src));
+            }
+        }
+        TraceFormat::Json => {
+            let file = path.to_string_lossy();
+            let events: Vec<Value> = trace
+                .events
+                .iter()
+                .map(|event| event_to_json(event, &file, src))
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&events).unwrap());
+        }
+    }
+}
+
+/// Returns the entries in `s` sorted by type variable so that the output is
+/// stable.
+fn sorted_subst(s: &Subst) -> Vec<(String, String)> {
+    let mut entries: Vec<_> = s.iter().collect();
+    entries.sort_by_key(|(id, _)| **id);
+    entries
+        .into_iter()
+        .map(|(id, t)| (format!("t{id}"), t.to_string()))
+        .collect()
+}
+
+fn format_event(event: &TraceEvent, src: &str) -> String {
+    let location = match &event.span {
+        Some(span) => {
+            let (line, column) = line_col(src, span.start);
+            format!("{line}:{column}")
+        }
+        None => String::from("-"),
+    };
+    let indent = "  ".repeat(event.depth);
+
+    let step = match &event.kind {
+        TraceEventKind::Unify { t1, t2, result } => {
+            let result = match result {
+                Some(s) => {
+                    let entries: Vec<String> = sorted_subst(s)
+                        .into_iter()
+                        .map(|(id, t)| format!("{id} := {t}"))
+                        .collect();
+                    format!("[{}]", entries.join(", "))
+                }
+                None => String::from("failed"),
+            };
+            format!("unify {t1} with {t2} -> {result}")
+        }
+        TraceEventKind::Instantiate { t, result } => format!("instantiate {t} as {result}"),
+        TraceEventKind::Generalize { t, result } => format!("generalize {t} to {result}"),
+    };
+
+    format!("{location:<8}{indent}{step}")
+}
+
+fn span_to_json(span: &Span, src: &str) -> Value {
+    let (start_line, start_column) = line_col(src, span.start);
+    let (end_line, end_column) = line_col(src, span.end);
+    json!({
+        "start": span.start,
+        "end": span.end,
+        "range": {
+            "start": { "line": start_line, "column": start_column },
+            "end": { "line": end_line, "column": end_column },
+        },
+    })
+}
+
+fn event_to_json(event: &TraceEvent, file: &str, src: &str) -> Value {
+    let mut value = match &event.kind {
+        TraceEventKind::Unify { t1, t2, result } => json!({
+            "kind": "unify",
+            "t1": t1.to_string(),
+            "t2": t2.to_string(),
+            "subst": result.as_ref().map(|s| {
+                sorted_subst(s)
+                    .into_iter()
+                    .map(|(id, t)| (id, Value::from(t)))
+                    .collect::<serde_json::Map<_, _>>()
+            }),
+        }),
+        TraceEventKind::Instantiate { t, result } => json!({
+            "kind": "instantiate",
+            "t": t.to_string(),
+            "result": result.to_string(),
+        }),
+        TraceEventKind::Generalize { t, result } => json!({
+            "kind": "generalize",
+            "t": t.to_string(),
+            "result": result.to_string(),
+        }),
+    };
+
+    value["file"] = json!(file);
+    value["depth"] = json!(event.depth);
+    value["span"] = match &event.span {
+        Some(span) => span_to_json(span, src),
+        None => Value::Null,
+    };
+    value
+}
@@ -0,0 +1,91 @@
+use clap::Args;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use crochet_fmt::{format, FormatOptions};
+
+use crate::driver::*;
+
+#[derive(Args, Debug)]
+pub struct FmtArgs {
+    /// .crochet files or directories containing them, defaults to the sources
+    /// listed in crochet.toml
+    pub inputs: Vec<PathBuf>,
+
+    /// Path to crochet.toml, by default it's searched for starting in the
+    /// current directory
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Report files that aren't formatted instead of rewriting them
+    #[arg(long)]
+    pub check: bool,
+
+    /// Maximum line width
+    #[arg(long, default_value_t = FormatOptions::default().width)]
+    pub width: usize,
+
+    /// Number of spaces per indentation level
+    #[arg(long, default_value_t = FormatOptions::default().indent_width)]
+    pub indent_width: usize,
+}
+
+/// Formats sources in place.  With `--check` nothing is written and the exit
+/// status is 1 if any of the sources would be changed.
+pub fn run(args: &FmtArgs) -> ExitCode {
+    let sources = load_config(args.config.as_deref())
+        .and_then(|config| project_sources(&args.inputs, config.as_ref()));
+    let sources = match sources {
+        Ok(sources) => sources,
+        Err(failure) => {
+            eprintln!("{failure}");
+            return exit_code(&[failure]);
+        }
+    };
+
+    let options = FormatOptions {
+        width: args.width,
+        indent_width: args.indent_width,
+    };
+
+    let mut failures: Vec<Failure> = vec![];
+    let mut unformatted: Vec<PathBuf> = vec![];
+    for source in &sources {
+        let path = &source.path;
+        let result = fs::read_to_string(path)
+            .map_err(|err| Failure::Io(path.to_owned(), err))
+            .and_then(|src| {
+                let formatted = catch_internal_errors(path, || {
+                    format(&src, &options)
+                        .map_err(|report| Failure::Format(path.to_owned(), report))
+                })?;
+                Ok((src, formatted))
+            });
+
+        match result {
+            Ok((src, formatted)) if src != formatted => {
+                if args.check {
+                    println!("{}", path.display());
+                    unformatted.push(path.to_owned());
+                } else if let Err(err) = fs::write(path, formatted) {
+                    let failure = Failure::Io(path.to_owned(), err);
+                    eprintln!("{failure}");
+                    failures.push(failure);
+                }
+            }
+            Ok(_) => (),
+            Err(failure) => {
+                eprintln!("{failure}");
+                failures.push(failure);
+            }
+        }
+    }
+
+    if failures.is_empty() && !unformatted.is_empty() {
+        eprintln!("{} file(s) need formatting", unformatted.len());
+        return ExitCode::FAILURE;
+    }
+
+    exit_code(&failures)
+}
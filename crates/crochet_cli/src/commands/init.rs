@@ -0,0 +1,49 @@
+use clap::Args;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use crochet::config::{CONFIG_FILE_NAME, STARTER_CONFIG};
+
+use crate::driver::*;
+
+#[derive(Args, Debug)]
+pub struct InitArgs {
+    /// Directory to create the project in
+    #[arg(default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Overwrite an existing crochet.toml
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Writes a starter crochet.toml and creates the `src` directory it refers to.
+pub fn run(args: &InitArgs) -> ExitCode {
+    let path = args.dir.join(CONFIG_FILE_NAME);
+    if path.exists() && !args.force {
+        eprintln!(
+            "error: {} already exists, use --force to overwrite it",
+            path.display()
+        );
+        return ExitCode::from(EXIT_IO_ERROR);
+    }
+
+    let src_dir = args.dir.join("src");
+    let result = fs::create_dir_all(&src_dir)
+        .map_err(|err| Failure::Io(src_dir, err))
+        .and_then(|_| {
+            fs::write(&path, STARTER_CONFIG).map_err(|err| Failure::Io(path.to_owned(), err))
+        });
+
+    match result {
+        Ok(_) => {
+            println!("created {}", path.display());
+            ExitCode::SUCCESS
+        }
+        Err(failure) => {
+            eprintln!("{failure}");
+            exit_code(&[failure])
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub mod build;
+pub mod check;
+pub mod explain_types;
+pub mod fmt;
+pub mod init;
+pub mod repl;
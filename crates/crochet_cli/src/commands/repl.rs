@@ -0,0 +1,186 @@
+use clap::Args;
+use itertools::Itertools;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use crochet::compiler::{merge_lib, CompileOptions, Compiler, Input};
+use crochet::diagnostics::{self, Diagnostic, FileDiagnostics};
+use crochet_ast::values::{Expr, Program, Statement};
+use crochet_infer::{infer_expr, infer_prog, Context};
+use crochet_parser::parse;
+
+use crate::driver::*;
+
+const HELP: &str = "\
+Enter a declaration or an expression to see its inferred type.
+
+Commands:
+  :type <expr>        show the type of <expr> without binding anything
+  :lib <file.d.ts>    load the declarations in <file.d.ts>
+  :help               show this message
+  :quit               exit the repl";
+
+#[derive(Args, Debug)]
+pub struct ReplArgs {
+    /// .d.ts file with declarations to load on startup
+    #[arg(long = "lib")]
+    pub libs: Vec<PathBuf>,
+}
+
+pub fn run(args: &ReplArgs) -> ExitCode {
+    let mut ctx = match load_libs(args.libs.iter().map(Input::file).collect()) {
+        Ok(ctx) => ctx,
+        Err(failures) => {
+            for failure in &failures {
+                eprintln!("{failure}");
+            }
+            return exit_code(&failures);
+        }
+    };
+
+    println!("crochet repl, type :help for help");
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => break, // EOF or an unreadable stdin
+        };
+        let line = line.trim();
+
+        match line.split_once(char::is_whitespace).unwrap_or((line, "")) {
+            ("", _) => (),
+            (":quit" | ":q", _) => break,
+            (":help", _) => println!("{HELP}"),
+            (":type" | ":t", src) => eval_type(src.trim(), &ctx),
+            (":lib", path) => load_lib(Path::new(path.trim()), &mut ctx),
+            (cmd, _) if cmd.starts_with(':') => println!("unknown command: {cmd}"),
+            _ => eval(line, &mut ctx),
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn repl_path() -> PathBuf {
+    PathBuf::from("<repl>")
+}
+
+fn repl_diagnostics(src: &str, diagnostics: Vec<Diagnostic>) -> FileDiagnostics {
+    FileDiagnostics::new(repl_path().to_string_lossy(), src, diagnostics)
+}
+
+/// Loads `libs` and merges their declarations into a single global scope.
+fn load_libs(libs: Vec<Input>) -> Result<Context, Vec<Failure>> {
+    let compiler = Compiler::new(CompileOptions {
+        libs,
+        ..CompileOptions::default()
+    });
+    match compiler.lib_ctx() {
+        Some(ctx) => Ok(ctx.to_owned()),
+        None => Err(lib_failures(&compiler)),
+    }
+}
+
+fn parse_input(src: &str) -> Result<Program, Failure> {
+    catch_internal_errors(&repl_path(), || {
+        parse(src).map_err(|report| {
+            let diagnostics = diagnostics::from_parse_error(&report);
+            Failure::Parse(repl_diagnostics(src, diagnostics))
+        })
+    })
+}
+
+/// Prints the type of each expression in `src` while leaving `ctx` untouched.
+fn eval_type(src: &str, ctx: &Context) {
+    let program = match parse_input(src) {
+        Ok(program) => program,
+        Err(failure) => return eprintln!("{failure}"),
+    };
+
+    for stmt in program.body {
+        match stmt {
+            Statement::Expr { mut expr, .. } => print_expr_type(&mut expr, src, ctx),
+            _ => eprintln!(":type expects an expression"),
+        }
+    }
+}
+
+/// Infers the types of the statements in `src`.  Declarations are added to
+/// `ctx` so that they can be used by later inputs.
+fn eval(src: &str, ctx: &mut Context) {
+    let program = match parse_input(src) {
+        Ok(program) => program,
+        Err(failure) => return eprintln!("{failure}"),
+    };
+
+    for stmt in program.body {
+        if let Statement::Expr { mut expr, .. } = stmt {
+            // Expressions aren't bound to anything so there's no need
+            // to update `ctx`.
+            print_expr_type(&mut expr, src, ctx);
+            continue;
+        }
+
+        let mut program = Program { body: vec![stmt] };
+        let mut new_ctx = ctx.to_owned();
+        let result = catch_internal_errors(&repl_path(), || {
+            infer_prog(&mut program, &mut new_ctx).map_err(|report| {
+                let diagnostics = diagnostics::from_type_errors(&report);
+                Failure::Type(repl_diagnostics(src, diagnostics))
+            })
+        });
+
+        match result {
+            Ok(mut new_ctx) => {
+                // `infer_prog` puts the module's bindings in a new scope, we
+                // fold it into the previous scope so that the number of scopes
+                // doesn't grow with each input.
+                let scope = new_ctx.scopes.pop().unwrap();
+                for (name, t) in scope.types.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+                    println!("type {name} = {t}");
+                    new_ctx.insert_type(name.to_owned(), t.to_owned());
+                }
+                for (name, b) in scope.values.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+                    println!("{name}: {}", b.t);
+                    new_ctx.insert_binding(name.to_owned(), b.to_owned());
+                }
+                *ctx = new_ctx;
+            }
+            Err(failure) => eprintln!("{failure}"),
+        }
+    }
+}
+
+fn print_expr_type(expr: &mut Expr, src: &str, ctx: &Context) {
+    let mut ctx = ctx.to_owned();
+    let result = catch_internal_errors(&repl_path(), || {
+        infer_expr(&mut ctx, expr).map_err(|report| {
+            let diagnostics = diagnostics::from_type_errors(&report);
+            Failure::Type(repl_diagnostics(src, diagnostics))
+        })
+    });
+    match result {
+        Ok(t) => println!("{t}"),
+        Err(failure) => eprintln!("{failure}"),
+    }
+}
+
+fn load_lib(path: &Path, ctx: &mut Context) {
+    match load_libs(vec![Input::file(path)]) {
+        Ok(lib_ctx) => {
+            merge_lib(ctx, &lib_ctx);
+            println!("loaded {}", path.display());
+        }
+        Err(failures) => {
+            for failure in failures {
+                eprintln!("{failure}");
+            }
+        }
+    }
+}
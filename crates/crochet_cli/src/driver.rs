@@ -0,0 +1,553 @@
+use clap::Args;
+use error_stack::{AttachmentKind, FrameKind, Report};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Mutex;
+
+use crochet::compiler::{
+    CompileOptions, CompileOutput, CompiledModules, Compiler, Input, JsOptions, SOURCE_EXT,
+};
+use crochet::diagnostics::{self, Diagnostic, FileDiagnostics, JsonDiagnostic, RenderOptions};
+use crochet::module_graph::{Module, ModuleGraph, ModuleKind};
+use crochet::resolve::{normalize, OsFs};
+use crochet::sarif;
+use crochet::{Config, ConfigError};
+use crochet_fmt::FormatError;
+
+// Exit statuses used by all commands.  Scripts can rely on these to tell a
+// mistake in the user's code apart from a bug in the compiler.
+pub const EXIT_TYPE_ERROR: u8 = 1;
+// 2 is used by clap for invalid command line arguments.
+pub const EXIT_PARSE_ERROR: u8 = 3;
+// Used when a source, lib or crochet.toml can't be read.
+pub const EXIT_IO_ERROR: u8 = 4;
+pub const EXIT_INTERNAL_ERROR: u8 = 101;
+
+/// A `.crochet` file that was found while expanding the inputs passed on the
+/// command line or the source roots in crochet.toml.  `root` is the file or
+/// directory it was found through and is used to mirror the source layout
+/// inside of the output directory.
+#[derive(Clone, Debug)]
+pub struct Source {
+    pub path: PathBuf,
+    pub root: PathBuf,
+}
+
+#[derive(Debug)]
+pub enum Failure {
+    Io(PathBuf, io::Error),
+    /// A lib, or a `.d.ts` file that's imported by a source, that couldn't be
+    /// loaded.
+    Lib(FileDiagnostics),
+    Config(PathBuf, Report<ConfigError>),
+    /// Parse and type errors keep the source text around so that they can
+    /// be rendered with a snippet of the offending code.  Warnings that are
+    /// treated as errors because of `strict.warnings_as_errors` are type
+    /// errors.
+    Parse(FileDiagnostics),
+    Type(FileDiagnostics),
+    Format(PathBuf, Report<FormatError>),
+    Internal(PathBuf, String),
+}
+
+impl Failure {
+    pub fn exit_status(&self) -> u8 {
+        match self {
+            Failure::Io(_, _) | Failure::Lib(_) | Failure::Config(_, _) => EXIT_IO_ERROR,
+            Failure::Parse(_) | Failure::Format(_, _) => EXIT_PARSE_ERROR,
+            Failure::Type(_) => EXIT_TYPE_ERROR,
+            Failure::Internal(_, _) => EXIT_INTERNAL_ERROR,
+        }
+    }
+}
+
+/// Returns the exit code for a run that produced `failures`.  When there are
+/// different kinds of failures the most severe one wins, internal errors are
+/// the most severe and type errors are the least.
+pub fn exit_code(failures: &[Failure]) -> ExitCode {
+    let severity = |status: u8| match status {
+        EXIT_INTERNAL_ERROR => 3,
+        EXIT_IO_ERROR => 2,
+        EXIT_PARSE_ERROR => 1,
+        _ => 0,
+    };
+
+    match failures
+        .iter()
+        .map(|failure| failure.exit_status())
+        .max_by_key(|status| severity(*status))
+    {
+        Some(status) => ExitCode::from(status),
+        None => ExitCode::SUCCESS,
+    }
+}
+
+impl Failure {
+    pub fn path(&self) -> &Path {
+        match self {
+            Failure::Io(path, _)
+            | Failure::Config(path, _)
+            | Failure::Format(path, _)
+            | Failure::Internal(path, _) => path,
+            Failure::Lib(file) | Failure::Parse(file) | Failure::Type(file) => {
+                Path::new(&file.path)
+            }
+        }
+    }
+
+    /// Converts the failure to machine readable diagnostics.  Failures that
+    /// don't point at any source code get a diagnostic without a location.
+    pub fn to_json(&self) -> Vec<JsonDiagnostic> {
+        let path = self.path().to_string_lossy();
+        match self {
+            Failure::Lib(file) | Failure::Parse(file) | Failure::Type(file) => file.to_json(),
+            Failure::Config(_, report) => {
+                let diagnostic = Diagnostic::error(messages(report).join("\n"));
+                diagnostics::to_json(&[diagnostic], &path, "")
+            }
+            Failure::Format(_, report) => {
+                let diagnostic = Diagnostic::error(messages(report).join("\n"));
+                diagnostics::to_json(&[diagnostic], &path, "")
+            }
+            Failure::Io(_, err) => {
+                diagnostics::to_json(&[Diagnostic::error(err.to_string())], &path, "")
+            }
+            Failure::Internal(_, msg) => {
+                let diagnostic = Diagnostic::error(format!("internal compiler error: {msg}"));
+                diagnostics::to_json(&[diagnostic], &path, "")
+            }
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Compiler style messages with source snippets
+    #[default]
+    Human,
+    /// A JSON array of diagnostics
+    Json,
+    /// A SARIF 2.1.0 log, e.g. for GitHub code scanning
+    Sarif,
+}
+
+/// Collects the failures and warnings from a run.  Human readable output is
+/// written to stderr as soon as it's reported, the machine readable formats
+/// are written to stdout as a single document once the run is finished.
+pub struct Reporter {
+    format: OutputFormat,
+    failures: Vec<Failure>,
+    warnings: Vec<FileDiagnostics>,
+}
+
+impl Reporter {
+    pub fn new(format: OutputFormat) -> Self {
+        Reporter {
+            format,
+            failures: vec![],
+            warnings: vec![],
+        }
+    }
+
+    /// Reports warnings, unlike failures these don't affect the exit code.
+    pub fn warn(&mut self, warnings: FileDiagnostics) {
+        if warnings.diagnostics.is_empty() {
+            return;
+        }
+        if self.format == OutputFormat::Human {
+            eprintln!("{}", render(&warnings));
+        }
+        self.warnings.push(warnings);
+    }
+
+    pub fn report(&mut self, failure: Failure) {
+        if self.format == OutputFormat::Human {
+            eprintln!("{failure}");
+        }
+        self.failures.push(failure);
+    }
+
+    pub fn failures(&self) -> &[Failure] {
+        &self.failures
+    }
+
+    /// Writes out the machine readable report, if one was requested, and
+    /// returns the exit code for the run.
+    pub fn finish(self) -> ExitCode {
+        let diagnostics: Vec<JsonDiagnostic> = self
+            .failures
+            .iter()
+            .flat_map(|failure| failure.to_json())
+            .chain(self.warnings.iter().flat_map(|warnings| warnings.to_json()))
+            .collect();
+
+        match self.format {
+            OutputFormat::Human => (),
+            OutputFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(&diagnostics).unwrap())
+            }
+            OutputFormat::Sarif => {
+                let sarif = sarif::to_sarif(&diagnostics);
+                println!("{}", serde_json::to_string_pretty(&sarif).unwrap())
+            }
+        }
+
+        exit_code(&self.failures)
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Io(path, err) => write!(fmt, "error: {}: {err}", path.display()),
+            Failure::Config(path, report) => write_report(fmt, path, report),
+            Failure::Lib(file) | Failure::Parse(file) | Failure::Type(file) => {
+                write!(fmt, "{}", render(file))
+            }
+            Failure::Format(path, report) => write_report(fmt, path, report),
+            Failure::Internal(path, msg) => write!(
+                fmt,
+                "internal compiler error: {}: {msg}\nnote: this is a bug in crochet, please report it",
+                path.display()
+            ),
+        }
+    }
+}
+
+fn write_report<C: error_stack::Context>(
+    fmt: &mut fmt::Formatter<'_>,
+    path: &Path,
+    report: &Report<C>,
+) -> fmt::Result {
+    write!(
+        fmt,
+        "error[{}]: {}",
+        report.current_context(),
+        path.display()
+    )?;
+    for message in messages(report) {
+        write!(fmt, "\n  {message}")?;
+    }
+    Ok(())
+}
+
+/// Renders `file`'s diagnostics with snippets of its source.
+pub fn render(file: &FileDiagnostics) -> String {
+    let options = RenderOptions { color: use_color() };
+    file.render(&options).trim_end().to_owned()
+}
+
+/// Failures are written to stderr, only colour them if it's a terminal and
+/// the user hasn't opted out, see https://no-color.org.
+fn use_color() -> bool {
+    env::var_os("NO_COLOR").is_none() && io::stderr().is_terminal()
+}
+
+/// Returns all of the printable attachments in `report`, skipping contexts
+/// and opaque attachments such as source locations.
+pub fn messages<C>(report: &Report<C>) -> Vec<String> {
+    report
+        .frames()
+        .filter_map(|frame| match frame.kind() {
+            FrameKind::Attachment(AttachmentKind::Printable(attachment)) => {
+                Some(attachment.to_string())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Command line arguments shared by commands that operate on a project.
+#[derive(Args, Debug)]
+pub struct ProjectArgs {
+    /// .crochet files or directories containing them, defaults to the sources
+    /// listed in crochet.toml
+    pub inputs: Vec<PathBuf>,
+
+    /// .d.ts file with declarations that are available to all sources, in
+    /// addition to the libs listed in crochet.toml
+    #[arg(long = "lib")]
+    pub libs: Vec<PathBuf>,
+
+    /// Path to crochet.toml, by default it's searched for starting in the
+    /// current directory
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+}
+
+/// Everything needed to compile a project after combining the command line
+/// arguments with crochet.toml.
+#[derive(Clone, Debug)]
+pub struct Project {
+    pub config: Config,
+    pub sources: Vec<Source>,
+    pub libs: Vec<PathBuf>,
+}
+
+impl Project {
+    pub fn out_dir(&self, out_dir: Option<&Path>) -> Option<PathBuf> {
+        out_dir
+            .map(Path::to_owned)
+            .or_else(|| self.config.out_dir())
+    }
+
+    pub fn js_options(&self) -> JsOptions {
+        self.config.js_options()
+    }
+
+    /// Returns the options for compiling the sources, the libs from the
+    /// command line come after the ones in crochet.toml.
+    pub fn compile_options(&self) -> CompileOptions {
+        CompileOptions {
+            libs: self.libs.iter().map(Input::file).collect(),
+            js: self.js_options(),
+            warnings_as_errors: self.config.strict.warnings_as_errors,
+            ..CompileOptions::default()
+        }
+    }
+}
+
+impl ProjectArgs {
+    pub fn resolve(&self) -> Result<Project, Failure> {
+        let config = load_config(self.config.as_deref())?;
+        let sources = project_sources(&self.inputs, config.as_ref())?;
+
+        let mut libs: Vec<PathBuf> = vec![];
+        if let Some(config) = &config {
+            let path = config.root.join(crochet::config::CONFIG_FILE_NAME);
+            libs.extend(
+                config
+                    .lib_paths()
+                    .map_err(|report| Failure::Config(path, report))?,
+            );
+        }
+        libs.extend(self.libs.iter().cloned());
+
+        Ok(Project {
+            config: config.unwrap_or_default(),
+            sources,
+            libs,
+        })
+    }
+}
+
+/// Loads crochet.toml from `path` if it's provided, otherwise searches for it
+/// in the current directory and its ancestors.
+pub fn load_config(path: Option<&Path>) -> Result<Option<Config>, Failure> {
+    let path = match path {
+        Some(path) => path.to_owned(),
+        None => {
+            let cwd = env::current_dir().map_err(|err| Failure::Io(PathBuf::from("."), err))?;
+            match Config::find(&cwd) {
+                Some(path) => path,
+                None => return Ok(None),
+            }
+        }
+    };
+
+    match Config::load(&path) {
+        Ok(config) => Ok(Some(config)),
+        Err(report) => Err(Failure::Config(path, report)),
+    }
+}
+
+/// Returns `inputs` expanded into sources, or when there aren't any inputs,
+/// the sources from `config`.
+pub fn project_sources(
+    inputs: &[PathBuf],
+    config: Option<&Config>,
+) -> Result<Vec<Source>, Failure> {
+    if !inputs.is_empty() {
+        return collect_sources(inputs);
+    }
+
+    let config = match config {
+        Some(config) => config,
+        None => {
+            let report = Report::new(ConfigError)
+                .attach_printable("no inputs were given and there's no crochet.toml");
+            return Err(Failure::Config(
+                PathBuf::from(crochet::config::CONFIG_FILE_NAME),
+                report,
+            ));
+        }
+    };
+
+    let path = config.root.join(crochet::config::CONFIG_FILE_NAME);
+    let paths = config
+        .sources()
+        .map_err(|report| Failure::Config(path, report))?;
+
+    Ok(paths
+        .into_iter()
+        .map(|path| {
+            let root = config
+                .src
+                .iter()
+                .map(|dir| config.root.join(dir))
+                .find(|dir| path.starts_with(dir))
+                .unwrap_or_else(|| config.root.to_owned());
+            Source { path, root }
+        })
+        .collect())
+}
+
+/// Expands `inputs` into the list of `.crochet` files to compile.  Directories
+/// are searched recursively.
+pub fn collect_sources(inputs: &[PathBuf]) -> Result<Vec<Source>, Failure> {
+    let mut sources: Vec<Source> = vec![];
+
+    for input in inputs {
+        let metadata = fs::metadata(input).map_err(|err| Failure::Io(input.to_owned(), err))?;
+        if metadata.is_dir() {
+            let mut paths: Vec<PathBuf> = vec![];
+            walk_dir(input, &mut paths)?;
+            paths.sort();
+            sources.extend(paths.into_iter().map(|path| Source {
+                path,
+                root: input.to_owned(),
+            }));
+        } else {
+            let root = match input.parent() {
+                Some(parent) => parent.to_owned(),
+                None => PathBuf::new(),
+            };
+            sources.push(Source {
+                path: input.to_owned(),
+                root,
+            });
+        }
+    }
+
+    Ok(sources)
+}
+
+fn walk_dir(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), Failure> {
+    let entries = fs::read_dir(dir).map_err(|err| Failure::Io(dir.to_owned(), err))?;
+    for entry in entries {
+        let path = entry
+            .map_err(|err| Failure::Io(dir.to_owned(), err))?
+            .path();
+        if path.is_dir() {
+            walk_dir(&path, paths)?;
+        } else if path.extension().is_some_and(|ext| ext == SOURCE_EXT) {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Returns a failure for each of the libs that `compiler` couldn't load.
+/// Nothing can be type checked until they're fixed.
+pub fn lib_failures(compiler: &Compiler) -> Vec<Failure> {
+    compiler
+        .lib_diagnostics()
+        .iter()
+        .cloned()
+        .map(Failure::Lib)
+        .collect()
+}
+
+static PANIC_MESSAGE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Runs `f` and converts any panic inside of it, e.g. a `todo!()` in one of
+/// the compiler crates, into a `Failure::Internal` for `path`.
+pub fn catch_internal_errors<T>(
+    path: &Path,
+    f: impl FnOnce() -> Result<T, Failure>,
+) -> Result<T, Failure> {
+    let prev_hook = panic::take_hook();
+    panic::set_hook(Box::new(|info| {
+        *PANIC_MESSAGE.lock().unwrap() = Some(info.to_string());
+    }));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    panic::set_hook(prev_hook);
+
+    match result {
+        Ok(result) => result,
+        Err(_) => {
+            let msg = PANIC_MESSAGE
+                .lock()
+                .unwrap()
+                .take()
+                .unwrap_or_else(|| String::from("unknown panic"));
+            Err(Failure::Internal(path.to_owned(), msg))
+        }
+    }
+}
+
+/// Builds the graph of `sources` and all of the modules they import.
+/// Sources that can't be read are returned as failures.
+pub fn module_graph(sources: &[Source]) -> (ModuleGraph, Vec<Failure>) {
+    rebuild_module_graph(ModuleGraph::default(), sources, &[])
+}
+
+/// Like `module_graph`, but only the modules in `changed` are read and parsed
+/// again, the rest are reused from `graph`.
+pub fn rebuild_module_graph(
+    graph: ModuleGraph,
+    sources: &[Source],
+    changed: &[String],
+) -> (ModuleGraph, Vec<Failure>) {
+    let paths: Vec<_> = sources
+        .iter()
+        .map(|source| source.path.to_string_lossy())
+        .collect();
+    let mut graph = graph.rebuild(&OsFs, &paths, changed);
+    let failures = mem::take(&mut graph.errors)
+        .into_iter()
+        .map(|(path, err)| Failure::Io(PathBuf::from(path), err))
+        .collect();
+    (graph, failures)
+}
+
+/// Returns the source that `module` was found through, `None` if it's only
+/// imported by other modules.
+pub fn find_source<'a>(sources: &'a [Source], module: &Module) -> Option<&'a Source> {
+    sources
+        .iter()
+        .find(|source| normalize(&source.path.to_string_lossy()) == module.path)
+}
+
+/// Compiles `module`, see `Compiler::compile_module`.  Panics inside of the
+/// compiler are returned as a failure for the module.
+pub fn compile_module(
+    compiler: &Compiler,
+    module: &Module,
+    compiled: &mut CompiledModules,
+) -> Result<Option<CompileOutput>, Failure> {
+    catch_internal_errors(Path::new(&module.path), || {
+        Ok(compiler.compile_module(module, compiled))
+    })
+}
+
+/// Reports the diagnostics in `output` for `module`.  Problems with the
+/// `.d.ts` files that `module` imports are reported as lib failures, the
+/// module's own diagnostics are reported as a parse failure if it has any
+/// syntax errors, a type failure if it has any other errors, and as warnings
+/// otherwise.
+pub fn report_output(reporter: &mut Reporter, module: &Module, output: &CompileOutput) {
+    let has_syntax_errors = match &module.kind {
+        ModuleKind::Source { syntax_errors, .. } => !syntax_errors.is_empty(),
+        ModuleKind::Declarations => false,
+    };
+
+    for file in &output.diagnostics {
+        let file = file.to_owned();
+        if file.path != module.path {
+            reporter.report(Failure::Lib(file));
+        } else if has_syntax_errors {
+            reporter.report(Failure::Parse(file));
+        } else if file.has_errors() {
+            reporter.report(Failure::Type(file));
+        } else {
+            reporter.warn(file);
+        }
+    }
+}
@@ -1,3 +1,56 @@
-fn main() {
-    println!("Hello, world!");
+use clap::{Parser, Subcommand};
+use std::process::ExitCode;
+
+mod commands;
+mod driver;
+mod watch;
+
+use commands::build::BuildArgs;
+use commands::check::CheckArgs;
+use commands::explain_types::ExplainTypesArgs;
+use commands::fmt::FmtArgs;
+use commands::init::InitArgs;
+use commands::repl::ReplArgs;
+
+#[derive(Parser, Debug)]
+#[command(name = "crochet", version, about = "The Crochet compiler")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compile .crochet files to .js and .d.ts
+    Build(BuildArgs),
+    /// Type check .crochet files without writing any output
+    ///
+    /// Exits with 1 for type errors, 3 for parse errors, 4 when a file or
+    /// lib can't be read, and 101 for internal compiler errors.
+    Check(CheckArgs),
+    /// Print each step taken while inferring the types in a file
+    ///
+    /// Lists every unification along with the substitution it produced, and
+    /// every instantiation and generalization, with the location in the file
+    /// that caused it.
+    ExplainTypes(ExplainTypesArgs),
+    /// Format .crochet files
+    Fmt(FmtArgs),
+    /// Create a crochet.toml with the default settings
+    Init(InitArgs),
+    /// Start an interactive session that prints inferred types
+    Repl(ReplArgs),
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match &cli.command {
+        Command::Build(args) => commands::build::run(args),
+        Command::Check(args) => commands::check::run(args),
+        Command::ExplainTypes(args) => commands::explain_types::run(args),
+        Command::Fmt(args) => commands::fmt::run(args),
+        Command::Init(args) => commands::init::run(args),
+        Command::Repl(args) => commands::repl::run(args),
+    }
 }
@@ -0,0 +1,288 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crochet::compiler::{CompiledModules, Compiler};
+use crochet::module_graph::{ModuleGraph, ModuleKind};
+use crochet::resolve::normalize;
+use crochet::Config;
+
+use crate::commands::build::{write_output, BuildArgs};
+use crate::driver::*;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Keeps track of everything needed to do incremental rebuilds.  The
+/// `Compiler` is only recreated when one of the lib files or crochet.toml
+/// changes, and only the files that changed are parsed again.  Paths are
+/// normalized so that they match the ones in the `ModuleGraph`.
+struct Watcher<'a> {
+    args: &'a BuildArgs,
+    project: Option<Project>,
+    config_changed: bool,
+    compiler: Option<Compiler>,
+    graph: ModuleGraph,
+    mtimes: HashMap<PathBuf, SystemTime>,
+    // Maps each input to the sources that need to be rebuilt when it changes.
+    dependents: HashMap<PathBuf, HashSet<PathBuf>>,
+    // The exports of each module from the last time it was built, modules
+    // that haven't changed use these instead of being rebuilt.
+    compiled: CompiledModules,
+}
+
+impl<'a> Watcher<'a> {
+    fn new(args: &'a BuildArgs) -> Self {
+        Watcher {
+            args,
+            project: None,
+            config_changed: false,
+            compiler: None,
+            graph: ModuleGraph::default(),
+            mtimes: HashMap::new(),
+            dependents: HashMap::new(),
+            compiled: CompiledModules::default(),
+        }
+    }
+
+    /// Returns the paths of all inputs that were added, modified or removed
+    /// since the last poll.
+    fn poll(&mut self) -> Vec<PathBuf> {
+        let project = match self.args.project.resolve() {
+            Ok(project) => project,
+            Err(failure) => {
+                eprintln!("{failure}");
+                return vec![];
+            }
+        };
+
+        let prev_config: Option<Config> = self.project.take().map(|project| project.config);
+        let config_changed = prev_config.is_some_and(|config| config != project.config);
+
+        let mut changed: Vec<PathBuf> = vec![];
+        // Files that are imported from outside of the sources are watched too.
+        let paths: HashSet<PathBuf> = project
+            .sources
+            .iter()
+            .map(|source| key(&source.path))
+            .chain(project.libs.iter().map(|lib| key(lib)))
+            .chain(self.dependents.keys().cloned())
+            .collect();
+        let mut mtimes: HashMap<PathBuf, SystemTime> = HashMap::new();
+        for path in paths {
+            let mtime = match fs::metadata(&path).and_then(|metadata| metadata.modified()) {
+                Ok(mtime) => mtime,
+                Err(_) => continue, // the file was removed while polling
+            };
+            if config_changed || self.mtimes.get(&path) != Some(&mtime) {
+                changed.push(path.to_owned());
+            }
+            mtimes.insert(path, mtime);
+        }
+        // Removed files are changes too, the modules that import them need to
+        // be rebuilt.
+        changed.extend(
+            self.mtimes
+                .keys()
+                .filter(|path| !mtimes.contains_key(*path))
+                .cloned(),
+        );
+        self.mtimes = mtimes;
+        self.project = Some(project);
+        self.config_changed |= config_changed;
+
+        changed
+    }
+
+    /// Returns the modules in `changed` along with all of their transitive
+    /// dependents.
+    fn invalidate(&self, graph: &ModuleGraph, changed: &[PathBuf]) -> BTreeSet<PathBuf> {
+        let sources: HashSet<PathBuf> = graph
+            .modules()
+            .iter()
+            .filter(|module| matches!(module.kind, ModuleKind::Source { .. }))
+            .map(|module| PathBuf::from(&module.path))
+            .collect();
+        let mut stale: BTreeSet<PathBuf> = BTreeSet::new();
+        let mut queue: Vec<PathBuf> = changed.to_vec();
+
+        while let Some(path) = queue.pop() {
+            if sources.contains(&path) && !stale.insert(path.to_owned()) {
+                continue;
+            }
+            if let Some(dependents) = self.dependents.get(&path) {
+                queue.extend(dependents.iter().filter(|p| !stale.contains(*p)).cloned());
+            }
+        }
+
+        stale
+    }
+
+    fn add_dependency(&mut self, source: &Path, dependency: &Path) {
+        self.dependents
+            .entry(dependency.to_owned())
+            .or_default()
+            .insert(source.to_owned());
+    }
+
+    fn rebuild(&mut self, changed: &[PathBuf]) {
+        let project = match &self.project {
+            Some(project) => project.to_owned(),
+            None => return,
+        };
+
+        // Sources are parsed even if the libs are broken so that the graph
+        // stays up to date with the files on disk.
+        let changed_paths: Vec<String> = changed
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        let (graph, graph_failures) =
+            rebuild_module_graph(mem::take(&mut self.graph), &project.sources, &changed_paths);
+
+        let libs_changed = project.libs.iter().any(|lib| changed.contains(&key(lib)));
+        let recreate = libs_changed || self.config_changed || self.compiler.is_none();
+        if recreate {
+            self.config_changed = false;
+            let compiler = Compiler::new(project.compile_options());
+            let failures = lib_failures(&compiler);
+            for failure in &failures {
+                eprintln!("{failure}");
+            }
+            self.compiler = failures.is_empty().then_some(compiler);
+        }
+
+        let compiler = match self.compiler.take() {
+            Some(compiler) => compiler,
+            // Nothing can be compiled until the libs are fixed.
+            None => {
+                self.graph = graph;
+                return;
+            }
+        };
+
+        let out_dir = project.out_dir(self.args.out_dir.as_deref());
+        let mut reporter = Reporter::new(OutputFormat::Human);
+        for failure in graph_failures {
+            reporter.report(failure);
+        }
+        for module in graph.modules() {
+            let path = Path::new(&module.path);
+            for lib in &project.libs {
+                self.add_dependency(path, &key(lib));
+            }
+            for dependency in module
+                .imports
+                .iter()
+                .filter_map(|import| import.path.as_ref())
+            {
+                self.add_dependency(path, Path::new(dependency));
+            }
+        }
+
+        let stale = self.invalidate(&graph, changed);
+        let mut rebuilt = 0;
+        for module in graph.modules() {
+            let path = PathBuf::from(&module.path);
+            let is_stale = stale.contains(&path)
+                || changed.contains(&path)
+                || recreate
+                || !self.compiled.contains(&module.path);
+            if !is_stale {
+                continue;
+            }
+            if matches!(module.kind, ModuleKind::Source { .. }) {
+                rebuilt += 1;
+            }
+
+            let output = match compile_module(&compiler, module, &mut self.compiled) {
+                Ok(Some(output)) => output,
+                Ok(None) => continue,
+                Err(failure) => {
+                    reporter.report(failure);
+                    continue;
+                }
+            };
+            report_output(&mut reporter, module, &output);
+            if let Some(source) = find_source(&project.sources, module) {
+                if let Err(failure) = write_output(source, out_dir.as_deref(), &output) {
+                    reporter.report(failure);
+                }
+            }
+        }
+        self.compiler = Some(compiler);
+        self.graph = graph;
+
+        eprintln!(
+            "[watch] rebuilt {} file(s) with {} error(s), waiting for changes...",
+            rebuilt,
+            reporter.failures().len()
+        );
+    }
+}
+
+/// Normalizes `path` the same way as the paths in a `ModuleGraph`.
+fn key(path: &Path) -> PathBuf {
+    PathBuf::from(normalize(&path.to_string_lossy()))
+}
+
+/// Builds all of the inputs and then rebuilds them as they change.  This only
+/// returns if the process is interrupted.
+pub fn run(args: &BuildArgs) -> ExitCode {
+    let mut watcher = Watcher::new(args);
+
+    loop {
+        let changed = watcher.poll();
+        if !changed.is_empty() {
+            watcher.rebuild(&changed);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deleting_an_imported_file_invalidates_its_dependents() {
+        let root = std::env::temp_dir().join("crochet_watch_deleting_an_imported_file");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("crochet.toml"), "").unwrap();
+        fs::write(
+            root.join("main.crochet"),
+            "import {a} from \"./a\";\nlet b = a;",
+        )
+        .unwrap();
+        fs::write(root.join("a.crochet"), "export let a = 5;").unwrap();
+
+        let args = BuildArgs {
+            project: ProjectArgs {
+                inputs: vec![root.join("main.crochet")],
+                libs: vec![],
+                config: Some(root.join("crochet.toml")),
+            },
+            out_dir: Some(root.join("out")),
+            watch: true,
+            format: OutputFormat::Human,
+        };
+        let mut watcher = Watcher::new(&args);
+        let changed = watcher.poll();
+        watcher.rebuild(&changed);
+        // The imported file is only watched once it's been found in the graph.
+        assert_eq!(watcher.poll(), vec![key(&root.join("a.crochet"))]);
+        watcher.rebuild(&[key(&root.join("a.crochet"))]);
+
+        fs::remove_file(root.join("a.crochet")).unwrap();
+        let changed = watcher.poll();
+        assert_eq!(changed, vec![key(&root.join("a.crochet"))]);
+        let stale = watcher.invalidate(&watcher.graph, &changed);
+        assert!(stale.contains(&key(&root.join("main.crochet"))));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
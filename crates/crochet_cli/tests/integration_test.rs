@@ -2,8 +2,8 @@ use error_stack::Report;
 
 use crochet_ast::values::{Program, Statement};
 use crochet_codegen::*;
-use crochet_infer::TypeError;
 use crochet_infer::*;
+use crochet_infer::{TypeError, TypeErrorKind};
 use crochet_parser::parse;
 
 use core::{any::TypeId, panic::Location};
@@ -44,7 +44,9 @@ fn infer(input: &str) -> String {
             let mut expr = expr.to_owned();
             infer_expr(&mut ctx, &mut expr)
         }
-        _ => Err(Report::new(TypeError).attach_printable("We can't infer decls yet")),
+        _ => Err(Report::new(TypeError::new(TypeErrorKind::Unsupported {
+            msg: "We can't infer decls yet".to_string(),
+        }))),
     };
     format!("{}", result.unwrap())
 }
@@ -496,7 +498,11 @@ fn infer_let_decl_with_incorrect_type_ann() {
 
     assert_eq!(
         error_messages,
-        vec!["Unification failure", "Location", "TypeError"]
+        vec![
+            "Unification failure",
+            "Location",
+            "TypeError[E0004]: Can't unify 10 with string"
+        ]
     );
 }
 
@@ -604,7 +610,11 @@ fn infer_tuple_with_type_annotation_and_incorrect_element() {
 
     assert_eq!(
         error_messages,
-        vec!["Unification failure", "Location", "TypeError"]
+        vec![
+            "Unification failure",
+            "Location",
+            "TypeError[E0004]: Can't unify 3 with boolean"
+        ]
     );
 }
 
@@ -739,7 +749,7 @@ fn codegen_code_with_type_delcarations() {
     let result = codegen_d_ts(&program, &ctx);
 
     insta::assert_snapshot!(result, @r###"
-    declare type Point = {
+    export declare type Point = {
         readonly x: number;
         readonly y: number;
     };
@@ -836,7 +846,7 @@ fn codegen_object_type_with_optional_property() {
     let result = codegen_d_ts(&program, &ctx);
 
     insta::assert_snapshot!(result, @r###"
-    declare type Point = {
+    export declare type Point = {
         readonly x?: number;
         readonly y: number;
     };
@@ -1482,14 +1492,14 @@ fn infer_if_let_refutable_pattern_with_disjoint_union() {
     let result = codegen_d_ts(&program, &ctx);
 
     insta::assert_snapshot!(result, @r###"
-    declare type Action = {
+    export declare type Action = {
         readonly type: "lineto";
         readonly point: Point;
     } | {
         readonly type: "moveto";
         readonly point: Point;
     };
-    declare type Point = {
+    export declare type Point = {
         readonly x: number;
         readonly y: number;
     };
@@ -9,7 +9,11 @@ use swc_ecma_codegen::*;
 
 use crochet_ast::types::{TFnParam, TGeneric, TObjElem, TPat, TVar, Type, TypeKind};
 use crochet_ast::{types, values};
-use crochet_infer::{get_type_params, Context};
+use crochet_infer::{get_type_params, Binding, Context};
+
+use crate::js::{
+    build_export_all, build_export_specifier, build_import_specifier, build_str, wrap_export,
+};
 
 pub fn codegen_d_ts(program: &values::Program, ctx: &Context) -> String {
     print_d_ts(&build_d_ts(program, ctx))
@@ -33,29 +37,147 @@ fn print_d_ts(program: &Program) -> String {
     String::from_utf8_lossy(&buf).to_string()
 }
 
-fn build_d_ts(_program: &values::Program, ctx: &Context) -> Program {
+fn build_d_ts(program: &values::Program, ctx: &Context) -> Program {
     let current_scope = ctx.scopes.last().unwrap();
+    let export_all = program.exports_all();
+    let exported_names = program.exported_names();
+    let is_exported = |name: &String| {
+        export_all
+            || exported_names
+                .iter()
+                .any(|(exported, local)| exported == name && local == name)
+    };
 
     let mut body: Vec<ModuleItem> = vec![];
 
+    // Imports are kept since exported types may refer to imported ones.
+    for stmt in &program.body {
+        if let values::Statement::Import {
+            specifiers, source, ..
+        } = stmt
+        {
+            body.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                span: DUMMY_SP,
+                specifiers: specifiers.iter().map(build_import_specifier).collect(),
+                src: Box::from(build_str(source)),
+                type_only: false,
+                asserts: None,
+            })));
+        }
+    }
+
+    let classes: Vec<&values::Class> = top_level_decls(program)
+        .filter_map(|stmt| match stmt {
+            values::Statement::ClassDecl { class, .. } => Some(class),
+            _ => None,
+        })
+        .collect();
+    let structs: Vec<&values::Struct> = top_level_decls(program)
+        .filter_map(|stmt| match stmt {
+            values::Statement::StructDecl { r#struct, .. } => Some(r#struct),
+            _ => None,
+        })
+        .collect();
+    // Structs are declared as classes too.
+    let is_class = |exported: &String, local: &String| {
+        exported == local
+            && (classes.iter().any(|class| &class.ident.name == local)
+                || structs.iter().any(|r#struct| &r#struct.ident.name == local))
+    };
+
+    let enums: Vec<&values::Enum> = top_level_decls(program)
+        .filter_map(|stmt| match stmt {
+            values::Statement::EnumDecl { r#enum, .. } => Some(r#enum),
+            _ => None,
+        })
+        .collect();
+
+    // Traits are declared as interfaces, see `build_trait_decl`.
+    let is_trait = |t: &Type| matches!(t.kind, TypeKind::Trait(_));
+
+    // Types that aren't exported are still declared since exported values
+    // may refer to them.  Types exported by name are declared below.  Modules
+    // without imports or exports export all of their types, classes and
+    // values may refer to them.
     for (name, t) in current_scope.types.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
-        let id = Ident {
-            span: DUMMY_SP,
-            sym: JsWord::from(name.to_owned()),
-            optional: false,
-        };
-        let decl = ModuleItem::Stmt(Stmt::Decl(Decl::TsTypeAlias(Box::from(TsTypeAliasDecl {
-            span: DUMMY_SP,
-            declare: true,
-            id,
-            type_params: build_type_params(t),
-            type_ann: Box::from(build_type(t, None)),
-        }))));
+        if (export_all || !is_exported(name)) && !is_class(name, name) && !is_trait(t) {
+            body.push(build_type_alias(name, t, export_all));
+        }
+    }
 
-        body.push(decl);
+    for (exported, local) in &exported_names {
+        if let Some(t) = current_scope.types.get(local) {
+            if !is_class(exported, local) && !is_trait(t) {
+                body.push(build_type_alias(exported, t, true));
+            }
+        }
+    }
+
+    for (name, t) in current_scope.types.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+        if let TypeKind::Trait(tr) = &t.kind {
+            body.push(build_trait_decl(name, tr, is_exported(name)));
+        }
+    }
+
+    // Classes declare both their instance type and their constructor.
+    for class in &classes {
+        body.push(build_class_decl(class, ctx, is_exported(&class.ident.name)));
+    }
+
+    for r#struct in &structs {
+        body.push(build_struct_decl(
+            r#struct,
+            ctx,
+            is_exported(&r#struct.ident.name),
+        ));
+    }
+
+    // Impls add the trait's methods to the class so they're declared by
+    // merging an interface that extends the trait with the class.  The
+    // interface must be exported if and only if the class is.
+    for stmt in top_level_decls(program) {
+        if let values::Statement::ImplDecl { r#impl, .. } = stmt {
+            let name = &r#impl.type_ident.name;
+            if !is_class(name, name) {
+                // TODO: use module augmentation for imported classes
+                continue;
+            }
+            body.push(build_impl_decl(r#impl, is_exported(name)));
+        }
     }
 
-    for (name, b) in current_scope.values.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+    // The values of enums are namespaces with a class for each variant, their
+    // types are declared above as unions of those classes.
+    for r#enum in &enums {
+        body.push(build_enum_decl(
+            r#enum,
+            ctx,
+            is_exported(&r#enum.ident.name),
+        ));
+    }
+
+    let values: Vec<(&String, &Binding)> = if export_all {
+        current_scope
+            .values
+            .iter()
+            .sorted_by(|a, b| a.0.cmp(b.0))
+            .collect()
+    } else {
+        exported_names
+            .iter()
+            .filter_map(|(exported, local)| {
+                current_scope
+                    .values
+                    .get(local)
+                    .map(|binding| (exported, binding))
+            })
+            .collect()
+    };
+
+    for (name, b) in values {
+        if is_class(name, name) || enums.iter().any(|r#enum| &r#enum.ident.name == name) {
+            continue;
+        }
         let type_params = build_type_params(&b.t);
         let id = Ident {
             span: DUMMY_SP,
@@ -88,6 +210,31 @@ fn build_d_ts(_program: &values::Program, ctx: &Context) -> Program {
         body.push(decl);
     }
 
+    // Re-exports are passed through as is, including the types.
+    for stmt in &program.body {
+        if let values::Statement::Export {
+            specifiers,
+            source: Some(source),
+            ..
+        } = stmt
+        {
+            let decl = match specifiers.as_slice() {
+                [values::ExportSpecifier::All { exported: None, .. }] => build_export_all(source),
+                _ => ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+                    span: DUMMY_SP,
+                    specifiers: specifiers
+                        .iter()
+                        .filter_map(build_export_specifier)
+                        .collect(),
+                    src: Some(Box::from(build_str(source))),
+                    type_only: false,
+                    asserts: None,
+                })),
+            };
+            body.push(decl);
+        }
+    }
+
     Program::Module(Module {
         span: DUMMY_SP,
         body,
@@ -95,6 +242,399 @@ fn build_d_ts(_program: &values::Program, ctx: &Context) -> Program {
     })
 }
 
+/// The statements at the top level of `program`, exported declarations are
+/// unwrapped from their `export`.
+fn top_level_decls(program: &values::Program) -> impl Iterator<Item = &values::Statement> {
+    program.body.iter().map(|stmt| match stmt {
+        values::Statement::ExportDecl { decl, .. } => decl.as_ref(),
+        stmt => stmt,
+    })
+}
+
+fn build_class_decl(class: &values::Class, ctx: &Context, export: bool) -> ModuleItem {
+    let name = &class.ident.name;
+    let instance = match ctx.lookup_type(name) {
+        Ok(t) => get_obj_elems(&t),
+        Err(_) => vec![],
+    };
+    let constructor = match ctx.lookup_value(name) {
+        Ok(t) => get_obj_elems(&t),
+        Err(_) => vec![],
+    };
+    let find_type = |elems: &[TObjElem], key: &str| {
+        elems.iter().find_map(|elem| match elem {
+            TObjElem::Prop(prop) if prop.name == key => Some(prop.to_owned()),
+            _ => None,
+        })
+    };
+
+    let mut members: Vec<ClassMember> = vec![];
+    for member in &class.body {
+        match member {
+            values::ClassMember::Constructor(_) => {
+                let params = constructor.iter().find_map(|elem| match elem {
+                    TObjElem::Constructor(callable) => Some(callable.params.to_owned()),
+                    _ => None,
+                });
+                members.push(ClassMember::Constructor(Constructor {
+                    span: DUMMY_SP,
+                    key: PropName::Ident(build_ident("constructor")),
+                    params: params
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|param| {
+                            ParamOrTsParamProp::Param(Param::from(build_fn_param_pat(param)))
+                        })
+                        .collect(),
+                    body: None,
+                    accessibility: None,
+                    is_optional: false,
+                }));
+            }
+            values::ClassMember::Method(values::ClassMethod { key, is_static, .. }) => {
+                let elems = if *is_static { &constructor } else { &instance };
+                let t = match find_type(elems, &key.name) {
+                    Some(prop) => prop.t,
+                    None => continue,
+                };
+                let (params, ret) = match &t.kind {
+                    TypeKind::Generic(TGeneric { t, .. }) => match &t.kind {
+                        TypeKind::Lam(types::TLam { params, ret }) => (params, ret),
+                        _ => continue,
+                    },
+                    TypeKind::Lam(types::TLam { params, ret }) => (params, ret),
+                    _ => continue,
+                };
+                members.push(ClassMember::Method(ClassMethod {
+                    span: DUMMY_SP,
+                    key: PropName::Ident(build_ident(&key.name)),
+                    function: Box::from(Function {
+                        params: params
+                            .iter()
+                            .map(|param| Param::from(build_fn_param_pat(param)))
+                            .collect(),
+                        decorators: vec![],
+                        span: DUMMY_SP,
+                        body: None,
+                        is_generator: false,
+                        is_async: false,
+                        type_params: build_type_params(&t),
+                        return_type: Some(Box::from(TsTypeAnn {
+                            span: DUMMY_SP,
+                            type_ann: Box::from(build_type(ret, None)),
+                        })),
+                    }),
+                    kind: MethodKind::Method,
+                    is_static: *is_static,
+                    accessibility: None,
+                    is_abstract: false,
+                    is_optional: false,
+                    is_override: false,
+                }));
+            }
+            values::ClassMember::Prop(values::ClassProp { key, is_static, .. }) => {
+                let elems = if *is_static { &constructor } else { &instance };
+                let prop = match find_type(elems, &key.name) {
+                    Some(prop) => prop,
+                    None => continue,
+                };
+                members.push(ClassMember::ClassProp(ClassProp {
+                    span: DUMMY_SP,
+                    key: PropName::Ident(build_ident(&key.name)),
+                    value: None,
+                    type_ann: Some(Box::from(TsTypeAnn {
+                        span: DUMMY_SP,
+                        type_ann: Box::from(build_type(&prop.t, build_type_params(&prop.t))),
+                    })),
+                    is_static: *is_static,
+                    decorators: vec![],
+                    accessibility: None,
+                    is_abstract: false,
+                    is_optional: prop.optional,
+                    is_override: false,
+                    readonly: !prop.mutable,
+                    declare: false,
+                    definite: false,
+                }));
+            }
+        }
+    }
+
+    let decl = Decl::Class(ClassDecl {
+        ident: build_ident(name),
+        declare: true,
+        class: Box::from(Class {
+            span: DUMMY_SP,
+            decorators: vec![],
+            body: members,
+            super_class: class
+                .super_class
+                .as_ref()
+                .map(|ident| Box::from(Expr::Ident(build_ident(&ident.name)))),
+            is_abstract: false,
+            type_params: None,
+            super_type_params: None,
+            implements: vec![],
+        }),
+    });
+
+    wrap_export(decl, export)
+}
+
+fn build_trait_decl(name: &str, tr: &types::TTrait, export: bool) -> ModuleItem {
+    let body: Vec<TsTypeElement> = tr
+        .methods
+        .iter()
+        .filter_map(|method| match &method.t.kind {
+            TypeKind::Lam(types::TLam { params, ret }) => {
+                Some(TsTypeElement::TsMethodSignature(TsMethodSignature {
+                    span: DUMMY_SP,
+                    readonly: false,
+                    key: Box::from(Expr::Ident(build_ident(&method.name))),
+                    computed: false,
+                    optional: false,
+                    params: build_fn_params(params),
+                    type_ann: Some(Box::from(TsTypeAnn {
+                        span: DUMMY_SP,
+                        type_ann: Box::from(build_type(ret, None)),
+                    })),
+                    type_params: None,
+                }))
+            }
+            _ => None,
+        })
+        .collect();
+
+    build_interface_decl(name, vec![], body, export)
+}
+
+/// e.g. `interface User extends Greet {}`
+fn build_impl_decl(r#impl: &values::Impl, export: bool) -> ModuleItem {
+    let extends = vec![TsExprWithTypeArgs {
+        span: DUMMY_SP,
+        expr: Box::from(Expr::Ident(build_ident(&r#impl.r#trait.name))),
+        type_args: None,
+    }];
+
+    build_interface_decl(&r#impl.type_ident.name, extends, vec![], export)
+}
+
+fn build_interface_decl(
+    name: &str,
+    extends: Vec<TsExprWithTypeArgs>,
+    body: Vec<TsTypeElement>,
+    export: bool,
+) -> ModuleItem {
+    let decl = Decl::TsInterface(Box::from(TsInterfaceDecl {
+        span: DUMMY_SP,
+        id: build_ident(name),
+        declare: true,
+        type_params: None,
+        extends,
+        body: TsInterfaceBody {
+            span: DUMMY_SP,
+            body,
+        },
+    }));
+
+    wrap_export(decl, export)
+}
+
+/// The constructor of a struct takes an object with a value for each field.
+fn build_struct_decl(r#struct: &values::Struct, ctx: &Context, export: bool) -> ModuleItem {
+    let name = &r#struct.ident.name;
+    let fields = match ctx.lookup_type(name) {
+        Ok(t) => get_obj_elems(&t),
+        Err(_) => vec![],
+    };
+    let constructor = match ctx.lookup_value(name) {
+        Ok(t) => get_obj_elems(&t),
+        Err(_) => vec![],
+    };
+
+    let mut members: Vec<ClassMember> = fields
+        .iter()
+        .filter_map(|elem| match elem {
+            TObjElem::Prop(prop) => Some(ClassMember::ClassProp(ClassProp {
+                span: DUMMY_SP,
+                key: PropName::Ident(build_ident(&prop.name)),
+                value: None,
+                type_ann: Some(Box::from(TsTypeAnn {
+                    span: DUMMY_SP,
+                    type_ann: Box::from(build_type(&prop.t, build_type_params(&prop.t))),
+                })),
+                is_static: false,
+                decorators: vec![],
+                accessibility: None,
+                is_abstract: false,
+                is_optional: false,
+                is_override: false,
+                readonly: !prop.mutable,
+                declare: false,
+                definite: false,
+            })),
+            _ => None,
+        })
+        .collect();
+    let params = constructor.iter().find_map(|elem| match elem {
+        TObjElem::Constructor(callable) => Some(callable.params.to_owned()),
+        _ => None,
+    });
+    members.push(ClassMember::Constructor(Constructor {
+        span: DUMMY_SP,
+        key: PropName::Ident(build_ident("constructor")),
+        params: params
+            .unwrap_or_default()
+            .iter()
+            .map(|param| ParamOrTsParamProp::Param(Param::from(build_fn_param_pat(param))))
+            .collect(),
+        body: None,
+        accessibility: None,
+        is_optional: false,
+    }));
+
+    let decl = Decl::Class(ClassDecl {
+        ident: build_ident(name),
+        declare: true,
+        class: Box::from(Class {
+            span: DUMMY_SP,
+            decorators: vec![],
+            body: members,
+            super_class: None,
+            is_abstract: false,
+            type_params: None,
+            super_type_params: None,
+            implements: vec![],
+        }),
+    });
+
+    wrap_export(decl, export)
+}
+
+/// Each variant is declared as a class so that TypeScript can narrow enums
+/// using `instanceof`.  Variants are branded with a private field, otherwise
+/// variants with the same payload would be interchangeable.
+fn build_enum_decl(r#enum: &values::Enum, ctx: &Context, export: bool) -> ModuleItem {
+    let name = &r#enum.ident.name;
+    let t = match ctx.lookup_type(name) {
+        Ok(t) => t,
+        Err(_) => return ModuleItem::Stmt(Stmt::Empty(EmptyStmt { span: DUMMY_SP })),
+    };
+    let variants = match &t.kind {
+        TypeKind::Generic(TGeneric { t, .. }) => match &t.kind {
+            TypeKind::Enum(types::TEnum { variants, .. }) => variants,
+            _ => return ModuleItem::Stmt(Stmt::Empty(EmptyStmt { span: DUMMY_SP })),
+        },
+        TypeKind::Enum(types::TEnum { variants, .. }) => variants,
+        _ => return ModuleItem::Stmt(Stmt::Empty(EmptyStmt { span: DUMMY_SP })),
+    };
+
+    let classes: Vec<ModuleItem> = variants
+        .iter()
+        .map(|variant| {
+            let mut members = vec![ClassMember::PrivateProp(PrivateProp {
+                span: DUMMY_SP,
+                key: PrivateName {
+                    span: DUMMY_SP,
+                    id: build_ident("private"),
+                },
+                value: None,
+                type_ann: None,
+                is_static: false,
+                decorators: vec![],
+                accessibility: None,
+                is_optional: false,
+                is_override: false,
+                readonly: false,
+                definite: false,
+            })];
+            if let Some(payload) = &variant.payload {
+                let type_ann = Some(Box::from(TsTypeAnn {
+                    span: DUMMY_SP,
+                    type_ann: Box::from(build_type(payload, None)),
+                }));
+                members.push(ClassMember::ClassProp(ClassProp {
+                    span: DUMMY_SP,
+                    key: PropName::Ident(build_ident("payload")),
+                    value: None,
+                    type_ann: type_ann.to_owned(),
+                    is_static: false,
+                    decorators: vec![],
+                    accessibility: None,
+                    is_abstract: false,
+                    is_optional: false,
+                    is_override: false,
+                    readonly: true,
+                    declare: false,
+                    definite: false,
+                }));
+                members.push(ClassMember::Constructor(Constructor {
+                    span: DUMMY_SP,
+                    key: PropName::Ident(build_ident("constructor")),
+                    params: vec![ParamOrTsParamProp::Param(Param::from(Pat::Ident(
+                        BindingIdent {
+                            id: build_ident("payload"),
+                            type_ann,
+                        },
+                    )))],
+                    body: None,
+                    accessibility: None,
+                    is_optional: false,
+                }));
+            }
+            ModuleItem::Stmt(Stmt::Decl(Decl::Class(ClassDecl {
+                ident: build_ident(&variant.name),
+                declare: false,
+                class: Box::from(Class {
+                    span: DUMMY_SP,
+                    decorators: vec![],
+                    body: members,
+                    super_class: None,
+                    is_abstract: false,
+                    // Every variant has all of the enum's type params so
+                    // that the enum's type is a union of them.
+                    type_params: build_type_params(&t),
+                    super_type_params: None,
+                    implements: vec![],
+                }),
+            })))
+        })
+        .collect();
+
+    let decl = Decl::TsModule(Box::from(TsModuleDecl {
+        span: DUMMY_SP,
+        declare: true,
+        global: false,
+        id: TsModuleName::Ident(build_ident(name)),
+        body: Some(TsNamespaceBody::TsModuleBlock(TsModuleBlock {
+            span: DUMMY_SP,
+            body: classes,
+        })),
+    }));
+
+    wrap_export(decl, export)
+}
+
+fn get_obj_elems(t: &Type) -> Vec<TObjElem> {
+    match &t.kind {
+        TypeKind::Generic(TGeneric { t, .. }) => get_obj_elems(t),
+        TypeKind::Object(types::TObject { elems }) => elems.to_owned(),
+        _ => vec![],
+    }
+}
+
+fn build_type_alias(name: &str, t: &Type, export: bool) -> ModuleItem {
+    let decl = Decl::TsTypeAlias(Box::from(TsTypeAliasDecl {
+        span: DUMMY_SP,
+        declare: true,
+        id: build_ident(name),
+        type_params: build_type_params(t),
+        type_ann: Box::from(build_type(t, None)),
+    }));
+
+    wrap_export(decl, export)
+}
+
 // TODO: create a trait for this and then provide multiple implementations
 pub fn build_ident(name: &str) -> Ident {
     Ident {
@@ -225,6 +765,10 @@ pub fn build_param_pat_rec(pattern: &values::Pattern, type_ann: Option<Box<TsTyp
         values::PatternKind::Wildcard(_) => {
             panic!("Wildcard patterns are not allowed in params")
         }
+        values::PatternKind::EnumVariant(_) => {
+            panic!("Enum variant patterns are not allowed in params")
+        }
+        values::PatternKind::Struct(_) => panic!("Struct patterns are not allowed in params"),
     }
 }
 
@@ -320,39 +864,9 @@ pub fn build_ts_fn_type_with_params(
     ret: &Type,
     type_params: Option<Box<TsTypeParamDecl>>,
 ) -> TsType {
-    let params: Vec<TsFnParam> = params
-        .iter()
-        .map(|param| {
-            let type_ann = Some(Box::from(TsTypeAnn {
-                span: DUMMY_SP,
-                type_ann: Box::from(build_type(&param.t, None)),
-            }));
-
-            let pat = tpat_to_pat(&param.pat, type_ann);
-
-            let result: TsFnParam = match pat {
-                Pat::Ident(bi) => {
-                    let id = Ident {
-                        optional: param.optional,
-                        ..bi.id
-                    };
-                    TsFnParam::Ident(BindingIdent { id, ..bi })
-                }
-                Pat::Array(array) => TsFnParam::Array(array),
-                Pat::Rest(rest) => TsFnParam::Rest(rest),
-                Pat::Object(obj) => TsFnParam::Object(obj),
-                Pat::Assign(_) => todo!(),
-                Pat::Invalid(_) => todo!(),
-                Pat::Expr(_) => todo!(),
-            };
-
-            result
-        })
-        .collect();
-
     TsType::TsFnOrConstructorType(TsFnOrConstructorType::TsFnType(TsFnType {
         span: DUMMY_SP,
-        params,
+        params: build_fn_params(params),
         type_params,
         type_ann: Box::from(TsTypeAnn {
             span: DUMMY_SP,
@@ -361,6 +875,39 @@ pub fn build_ts_fn_type_with_params(
     }))
 }
 
+fn build_fn_params(params: &[TFnParam]) -> Vec<TsFnParam> {
+    params
+        .iter()
+        .map(|param| match build_fn_param_pat(param) {
+            Pat::Ident(bi) => TsFnParam::Ident(bi),
+            Pat::Array(array) => TsFnParam::Array(array),
+            Pat::Rest(rest) => TsFnParam::Rest(rest),
+            Pat::Object(obj) => TsFnParam::Object(obj),
+            Pat::Assign(_) => todo!(),
+            Pat::Invalid(_) => todo!(),
+            Pat::Expr(_) => todo!(),
+        })
+        .collect()
+}
+
+fn build_fn_param_pat(param: &TFnParam) -> Pat {
+    let type_ann = Some(Box::from(TsTypeAnn {
+        span: DUMMY_SP,
+        type_ann: Box::from(build_type(&param.t, None)),
+    }));
+
+    match tpat_to_pat(&param.pat, type_ann) {
+        Pat::Ident(bi) => {
+            let id = Ident {
+                optional: param.optional,
+                ..bi.id
+            };
+            Pat::Ident(BindingIdent { id, ..bi })
+        }
+        pat => pat,
+    }
+}
+
 pub fn build_ts_fn_type_with_args(
     args: &[Type],
     ret: &Type,
@@ -472,6 +1019,7 @@ pub fn build_type(t: &Type, type_params: Option<Box<TsTypeParamDecl>>) -> TsType
                 types::TKeyword::Symbol => TsKeywordTypeKind::TsSymbolKeyword,
                 types::TKeyword::Undefined => TsKeywordTypeKind::TsUndefinedKeyword,
                 types::TKeyword::Never => TsKeywordTypeKind::TsNeverKeyword,
+                types::TKeyword::Unknown => TsKeywordTypeKind::TsUnknownKeyword,
             };
 
             TsType::TsKeywordType(TsKeywordType {
@@ -533,7 +1081,19 @@ pub fn build_type(t: &Type, type_params: Option<Box<TsTypeParamDecl>>) -> TsType
                 .iter()
                 .map(|elem| match elem {
                     TObjElem::Call(_) => todo!(),
-                    TObjElem::Constructor(_) => todo!(),
+                    TObjElem::Constructor(types::TCallable {
+                        params,
+                        ret,
+                        type_params: _,
+                    }) => TsTypeElement::TsConstructSignatureDecl(TsConstructSignatureDecl {
+                        span: DUMMY_SP,
+                        params: build_fn_params(params),
+                        type_ann: Some(Box::from(TsTypeAnn {
+                            span: DUMMY_SP,
+                            type_ann: Box::from(build_type(ret, None)),
+                        })),
+                        type_params: None,
+                    }),
                     TObjElem::Index(index) => TsTypeElement::TsIndexSignature(TsIndexSignature {
                         span: DUMMY_SP,
                         readonly: !index.mutable && !t.mutable,
@@ -592,6 +1152,44 @@ pub fn build_type(t: &Type, type_params: Option<Box<TsTypeParamDecl>>) -> TsType
                 })
             }),
         }),
+        // Traits are declared as interfaces, see `build_trait_decl`.
+        TypeKind::Trait(types::TTrait { name, .. }) => TsType::TsTypeRef(TsTypeRef {
+            span: DUMMY_SP,
+            type_name: TsEntityName::from(build_ident(name)),
+            type_params: None,
+        }),
+        // e.g. `IpAddr.V4 | IpAddr.V6`, see `build_enum_decl`
+        TypeKind::Enum(types::TEnum {
+            name,
+            type_args,
+            variants,
+        }) => {
+            let type_args = type_args.as_ref().map(|type_args| {
+                Box::from(TsTypeParamInstantiation {
+                    span: DUMMY_SP,
+                    params: type_args
+                        .iter()
+                        .map(|t| Box::from(build_type(t, None)))
+                        .collect(),
+                })
+            });
+            TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsUnionType(TsUnionType {
+                span: DUMMY_SP,
+                types: variants
+                    .iter()
+                    .map(|variant| {
+                        Box::from(TsType::TsTypeRef(TsTypeRef {
+                            span: DUMMY_SP,
+                            type_name: TsEntityName::TsQualifiedName(Box::from(TsQualifiedName {
+                                left: TsEntityName::from(build_ident(name)),
+                                right: build_ident(&variant.name),
+                            })),
+                            type_params: type_args.to_owned(),
+                        }))
+                    })
+                    .collect(),
+            }))
+        }
         TypeKind::Tuple(types) => {
             let type_ann = TsType::TsTupleType(TsTupleType {
                 span: DUMMY_SP,
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use swc_atoms::*;
@@ -14,6 +15,10 @@ use values::LetExpr;
 
 pub struct Context {
     pub temp_id: u32,
+    /// The enums declared in the program being built, mapped to whether
+    /// each of their variants has a payload.
+    // TODO: include enums that are imported from other modules
+    pub enums: HashMap<String, HashMap<String, bool>>,
 }
 
 impl Context {
@@ -26,16 +31,62 @@ impl Context {
         self.temp_id += 1;
         ident
     }
+
+    /// Returns whether `member` is a variant of a known enum, e.g. `IpAddr.V4`,
+    /// and if so whether that variant has a payload.
+    fn lookup_variant(&self, member: &values::Member) -> Option<bool> {
+        match (&member.obj.kind, &member.prop) {
+            (values::ExprKind::Ident(obj), values::MemberProp::Ident(prop)) => self
+                .enums
+                .get(&obj.name)
+                .and_then(|variants| variants.get(&prop.name))
+                .copied(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ModuleFormat {
+    #[default]
+    Esm,
+    CommonJs,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JsxRuntime {
+    /// Uses `jsx()` from "react/jsx-runtime"
+    #[default]
+    Automatic,
+    /// Uses `React.createElement()`
+    Classic,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct JsOptions {
+    pub module: ModuleFormat,
+    pub jsx_runtime: JsxRuntime,
 }
 
 pub fn codegen_js(program: &values::Program) -> String {
-    let mut ctx = Context { temp_id: 0 };
+    codegen_js_with_options(program, &JsOptions::default())
+}
+
+pub fn codegen_js_with_options(program: &values::Program, options: &JsOptions) -> String {
+    let mut ctx = Context {
+        temp_id: 0,
+        enums: HashMap::default(),
+    };
     let program = build_js(program, &mut ctx);
 
     let cm = Rc::new(SourceMap::default());
     let comments: Option<SingleThreadedComments> = None;
-    let options = Options {
-        runtime: Some(Runtime::Automatic),
+    let runtime = match options.jsx_runtime {
+        JsxRuntime::Automatic => Runtime::Automatic,
+        JsxRuntime::Classic => Runtime::Classic,
+    };
+    let react_options = Options {
+        runtime: Some(runtime),
         ..Default::default()
     };
 
@@ -43,12 +94,245 @@ pub fn codegen_js(program: &values::Program) -> String {
     // The call to Mark::new() must be wrapped in a GLOBALS.set() closure
     GLOBALS.set(&globals, || {
         let top_level_mark = Mark::new();
-        let mut v = react(cm, comments, options, top_level_mark);
+        let mut v = react(cm, comments, react_options, top_level_mark);
         let program = program.fold_with(&mut v);
+        let program = match options.module {
+            ModuleFormat::Esm => program,
+            ModuleFormat::CommonJs => to_common_js(program),
+        };
         print_js(&program)
     })
 }
 
+/// Converts an ES module into a CommonJS script.  Imports are replaced with
+/// calls to `require()` and exports are assigned to `exports`.
+fn to_common_js(program: Program) -> Program {
+    let module = match program {
+        Program::Module(module) => module,
+        Program::Script(_) => return program,
+    };
+
+    let mut body: Vec<Stmt> = vec![];
+    for item in module.body {
+        match item {
+            ModuleItem::Stmt(stmt) => body.push(stmt),
+            ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
+                body.extend(build_require(&import));
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => {
+                let mut names: Vec<Ident> = vec![];
+                match &decl {
+                    Decl::Var(var_decl) => {
+                        for decl in &var_decl.decls {
+                            get_binding_idents(&decl.name, &mut names);
+                        }
+                    }
+                    Decl::Class(ClassDecl { ident, .. }) => names.push(ident.to_owned()),
+                    _ => (),
+                }
+                body.push(Stmt::Decl(decl));
+                for name in names {
+                    body.push(build_exports_assign(
+                        &name.sym,
+                        Expr::Ident(name.to_owned()),
+                    ));
+                }
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+                specifiers,
+                src,
+                ..
+            })) => {
+                // Re-exported values are read from the required module.
+                let source = src.map(|src| build_require_call(&src));
+                for specifier in specifiers {
+                    match specifier {
+                        ExportSpecifier::Named(ExportNamedSpecifier { orig, exported, .. }) => {
+                            let orig = module_export_name(&orig);
+                            let exported = exported
+                                .map(|exported| module_export_name(&exported))
+                                .unwrap_or_else(|| orig.to_owned());
+                            let value = match &source {
+                                Some(require) => Expr::Member(MemberExpr {
+                                    span: DUMMY_SP,
+                                    obj: Box::from(require.to_owned()),
+                                    prop: MemberProp::Ident(build_ident(&orig)),
+                                }),
+                                None => Expr::Ident(build_ident(&orig)),
+                            };
+                            body.push(build_exports_assign(&exported, value));
+                        }
+                        ExportSpecifier::Namespace(ExportNamespaceSpecifier { name, .. }) => {
+                            let require = source.to_owned().unwrap();
+                            body.push(build_exports_assign(&module_export_name(&name), require));
+                        }
+                        // Crochet doesn't have default exports, see
+                        // `values::ExportSpecifier`, so `build_js` never
+                        // produces `export v from "mod"`.
+                        ExportSpecifier::Default(_) => (),
+                    }
+                }
+            }
+            // Object.assign(exports, require(<src>));
+            ModuleItem::ModuleDecl(ModuleDecl::ExportAll(ExportAll { src, .. })) => {
+                body.push(Stmt::Expr(ExprStmt {
+                    span: DUMMY_SP,
+                    expr: Box::from(Expr::Call(CallExpr {
+                        span: DUMMY_SP,
+                        callee: Callee::Expr(Box::from(Expr::Member(MemberExpr {
+                            span: DUMMY_SP,
+                            obj: Box::from(Expr::Ident(build_ident("Object"))),
+                            prop: MemberProp::Ident(build_ident("assign")),
+                        }))),
+                        args: vec![
+                            ExprOrSpread {
+                                spread: None,
+                                expr: Box::from(Expr::Ident(build_ident("exports"))),
+                            },
+                            ExprOrSpread {
+                                spread: None,
+                                expr: Box::from(build_require_call(&src)),
+                            },
+                        ],
+                        type_args: None,
+                    })),
+                }));
+            }
+            // `build_js` never produces default exports or TypeScript only
+            // module syntax.  These are listed explicitly so that new kinds of
+            // module declarations have to be handled here.
+            ModuleItem::ModuleDecl(
+                ModuleDecl::ExportDefaultDecl(_)
+                | ModuleDecl::ExportDefaultExpr(_)
+                | ModuleDecl::TsImportEquals(_)
+                | ModuleDecl::TsExportAssignment(_)
+                | ModuleDecl::TsNamespaceExport(_),
+            ) => (),
+        }
+    }
+
+    Program::Script(Script {
+        span: DUMMY_SP,
+        body,
+        shebang: module.shebang,
+    })
+}
+
+fn get_binding_idents(pat: &Pat, names: &mut Vec<Ident>) {
+    match pat {
+        Pat::Ident(bi) => names.push(bi.id.to_owned()),
+        Pat::Array(ArrayPat { elems, .. }) => {
+            for elem in elems.iter().flatten() {
+                get_binding_idents(elem, names);
+            }
+        }
+        Pat::Rest(RestPat { arg, .. }) => get_binding_idents(arg, names),
+        Pat::Object(ObjectPat { props, .. }) => {
+            for prop in props {
+                match prop {
+                    ObjectPatProp::KeyValue(KeyValuePatProp { value, .. }) => {
+                        get_binding_idents(value, names)
+                    }
+                    ObjectPatProp::Assign(AssignPatProp { key, .. }) => names.push(key.to_owned()),
+                    ObjectPatProp::Rest(RestPat { arg, .. }) => get_binding_idents(arg, names),
+                }
+            }
+        }
+        Pat::Assign(AssignPat { left, .. }) => get_binding_idents(left, names),
+        Pat::Invalid(_) | Pat::Expr(_) => (),
+    }
+}
+
+// exports.<name> = <value>;
+fn build_exports_assign(name: &JsWord, value: Expr) -> Stmt {
+    Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::from(Expr::Assign(AssignExpr {
+            span: DUMMY_SP,
+            op: AssignOp::Assign,
+            left: PatOrExpr::Expr(Box::from(Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::from(Expr::Ident(build_ident("exports"))),
+                prop: MemberProp::Ident(build_ident(name)),
+            }))),
+            right: Box::from(value),
+        })),
+    })
+}
+
+fn module_export_name(name: &ModuleExportName) -> JsWord {
+    match name {
+        ModuleExportName::Ident(ident) => ident.sym.to_owned(),
+        ModuleExportName::Str(str) => str.value.to_owned(),
+    }
+}
+
+// require(<src>)
+fn build_require_call(src: &Str) -> Expr {
+    Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        callee: Callee::Expr(Box::from(Expr::Ident(build_ident("require")))),
+        args: vec![ExprOrSpread {
+            spread: None,
+            expr: Box::from(Expr::Lit(Lit::Str(src.to_owned()))),
+        }],
+        type_args: None,
+    })
+}
+
+// const <specifiers> = require(<src>);
+fn build_require(import: &ImportDecl) -> Vec<Stmt> {
+    let require = build_require_call(&import.src);
+
+    if import.specifiers.is_empty() {
+        return vec![Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::from(require),
+        })];
+    }
+
+    let mut stmts: Vec<Stmt> = vec![];
+    let mut props: Vec<ObjectPatProp> = vec![];
+    for specifier in &import.specifiers {
+        match specifier {
+            ImportSpecifier::Named(ImportNamedSpecifier {
+                local, imported, ..
+            }) => {
+                let key = match imported {
+                    Some(ModuleExportName::Ident(ident)) => ident.to_owned(),
+                    Some(ModuleExportName::Str(str)) => build_ident(&str.value),
+                    None => local.to_owned(),
+                };
+                props.push(ObjectPatProp::KeyValue(KeyValuePatProp {
+                    key: PropName::Ident(key),
+                    value: Box::from(Pat::Ident(BindingIdent::from(local.to_owned()))),
+                }));
+            }
+            ImportSpecifier::Default(ImportDefaultSpecifier { local, .. }) => {
+                props.push(ObjectPatProp::KeyValue(KeyValuePatProp {
+                    key: PropName::Ident(build_ident("default")),
+                    value: Box::from(Pat::Ident(BindingIdent::from(local.to_owned()))),
+                }));
+            }
+            ImportSpecifier::Namespace(ImportStarAsSpecifier { local, .. }) => {
+                stmts.push(build_const_decl_stmt(local, require.to_owned()));
+            }
+        }
+    }
+
+    if !props.is_empty() {
+        let pat = Pat::Object(ObjectPat {
+            span: DUMMY_SP,
+            props,
+            optional: false,
+            type_ann: None,
+        });
+        stmts.push(build_const_decl_stmt_with_pat(pat, require));
+    }
+
+    stmts
+}
+
 fn print_js(program: &Program) -> String {
     let mut buf = vec![];
     let cm = Rc::new(SourceMap::default());
@@ -68,55 +352,30 @@ fn print_js(program: &Program) -> String {
 }
 
 fn build_js(program: &values::Program, ctx: &mut Context) -> Program {
+    let export_all = program.exports_all();
+
+    // Enums can be used before they're declared, e.g. inside of functions.
+    for stmt in &program.body {
+        let stmt = match stmt {
+            values::Statement::ExportDecl { decl, .. } => decl.as_ref(),
+            stmt => stmt,
+        };
+        if let values::Statement::EnumDecl { r#enum, .. } = stmt {
+            let variants = r#enum
+                .variants
+                .iter()
+                .map(|variant| (variant.ident.name.to_owned(), variant.payload.is_some()))
+                .collect();
+            ctx.enums.insert(r#enum.ident.name.to_owned(), variants);
+        }
+    }
+
     let body: Vec<ModuleItem> = program
         .body
         .iter()
         .flat_map(|child| {
             let mut stmts: Vec<Stmt> = vec![];
-            let result = match child {
-                values::Statement::VarDecl {
-                    pattern,
-                    init,
-                    declare,
-                    ..
-                } => match declare {
-                    true => ModuleItem::Stmt(Stmt::Empty(EmptyStmt { span: DUMMY_SP })),
-                    false => {
-                        // It should be okay to unwrap this here since any decl that isn't
-                        // using `declare` should have an initial value.
-                        let init = init.as_ref().unwrap();
-
-                        match build_pattern(pattern, &mut stmts, ctx) {
-                            Some(name) => {
-                                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
-                                    span: DUMMY_SP,
-                                    decl: Decl::Var(Box::from(VarDecl {
-                                        span: DUMMY_SP,
-                                        kind: VarDeclKind::Const,
-                                        declare: false,
-                                        decls: vec![VarDeclarator {
-                                            span: DUMMY_SP,
-                                            name,
-                                            init: Some(Box::from(build_expr(
-                                                init, &mut stmts, ctx,
-                                            ))),
-                                            definite: false,
-                                        }],
-                                    })),
-                                }))
-                            }
-                            None => todo!(),
-                        }
-                    }
-                },
-                values::Statement::TypeDecl { .. } => {
-                    ModuleItem::Stmt(Stmt::Empty(EmptyStmt { span: DUMMY_SP }))
-                }
-                values::Statement::Expr { expr, .. } => ModuleItem::Stmt(Stmt::Expr(ExprStmt {
-                    span: DUMMY_SP,
-                    expr: Box::from(build_expr(expr, &mut stmts, ctx)),
-                })),
-            };
+            let result = build_module_item(child, export_all, &mut stmts, ctx);
 
             let mut items: Vec<ModuleItem> = stmts
                 .iter()
@@ -135,6 +394,239 @@ fn build_js(program: &values::Program, ctx: &mut Context) -> Program {
     })
 }
 
+/// Returns `decl` as an `export` declaration if `export` is true.
+pub(crate) fn wrap_export(decl: Decl, export: bool) -> ModuleItem {
+    match export {
+        true => ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+            span: DUMMY_SP,
+            decl,
+        })),
+        false => ModuleItem::Stmt(Stmt::Decl(decl)),
+    }
+}
+
+fn build_module_item(
+    stmt: &values::Statement,
+    export: bool,
+    stmts: &mut Vec<Stmt>,
+    ctx: &mut Context,
+) -> ModuleItem {
+    match stmt {
+        values::Statement::VarDecl {
+            pattern,
+            init,
+            declare,
+            ..
+        } => match declare {
+            true => ModuleItem::Stmt(Stmt::Empty(EmptyStmt { span: DUMMY_SP })),
+            false => {
+                // It should be okay to unwrap this here since any decl that isn't
+                // using `declare` should have an initial value.
+                let init = init.as_ref().unwrap();
+
+                match build_pattern(pattern, stmts, ctx) {
+                    Some(name) => {
+                        let decl = Decl::Var(Box::from(VarDecl {
+                            span: DUMMY_SP,
+                            kind: build_var_decl_kind(pattern),
+                            declare: false,
+                            decls: vec![VarDeclarator {
+                                span: DUMMY_SP,
+                                name,
+                                init: Some(Box::from(build_expr(init, stmts, ctx))),
+                                definite: false,
+                            }],
+                        }));
+                        wrap_export(decl, export)
+                    }
+                    None => todo!(),
+                }
+            }
+        },
+        values::Statement::TypeDecl { .. } => {
+            ModuleItem::Stmt(Stmt::Empty(EmptyStmt { span: DUMMY_SP }))
+        }
+        values::Statement::ClassDecl { class, .. } => {
+            let decl = Decl::Class(build_class(class, stmts, ctx));
+            wrap_export(decl, export)
+        }
+        values::Statement::EnumDecl { r#enum, .. } => {
+            let decl = build_enum(r#enum);
+            wrap_export(decl, export)
+        }
+        values::Statement::StructDecl { r#struct, .. } => {
+            let decl = Decl::Class(build_struct(r#struct));
+            wrap_export(decl, export)
+        }
+        values::Statement::TraitDecl { r#trait, .. } => {
+            let decl = build_trait(r#trait, stmts, ctx);
+            wrap_export(decl, export)
+        }
+        values::Statement::ImplDecl { r#impl, .. } => {
+            ModuleItem::Stmt(build_impl(r#impl, stmts, ctx))
+        }
+        values::Statement::Expr { expr, .. } => ModuleItem::Stmt(build_expr_stmt(expr, stmts, ctx)),
+        values::Statement::Import {
+            specifiers, source, ..
+        } => build_import(specifiers, source),
+        values::Statement::Export {
+            specifiers, source, ..
+        } => build_export(specifiers, source.as_ref()),
+        values::Statement::ExportDecl { decl, .. } => build_module_item(decl, true, stmts, ctx),
+    }
+}
+
+pub(crate) fn build_str(s: &values::Str) -> Str {
+    Str {
+        span: DUMMY_SP,
+        value: JsWord::from(s.value.to_owned()),
+        raw: None,
+    }
+}
+
+pub(crate) fn build_import_specifier(specifier: &values::ImportSpecifier) -> ImportSpecifier {
+    match specifier {
+        values::ImportSpecifier::Named {
+            local,
+            imported,
+            is_type,
+            ..
+        } => ImportSpecifier::Named(ImportNamedSpecifier {
+            span: DUMMY_SP,
+            local: Ident::from(local),
+            imported: imported
+                .as_ref()
+                .map(|imported| ModuleExportName::Ident(Ident::from(imported))),
+            is_type_only: *is_type,
+        }),
+        values::ImportSpecifier::Namespace { local, .. } => {
+            ImportSpecifier::Namespace(ImportStarAsSpecifier {
+                span: DUMMY_SP,
+                local: Ident::from(local),
+            })
+        }
+    }
+}
+
+/// Returns `None` for `export * from "./foo";` since it's a separate kind of
+/// declaration, see `build_export`.
+pub(crate) fn build_export_specifier(
+    specifier: &values::ExportSpecifier,
+) -> Option<ExportSpecifier> {
+    match specifier {
+        values::ExportSpecifier::Named {
+            local,
+            exported,
+            is_type,
+            ..
+        } => Some(ExportSpecifier::Named(ExportNamedSpecifier {
+            span: DUMMY_SP,
+            orig: ModuleExportName::Ident(Ident::from(local)),
+            exported: exported
+                .as_ref()
+                .map(|exported| ModuleExportName::Ident(Ident::from(exported))),
+            is_type_only: *is_type,
+        })),
+        values::ExportSpecifier::All { exported, .. } => exported.as_ref().map(|exported| {
+            ExportSpecifier::Namespace(ExportNamespaceSpecifier {
+                span: DUMMY_SP,
+                name: ModuleExportName::Ident(Ident::from(exported)),
+            })
+        }),
+    }
+}
+
+// Type-only specifiers are dropped since there's nothing to import at runtime.
+fn build_import(specifiers: &[values::ImportSpecifier], source: &values::Str) -> ModuleItem {
+    let values: Vec<ImportSpecifier> = specifiers
+        .iter()
+        .filter(|specifier| !specifier.is_type())
+        .map(build_import_specifier)
+        .collect();
+
+    // `import "./foo";` is kept for its side effects, but an import that
+    // only had types in it is removed entirely.
+    if values.is_empty() && !specifiers.is_empty() {
+        return ModuleItem::Stmt(Stmt::Empty(EmptyStmt { span: DUMMY_SP }));
+    }
+
+    ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+        span: DUMMY_SP,
+        specifiers: values,
+        src: Box::from(build_str(source)),
+        type_only: false,
+        asserts: None,
+    }))
+}
+
+pub(crate) fn build_export_all(source: &values::Str) -> ModuleItem {
+    ModuleItem::ModuleDecl(ModuleDecl::ExportAll(ExportAll {
+        span: DUMMY_SP,
+        src: Box::from(build_str(source)),
+        asserts: None,
+    }))
+}
+
+fn build_export(
+    specifiers: &[values::ExportSpecifier],
+    source: Option<&values::Str>,
+) -> ModuleItem {
+    if let (Some(source), [values::ExportSpecifier::All { exported: None, .. }]) =
+        (source, specifiers)
+    {
+        return build_export_all(source);
+    }
+
+    let values: Vec<ExportSpecifier> = specifiers
+        .iter()
+        .filter(|specifier| !specifier.is_type())
+        .filter_map(build_export_specifier)
+        .collect();
+
+    if values.is_empty() && !specifiers.is_empty() {
+        return ModuleItem::Stmt(Stmt::Empty(EmptyStmt { span: DUMMY_SP }));
+    }
+
+    ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+        span: DUMMY_SP,
+        specifiers: values,
+        src: source.map(|source| Box::from(build_str(source))),
+        type_only: false,
+        asserts: None,
+    }))
+}
+
+fn build_object_pat_props(
+    props: &[values::ObjectPatProp],
+    stmts: &mut Vec<Stmt>,
+    ctx: &mut Context,
+) -> Vec<ObjectPatProp> {
+    props
+        .iter()
+        .filter_map(|p| match p {
+            values::ObjectPatProp::KeyValue(kvp) => build_pattern(kvp.value.as_ref(), stmts, ctx)
+                .map(|value| {
+                    ObjectPatProp::KeyValue(KeyValuePatProp {
+                        key: PropName::Ident(Ident::from(&kvp.key)),
+                        value: Box::from(value),
+                    })
+                }),
+            values::ObjectPatProp::Shorthand(values::ShorthandPatProp {
+                ident,
+                init,
+                span: _,
+            }) => Some(ObjectPatProp::Assign(AssignPatProp {
+                span: DUMMY_SP,
+                key: Ident::from(ident),
+                value: init
+                    .clone()
+                    .map(|value| Box::from(build_expr(value.as_ref(), stmts, ctx))),
+            })),
+            values::ObjectPatProp::Rest(_) => todo!(),
+        })
+        .collect()
+}
+
 fn build_pattern(
     pattern: &values::Pattern,
     stmts: &mut Vec<Stmt>,
@@ -165,38 +657,20 @@ fn build_pattern(
         }
         values::PatternKind::Object(values::ObjectPat {
             props, optional, ..
-        }) => {
-            let props: Vec<ObjectPatProp> = props
-                .iter()
-                .filter_map(|p| match p {
-                    values::ObjectPatProp::KeyValue(kvp) => {
-                        build_pattern(kvp.value.as_ref(), stmts, ctx).map(|value| {
-                            ObjectPatProp::KeyValue(KeyValuePatProp {
-                                key: PropName::Ident(Ident::from(&kvp.key)),
-                                value: Box::from(value),
-                            })
-                        })
-                    }
-                    values::ObjectPatProp::Shorthand(values::ShorthandPatProp {
-                        ident,
-                        init,
-                        span: _,
-                    }) => Some(ObjectPatProp::Assign(AssignPatProp {
-                        span: DUMMY_SP,
-                        key: Ident::from(ident),
-                        value: init
-                            .clone()
-                            .map(|value| Box::from(build_expr(value.as_ref(), stmts, ctx))),
-                    })),
-                    values::ObjectPatProp::Rest(_) => todo!(),
-                })
-                .collect();
-
+        }) => Some(Pat::Object(ObjectPat {
+            span: DUMMY_SP,
+            optional: optional.to_owned(),
+            type_ann: None, // because we're generating .js
+            props: build_object_pat_props(props, stmts, ctx),
+        })),
+        // Structs are classes so their fields can be destructured like the
+        // props of any other object.
+        values::PatternKind::Struct(values::StructPat { props, .. }) => {
             Some(Pat::Object(ObjectPat {
                 span: DUMMY_SP,
-                optional: optional.to_owned(),
-                type_ann: None, // because we're generating .js
-                props,
+                optional: false,
+                type_ann: None,
+                props: build_object_pat_props(props, stmts, ctx),
             }))
         }
         values::PatternKind::Array(values::ArrayPat {
@@ -223,6 +697,19 @@ fn build_pattern(
             id: build_ident(&ident.name),
             type_ann: None,
         })),
+        // Variants store their payload in a `payload` property, see `build_enum`.
+        values::PatternKind::EnumVariant(values::EnumVariantPat { payload, .. }) => {
+            let value = build_pattern(payload.as_ref()?, stmts, ctx)?;
+            Some(Pat::Object(ObjectPat {
+                span: DUMMY_SP,
+                optional: false,
+                type_ann: None,
+                props: vec![ObjectPatProp::KeyValue(KeyValuePatProp {
+                    key: PropName::Ident(build_ident("payload")),
+                    value: Box::from(value),
+                })],
+            }))
+        }
     }
 }
 
@@ -250,6 +737,13 @@ fn build_expr_in_new_scope(expr: &values::Expr, temp_id: &Ident, ctx: &mut Conte
 
     let expr = _build_expr(expr, &mut stmts, ctx);
 
+    if ends_with_jump(&stmts) {
+        return BlockStmt {
+            span: DUMMY_SP,
+            stmts,
+        };
+    }
+
     // Assigns the result of the block to the temp variable
     stmts.push(Stmt::Expr(ExprStmt {
         span: DUMMY_SP,
@@ -273,7 +767,22 @@ fn build_expr_in_new_scope(expr: &values::Expr, temp_id: &Ident, ctx: &mut Conte
 fn build_expr(expr: &values::Expr, stmts: &mut Vec<Stmt>, ctx: &mut Context) -> Expr {
     match &expr.kind {
         values::ExprKind::App(values::App { lam, args, .. }) => {
-            let callee = Callee::Expr(Box::from(build_expr(lam.as_ref(), stmts, ctx)));
+            // Variants are classes so they have to be constructed with `new`.
+            if let values::ExprKind::Member(member) = &lam.kind {
+                if ctx.lookup_variant(member) == Some(true) {
+                    return Expr::New(NewExpr {
+                        span: DUMMY_SP,
+                        callee: Box::from(build_variant_member(member)),
+                        args: Some(build_args(args, stmts, ctx)),
+                        type_args: None,
+                    });
+                }
+            }
+
+            let callee = match &lam.kind {
+                values::ExprKind::Super => Callee::Super(Super { span: DUMMY_SP }),
+                _ => Callee::Expr(Box::from(build_expr(lam.as_ref(), stmts, ctx))),
+            };
 
             let is_partial = args.iter().any(|arg| match &arg.expr.kind {
                 values::ExprKind::Ident(bi) => bi.name == "_",
@@ -350,25 +859,23 @@ fn build_expr(expr: &values::Expr, stmts: &mut Vec<Stmt>, ctx: &mut Context) ->
                 });
             }
 
-            let args: Vec<ExprOrSpread> = args
-                .iter()
-                .map(|arg| ExprOrSpread {
-                    spread: if arg.spread.is_some() {
-                        Some(DUMMY_SP)
-                    } else {
-                        None
-                    },
-                    expr: Box::from(build_expr(arg.expr.as_ref(), stmts, ctx)),
-                })
-                .collect();
-
             Expr::Call(CallExpr {
                 span: DUMMY_SP,
                 callee,
-                args,
+                args: build_args(args, stmts, ctx),
                 type_args: None,
             })
         }
+        values::ExprKind::New(values::New { expr, args }) => Expr::New(NewExpr {
+            span: DUMMY_SP,
+            callee: Box::from(build_expr(expr, stmts, ctx)),
+            args: Some(build_args(args, stmts, ctx)),
+            type_args: None,
+        }),
+        values::ExprKind::This => Expr::This(ThisExpr { span: DUMMY_SP }),
+        values::ExprKind::Super => {
+            panic!("Super should always be handled by the App or Member branch")
+        }
         values::ExprKind::Ident(ident) => Expr::from(build_ident(&ident.name)),
         values::ExprKind::Lambda(values::Lambda {
             params: args,
@@ -526,6 +1033,88 @@ fn build_expr(expr: &values::Expr, stmts: &mut Vec<Stmt>, ctx: &mut Context) ->
                 Expr::Ident(temp_id)
             }
         },
+        values::ExprKind::Do(values::Do { body }) => {
+            // let $temp_n;
+            let temp_id = ctx.new_ident();
+            let temp_decl = build_let_decl_stmt(&temp_id);
+            stmts.push(temp_decl);
+
+            // { ...; $temp_n = <result> }
+            let block = build_expr_in_new_scope(body, &temp_id, ctx);
+            stmts.push(Stmt::Block(block));
+
+            // $temp_n
+            Expr::Ident(temp_id)
+        }
+        values::ExprKind::Try(values::Try {
+            body,
+            catch_param,
+            catch_body,
+            finally,
+        }) => {
+            // let $temp_n;
+            let temp_id = ctx.new_ident();
+            let temp_decl = build_let_decl_stmt(&temp_id);
+            stmts.push(temp_decl);
+
+            // try { ...; $temp_n = <body_res> } catch (e) { ...; $temp_n = <catch_res> }
+            let block = build_expr_in_new_scope(body, &temp_id, ctx);
+            let handler = catch_body.as_ref().map(|catch_body| {
+                let mut param_stmts: Vec<Stmt> = vec![];
+                let param = catch_param
+                    .as_ref()
+                    .and_then(|param| build_pattern(&param.pat, &mut param_stmts, ctx));
+                let mut body = build_expr_in_new_scope(catch_body, &temp_id, ctx);
+                param_stmts.append(&mut body.stmts);
+                body.stmts = param_stmts;
+                CatchClause {
+                    span: DUMMY_SP,
+                    param,
+                    body,
+                }
+            });
+            let finalizer = finally
+                .as_ref()
+                .map(|finally| build_discarded_block_stmt(finally, ctx));
+            stmts.push(Stmt::Try(Box::from(TryStmt {
+                span: DUMMY_SP,
+                block,
+                handler,
+                finalizer,
+            })));
+
+            // $temp_n
+            Expr::Ident(temp_id)
+        }
+        values::ExprKind::Pipe(pipe) => {
+            // e.g. `x |> f(a, _)` becomes `f(a, x)`
+            let app = values::Expr {
+                span: expr.span.to_owned(),
+                kind: values::ExprKind::App(pipe.to_app()),
+                inferred_type: None,
+            };
+            build_expr(&app, stmts, ctx)
+        }
+        values::ExprKind::Throw(values::Throw { arg }) => {
+            // JavaScript doesn't have `throw` expressions yet so the `throw`
+            // is moved into its own statement.
+            let arg = build_expr(arg, stmts, ctx);
+            stmts.push(Stmt::Throw(ThrowStmt {
+                span: DUMMY_SP,
+                arg: Box::from(arg),
+            }));
+
+            Expr::from(build_ident("undefined"))
+        }
+        values::ExprKind::ForOf(_)
+        | values::ExprKind::While(_)
+        | values::ExprKind::Break
+        | values::ExprKind::Continue => {
+            let stmt = build_loop_stmt(expr, stmts, ctx);
+            stmts.push(stmt);
+
+            Expr::from(build_ident("undefined"))
+        }
         values::ExprKind::Obj(values::Obj { props, .. }) => {
             let props: Vec<PropOrSpread> = props
                 .iter()
@@ -577,7 +1166,26 @@ fn build_expr(expr: &values::Expr, stmts: &mut Vec<Stmt>, ctx: &mut Context) ->
                 })
                 .collect(),
         }),
-        values::ExprKind::Member(values::Member { obj, prop, .. }) => {
+        values::ExprKind::Member(member @ values::Member { obj, prop, .. }) => {
+            if let Some(has_payload) = ctx.lookup_variant(member) {
+                return build_variant_constructor(member, has_payload);
+            }
+            if let values::ExprKind::Super = &obj.kind {
+                let prop = match prop {
+                    values::MemberProp::Ident(ident) => SuperProp::Ident(build_ident(&ident.name)),
+                    values::MemberProp::Computed(values::ComputedPropName { expr, .. }) => {
+                        SuperProp::Computed(ComputedPropName {
+                            span: DUMMY_SP,
+                            expr: Box::from(build_expr(expr, stmts, ctx)),
+                        })
+                    }
+                };
+                return Expr::SuperProp(SuperPropExpr {
+                    span: DUMMY_SP,
+                    obj: Super { span: DUMMY_SP },
+                    prop,
+                });
+            }
             let prop = match prop {
                 values::MemberProp::Ident(ident) => MemberProp::Ident(build_ident(&ident.name)),
                 values::MemberProp::Computed(values::ComputedPropName { expr, .. }) => {
@@ -681,6 +1289,465 @@ fn build_expr(expr: &values::Expr, stmts: &mut Vec<Stmt>, ctx: &mut Context) ->
     }
 }
 
+fn build_args(
+    args: &[values::ExprOrSpread],
+    stmts: &mut Vec<Stmt>,
+    ctx: &mut Context,
+) -> Vec<ExprOrSpread> {
+    args.iter()
+        .map(|arg| ExprOrSpread {
+            spread: if arg.spread.is_some() {
+                Some(DUMMY_SP)
+            } else {
+                None
+            },
+            expr: Box::from(build_expr(arg.expr.as_ref(), stmts, ctx)),
+        })
+        .collect()
+}
+
+fn build_class(class: &values::Class, stmts: &mut Vec<Stmt>, ctx: &mut Context) -> ClassDecl {
+    let body: Vec<ClassMember> = class
+        .body
+        .iter()
+        .map(|member| match member {
+            values::ClassMember::Constructor(values::Constructor { params, body, .. }) => {
+                let params: Vec<ParamOrTsParamProp> = params
+                    .iter()
+                    .map(|param| {
+                        let pat = build_pattern(&param.pat, stmts, ctx).unwrap();
+                        ParamOrTsParamProp::Param(Param::from(pat))
+                    })
+                    .collect();
+                ClassMember::Constructor(Constructor {
+                    span: DUMMY_SP,
+                    key: PropName::Ident(build_ident("constructor")),
+                    params,
+                    body: Some(build_discarded_block_stmt(body, ctx)),
+                    accessibility: None,
+                    is_optional: false,
+                })
+            }
+            values::ClassMember::Method(values::ClassMethod {
+                key,
+                lambda,
+                is_static,
+                ..
+            }) => {
+                let params: Vec<Param> = lambda
+                    .params
+                    .iter()
+                    .map(|param| Param::from(build_pattern(&param.pat, stmts, ctx).unwrap()))
+                    .collect();
+                ClassMember::Method(ClassMethod {
+                    span: DUMMY_SP,
+                    key: PropName::Ident(build_ident(&key.name)),
+                    function: Box::from(Function {
+                        params,
+                        decorators: vec![],
+                        span: DUMMY_SP,
+                        body: Some(build_block_stmt(&lambda.body, ctx)),
+                        is_generator: false,
+                        is_async: lambda.is_async,
+                        type_params: None,
+                        return_type: None,
+                    }),
+                    kind: MethodKind::Method,
+                    is_static: *is_static,
+                    accessibility: None,
+                    is_abstract: false,
+                    is_optional: false,
+                    is_override: false,
+                })
+            }
+            values::ClassMember::Prop(values::ClassProp {
+                key,
+                value,
+                is_static,
+                ..
+            }) => ClassMember::ClassProp(ClassProp {
+                span: DUMMY_SP,
+                key: PropName::Ident(build_ident(&key.name)),
+                value: value
+                    .as_ref()
+                    .map(|value| Box::from(build_expr(value, stmts, ctx))),
+                type_ann: None,
+                is_static: *is_static,
+                decorators: vec![],
+                accessibility: None,
+                is_abstract: false,
+                is_optional: false,
+                is_override: false,
+                readonly: false,
+                declare: false,
+                definite: false,
+            }),
+        })
+        .collect();
+
+    ClassDecl {
+        ident: build_ident(&class.ident.name),
+        declare: false,
+        class: Box::from(Class {
+            span: DUMMY_SP,
+            decorators: vec![],
+            body,
+            super_class: class
+                .super_class
+                .as_ref()
+                .map(|ident| Box::from(Expr::Ident(build_ident(&ident.name)))),
+            is_abstract: false,
+            type_params: None,
+            super_type_params: None,
+            implements: vec![],
+        }),
+    }
+}
+
+/// Each variant is a separate class so that they can be distinguished using
+/// `instanceof`, e.g.
+/// ```js
+/// const IpAddr = {
+///     V4: class V4 {
+///         constructor(payload){
+///             this.payload = payload;
+///         }
+///     }
+/// };
+/// ```
+fn build_enum(r#enum: &values::Enum) -> Decl {
+    let props: Vec<PropOrSpread> = r#enum
+        .variants
+        .iter()
+        .map(|variant| {
+            let body = match variant.payload {
+                Some(_) => {
+                    let payload = build_ident("payload");
+                    vec![ClassMember::Constructor(Constructor {
+                        span: DUMMY_SP,
+                        key: PropName::Ident(build_ident("constructor")),
+                        params: vec![ParamOrTsParamProp::Param(Param::from(Pat::Ident(
+                            BindingIdent::from(payload.to_owned()),
+                        )))],
+                        body: Some(BlockStmt {
+                            span: DUMMY_SP,
+                            stmts: vec![Stmt::Expr(ExprStmt {
+                                span: DUMMY_SP,
+                                expr: Box::from(Expr::Assign(AssignExpr {
+                                    span: DUMMY_SP,
+                                    op: AssignOp::Assign,
+                                    left: PatOrExpr::Expr(Box::from(Expr::Member(MemberExpr {
+                                        span: DUMMY_SP,
+                                        obj: Box::from(Expr::This(ThisExpr { span: DUMMY_SP })),
+                                        prop: MemberProp::Ident(payload.to_owned()),
+                                    }))),
+                                    right: Box::from(Expr::Ident(payload)),
+                                })),
+                            })],
+                        }),
+                        accessibility: None,
+                        is_optional: false,
+                    })]
+                }
+                None => vec![],
+            };
+            let class = Expr::Class(ClassExpr {
+                ident: Some(build_ident(&variant.ident.name)),
+                class: Box::from(Class {
+                    span: DUMMY_SP,
+                    decorators: vec![],
+                    body,
+                    super_class: None,
+                    is_abstract: false,
+                    type_params: None,
+                    super_type_params: None,
+                    implements: vec![],
+                }),
+            });
+            PropOrSpread::Prop(Box::from(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(build_ident(&variant.ident.name)),
+                value: Box::from(class),
+            })))
+        })
+        .collect();
+
+    Decl::Var(Box::from(VarDecl {
+        span: DUMMY_SP,
+        kind: VarDeclKind::Const,
+        declare: false,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(BindingIdent::from(build_ident(&r#enum.ident.name))),
+            init: Some(Box::from(Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props,
+            }))),
+            definite: false,
+        }],
+    }))
+}
+
+/// Structs are classes whose constructor copies each field from the object
+/// that's passed to it, e.g.
+/// ```js
+/// class Point {
+///     constructor(values){
+///         this.x = values.x;
+///         this.y = values.y;
+///     }
+/// }
+/// ```
+fn build_struct(r#struct: &values::Struct) -> ClassDecl {
+    let values = build_ident("values");
+    let stmts: Vec<Stmt> = r#struct
+        .fields
+        .iter()
+        .map(|field| {
+            let key = build_ident(&field.key.name);
+            Stmt::Expr(ExprStmt {
+                span: DUMMY_SP,
+                expr: Box::from(Expr::Assign(AssignExpr {
+                    span: DUMMY_SP,
+                    op: AssignOp::Assign,
+                    left: PatOrExpr::Expr(Box::from(Expr::Member(MemberExpr {
+                        span: DUMMY_SP,
+                        obj: Box::from(Expr::This(ThisExpr { span: DUMMY_SP })),
+                        prop: MemberProp::Ident(key.to_owned()),
+                    }))),
+                    right: Box::from(Expr::Member(MemberExpr {
+                        span: DUMMY_SP,
+                        obj: Box::from(Expr::Ident(values.to_owned())),
+                        prop: MemberProp::Ident(key),
+                    })),
+                })),
+            })
+        })
+        .collect();
+
+    ClassDecl {
+        ident: build_ident(&r#struct.ident.name),
+        declare: false,
+        class: Box::from(Class {
+            span: DUMMY_SP,
+            decorators: vec![],
+            body: vec![ClassMember::Constructor(Constructor {
+                span: DUMMY_SP,
+                key: PropName::Ident(build_ident("constructor")),
+                params: vec![ParamOrTsParamProp::Param(Param::from(Pat::Ident(
+                    BindingIdent::from(values),
+                )))],
+                body: Some(BlockStmt {
+                    span: DUMMY_SP,
+                    stmts,
+                }),
+                accessibility: None,
+                is_optional: false,
+            })],
+            super_class: None,
+            is_abstract: false,
+            type_params: None,
+            super_type_params: None,
+            implements: vec![],
+        }),
+    }
+}
+
+/// The value of a trait is an object with its default implementations, e.g.
+/// ```js
+/// const Greet = {
+///     greet () {
+///         return `Hello, ${this.name()}`;
+///     }
+/// };
+/// ```
+fn build_trait(r#trait: &values::Trait, stmts: &mut Vec<Stmt>, ctx: &mut Context) -> Decl {
+    let props: Vec<PropOrSpread> = r#trait
+        .methods
+        .iter()
+        .filter_map(|method| {
+            let body = method.body.as_ref()?;
+            Some(build_method_prop(
+                &method.key,
+                &method.params,
+                body,
+                stmts,
+                ctx,
+            ))
+        })
+        .collect();
+
+    Decl::Var(Box::from(VarDecl {
+        span: DUMMY_SP,
+        kind: VarDeclKind::Const,
+        declare: false,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(BindingIdent::from(build_ident(&r#trait.ident.name))),
+            init: Some(Box::from(Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props,
+            }))),
+            definite: false,
+        }],
+    }))
+}
+
+/// Impls add the trait's methods to the prototype of the class, the trait's
+/// default implementations are added first so that they can be overridden,
+/// e.g. `Object.assign(User.prototype, Greet, {name () {...}});`
+fn build_impl(r#impl: &values::Impl, stmts: &mut Vec<Stmt>, ctx: &mut Context) -> Stmt {
+    let props: Vec<PropOrSpread> = r#impl
+        .methods
+        .iter()
+        .map(|method| {
+            build_method_prop(
+                &method.key,
+                &method.lambda.params,
+                &method.lambda.body,
+                stmts,
+                ctx,
+            )
+        })
+        .collect();
+    let prototype = Expr::Member(MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::from(Expr::Ident(build_ident(&r#impl.type_ident.name))),
+        prop: MemberProp::Ident(build_ident("prototype")),
+    });
+    let args: Vec<ExprOrSpread> = vec![
+        prototype,
+        Expr::Ident(build_ident(&r#impl.r#trait.name)),
+        Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props,
+        }),
+    ]
+    .into_iter()
+    .map(|expr| ExprOrSpread {
+        spread: None,
+        expr: Box::from(expr),
+    })
+    .collect();
+
+    Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::from(Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            callee: Callee::Expr(Box::from(Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::from(Expr::Ident(build_ident("Object"))),
+                prop: MemberProp::Ident(build_ident("assign")),
+            }))),
+            args,
+            type_args: None,
+        })),
+    })
+}
+
+/// Methods in object literals, unlike arrow functions, can use `this`.
+fn build_method_prop(
+    key: &values::Ident,
+    params: &[values::EFnParam],
+    body: &values::Expr,
+    stmts: &mut Vec<Stmt>,
+    ctx: &mut Context,
+) -> PropOrSpread {
+    let params: Vec<Param> = params
+        .iter()
+        .map(|param| Param::from(build_pattern(&param.pat, stmts, ctx).unwrap()))
+        .collect();
+    PropOrSpread::Prop(Box::from(Prop::Method(MethodProp {
+        key: PropName::Ident(build_ident(&key.name)),
+        function: Box::from(Function {
+            params,
+            decorators: vec![],
+            span: DUMMY_SP,
+            body: Some(build_block_stmt(body, ctx)),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        }),
+    })))
+}
+
+/// `member` must be a variant, e.g. `IpAddr.V4`, see `Context::lookup_variant`.
+fn build_variant_member(member: &values::Member) -> Expr {
+    let (obj, prop) = match (&member.obj.kind, &member.prop) {
+        (values::ExprKind::Ident(obj), values::MemberProp::Ident(prop)) => (obj, prop),
+        _ => unreachable!("variants are always accessed using `Enum.Variant`"),
+    };
+    Expr::Member(MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::from(Expr::Ident(build_ident(&obj.name))),
+        prop: MemberProp::Ident(build_ident(&prop.name)),
+    })
+}
+
+/// Variants that aren't called are either values themselves, e.g. `Option.None`
+/// becomes `new Option.None()`, or are passed around as functions, e.g.
+/// `IpAddr.V4` becomes `(payload)=>new IpAddr.V4(payload)`.
+fn build_variant_constructor(member: &values::Member, has_payload: bool) -> Expr {
+    let payload = build_ident("payload");
+    let new = Expr::New(NewExpr {
+        span: DUMMY_SP,
+        callee: Box::from(build_variant_member(member)),
+        args: Some(match has_payload {
+            true => vec![ExprOrSpread {
+                spread: None,
+                expr: Box::from(Expr::Ident(payload.to_owned())),
+            }],
+            false => vec![],
+        }),
+        type_args: None,
+    });
+    match has_payload {
+        true => Expr::Arrow(ArrowExpr {
+            span: DUMMY_SP,
+            params: vec![Pat::Ident(BindingIdent::from(payload))],
+            body: BlockStmtOrExpr::from(new),
+            is_async: false,
+            is_generator: false,
+            return_type: None,
+            type_params: None,
+        }),
+        false => new,
+    }
+}
+
+/// Constructors and methods always have a block body, unlike arrow functions.
+fn build_block_stmt(body: &values::Expr, ctx: &mut Context) -> BlockStmt {
+    match build_fn_body(body, ctx) {
+        BlockStmtOrExpr::BlockStmt(block) => block,
+        BlockStmtOrExpr::Expr(expr) => BlockStmt {
+            span: DUMMY_SP,
+            stmts: vec![Stmt::Return(ReturnStmt {
+                span: DUMMY_SP,
+                arg: Some(expr),
+            })],
+        },
+    }
+}
+
+/// Evaluates the last expression as a statement instead of returning it.
+/// This is used for constructors, since returning an object from one would
+/// replace the instance, and `finally` blocks.
+fn build_discarded_block_stmt(body: &values::Expr, ctx: &mut Context) -> BlockStmt {
+    let mut block = build_block_stmt(body, ctx);
+    if let Some(Stmt::Return(ReturnStmt { arg: Some(arg), .. })) = block.stmts.last() {
+        let arg = arg.to_owned();
+        block.stmts.pop();
+        match arg.as_ref() {
+            Expr::Ident(Ident { sym, .. }) if sym == "undefined" => (),
+            _ => block.stmts.push(Stmt::Expr(ExprStmt {
+                span: DUMMY_SP,
+                expr: arg,
+            })),
+        }
+    }
+    block
+}
+
 fn build_let_expr(
     let_expr: &values::LetExpr,
     consequent: &values::Expr,
@@ -888,6 +1955,11 @@ fn build_fn_body(body: &values::Expr, ctx: &mut Context) -> BlockStmtOrExpr {
     if stmts.is_empty() {
         // Use fat arrow shorthand, e.g. (x) => x
         BlockStmtOrExpr::Expr(Box::from(ret_expr))
+    } else if ends_with_jump(&stmts) {
+        BlockStmtOrExpr::BlockStmt(BlockStmt {
+            span: DUMMY_SP,
+            stmts,
+        })
     } else {
         let ret = Stmt::Return(ReturnStmt {
             span: DUMMY_SP,
@@ -902,6 +1974,15 @@ fn build_fn_body(body: &values::Expr, ctx: &mut Context) -> BlockStmtOrExpr {
     }
 }
 
+/// Nothing after a `throw`, `break`, or `continue` is reachable so there's no
+/// need to use the value of the expression that contained it.
+fn ends_with_jump(stmts: &[Stmt]) -> bool {
+    matches!(
+        stmts.last(),
+        Some(Stmt::Throw(_) | Stmt::Break(_) | Stmt::Continue(_))
+    )
+}
+
 fn let_to_child(r#let: &values::Let, stmts: &mut Vec<Stmt>, ctx: &mut Context) -> Stmt {
     let values::Let { pattern, init, .. } = r#let;
 
@@ -909,16 +1990,123 @@ fn let_to_child(r#let: &values::Let, stmts: &mut Vec<Stmt>, ctx: &mut Context) -
     // unique identifiers.
     match pattern {
         Some(pattern) => match build_pattern(pattern, stmts, ctx) {
-            Some(name) => build_const_decl_stmt_with_pat(name, build_expr(init, stmts, ctx)),
+            Some(name) => build_var_decl_stmt(
+                build_var_decl_kind(pattern),
+                name,
+                build_expr(init, stmts, ctx),
+            ),
             None => todo!(),
         },
-        None => Stmt::Expr(ExprStmt {
+        None => build_expr_stmt(init, stmts, ctx),
+    }
+}
+
+/// Loops, `break`, and `continue` are emitted as native statements when their
+/// value isn't used.
+fn build_expr_stmt(expr: &values::Expr, stmts: &mut Vec<Stmt>, ctx: &mut Context) -> Stmt {
+    match &expr.kind {
+        values::ExprKind::ForOf(_)
+        | values::ExprKind::While(_)
+        | values::ExprKind::Break
+        | values::ExprKind::Continue => build_loop_stmt(expr, stmts, ctx),
+        _ => Stmt::Expr(ExprStmt {
             span: DUMMY_SP,
-            expr: Box::from(build_expr(init, stmts, ctx)),
+            expr: Box::from(build_expr(expr, stmts, ctx)),
         }),
     }
 }
 
+fn build_loop_stmt(expr: &values::Expr, stmts: &mut Vec<Stmt>, ctx: &mut Context) -> Stmt {
+    match &expr.kind {
+        values::ExprKind::ForOf(values::ForOf {
+            pattern,
+            expr,
+            body,
+        }) => {
+            let right = build_expr(expr, stmts, ctx);
+            let name = build_pattern(pattern, stmts, ctx).unwrap();
+            let left = VarDeclOrPat::VarDecl(Box::from(VarDecl {
+                span: DUMMY_SP,
+                kind: build_var_decl_kind(pattern),
+                declare: false,
+                decls: vec![VarDeclarator {
+                    span: DUMMY_SP,
+                    name,
+                    init: None,
+                    definite: false,
+                }],
+            }));
+            let body = build_discarded_block_stmt(body, ctx);
+
+            Stmt::ForOf(ForOfStmt {
+                span: DUMMY_SP,
+                await_token: None,
+                left,
+                right: Box::from(right),
+                body: Box::from(Stmt::Block(body)),
+            })
+        }
+        values::ExprKind::While(values::While { cond, body }) => {
+            let mut cond_stmts: Vec<Stmt> = vec![];
+            let test = build_expr(cond, &mut cond_stmts, ctx);
+            let mut body = build_discarded_block_stmt(body, ctx);
+
+            if cond_stmts.is_empty() {
+                return Stmt::While(WhileStmt {
+                    span: DUMMY_SP,
+                    test: Box::from(test),
+                    body: Box::from(Stmt::Block(body)),
+                });
+            }
+
+            // The statements for the condition have to be run before each
+            // iteration so they're moved inside of the loop, e.g.
+            // while (true) { ...; if (!(<cond>)) { break; } ... }
+            cond_stmts.push(Stmt::If(IfStmt {
+                span: DUMMY_SP,
+                test: Box::from(Expr::Unary(UnaryExpr {
+                    span: DUMMY_SP,
+                    op: UnaryOp::Bang,
+                    arg: Box::from(Expr::Paren(ParenExpr {
+                        span: DUMMY_SP,
+                        expr: Box::from(test),
+                    })),
+                })),
+                cons: Box::from(Stmt::Block(BlockStmt {
+                    span: DUMMY_SP,
+                    stmts: vec![Stmt::Break(BreakStmt {
+                        span: DUMMY_SP,
+                        label: None,
+                    })],
+                })),
+                alt: None,
+            }));
+            cond_stmts.append(&mut body.stmts);
+
+            Stmt::While(WhileStmt {
+                span: DUMMY_SP,
+                test: Box::from(Expr::Lit(Lit::Bool(Bool {
+                    span: DUMMY_SP,
+                    value: true,
+                }))),
+                body: Box::from(Stmt::Block(BlockStmt {
+                    span: DUMMY_SP,
+                    stmts: cond_stmts,
+                })),
+            })
+        }
+        values::ExprKind::Break => Stmt::Break(BreakStmt {
+            span: DUMMY_SP,
+            label: None,
+        }),
+        values::ExprKind::Continue => Stmt::Continue(ContinueStmt {
+            span: DUMMY_SP,
+            label: None,
+        }),
+        _ => unreachable!("build_loop_stmt() called with a non-loop expression"),
+    }
+}
+
 fn build_cond_for_pat(pat: &values::Pattern, id: &Ident) -> Option<Expr> {
     if values::is_refutable(pat) {
         // Right now the only refutable pattern we support is LitPat.
@@ -936,10 +2124,11 @@ fn build_cond_for_pat(pat: &values::Pattern, id: &Ident) -> Option<Expr> {
             None => return None,
         };
 
+        // All of the checks have to pass for the pattern to match.
         Some(iter.fold(first, |prev, next| {
             Expr::Bin(BinExpr {
                 span: DUMMY_SP,
-                op: BinaryOp::LogicalOr,
+                op: BinaryOp::LogicalAnd,
                 left: Box::from(prev),
                 right: Box::from(cond_to_expr(next, id)),
             })
@@ -1003,6 +2192,8 @@ enum Check {
     EqualLit(values::Lit),
     Typeof(String), // limit this to primitives: "number", "string", "boolean"
     Instanceof(values::Ident),
+    /// `enum` and `variant`, e.g. `$temp_0 instanceof IpAddr.V4`
+    InstanceofVariant(values::Ident, values::Ident),
     // TODO: array length
 }
 
@@ -1023,19 +2214,14 @@ fn get_conds_for_pat(pat: &values::Pattern, conds: &mut Vec<Condition>, path: &m
 
         // refutable and possibly refutable
         values::PatternKind::Object(values::ObjectPat { props, .. }) => {
-            for prop in props {
-                match prop {
-                    values::ObjectPatProp::KeyValue(values::KeyValuePatProp {
-                        value, key, ..
-                    }) => {
-                        path.push(PathElem::ObjProp(key.name.clone()));
-                        get_conds_for_pat(value, conds, path);
-                        path.pop();
-                    }
-                    values::ObjectPatProp::Shorthand(_) => (),
-                    values::ObjectPatProp::Rest(_) => (),
-                }
-            }
+            get_conds_for_props(props, conds, path);
+        }
+        values::PatternKind::Struct(values::StructPat { ident, props }) => {
+            conds.push(Condition {
+                path: path.to_owned(),
+                check: Check::Instanceof(ident.to_owned()),
+            });
+            get_conds_for_props(props, conds, path);
         }
         values::PatternKind::Array(values::ArrayPat { elems, .. }) => {
             for (index, elem) in elems.iter().enumerate() {
@@ -1052,6 +2238,21 @@ fn get_conds_for_pat(pat: &values::Pattern, conds: &mut Vec<Condition>, path: &m
                 check: Check::EqualLit(lit.to_owned()),
             });
         }
+        values::PatternKind::EnumVariant(values::EnumVariantPat {
+            r#enum,
+            variant,
+            payload,
+        }) => {
+            conds.push(Condition {
+                path: path.to_owned(),
+                check: Check::InstanceofVariant(r#enum.to_owned(), variant.to_owned()),
+            });
+            if let Some(payload) = payload {
+                path.push(PathElem::ObjProp(String::from("payload")));
+                get_conds_for_pat(payload, conds, path);
+                path.pop();
+            }
+        }
         values::PatternKind::Is(values::IsPat { is_id, .. }) => match is_id.name.as_ref() {
             "string" | "number" | "boolean" => {
                 conds.push(Condition {
@@ -1060,7 +2261,6 @@ fn get_conds_for_pat(pat: &values::Pattern, conds: &mut Vec<Condition>, path: &m
                 });
             }
             _ => {
-                println!("adding Check::Instanceof condition");
                 conds.push(Condition {
                     path: path.to_owned(),
                     check: Check::Instanceof(is_id.to_owned()),
@@ -1070,6 +2270,24 @@ fn get_conds_for_pat(pat: &values::Pattern, conds: &mut Vec<Condition>, path: &m
     }
 }
 
+fn get_conds_for_props(
+    props: &[values::ObjectPatProp],
+    conds: &mut Vec<Condition>,
+    path: &mut Path,
+) {
+    for prop in props {
+        match prop {
+            values::ObjectPatProp::KeyValue(values::KeyValuePatProp { value, key, .. }) => {
+                path.push(PathElem::ObjProp(key.name.clone()));
+                get_conds_for_pat(value, conds, path);
+                path.pop();
+            }
+            values::ObjectPatProp::Shorthand(_) => (),
+            values::ObjectPatProp::Rest(_) => (),
+        }
+    }
+}
+
 fn cond_to_expr(cond: &Condition, id: &Ident) -> Expr {
     let Condition { check, path } = cond;
 
@@ -1126,6 +2344,16 @@ fn cond_to_expr(cond: &Condition, id: &Ident) -> Expr {
             left: Box::from(left),
             right: Box::from(Expr::Ident(Ident::from(id))),
         }),
+        Check::InstanceofVariant(r#enum, variant) => Expr::Bin(BinExpr {
+            span: DUMMY_SP,
+            op: BinaryOp::InstanceOf,
+            left: Box::from(left),
+            right: Box::from(Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::from(Expr::Ident(Ident::from(r#enum))),
+                prop: MemberProp::Ident(Ident::from(variant)),
+            })),
+        }),
     }
 }
 
@@ -1134,9 +2362,21 @@ fn build_const_decl_stmt(id: &Ident, expr: Expr) -> Stmt {
 }
 
 fn build_const_decl_stmt_with_pat(name: Pat, expr: Expr) -> Stmt {
+    build_var_decl_stmt(VarDeclKind::Const, name, expr)
+}
+
+/// Bindings declared with `mut` can be reassigned so they can't use `const`.
+fn build_var_decl_kind(pattern: &values::Pattern) -> VarDeclKind {
+    match values::has_mutable_binding(pattern) {
+        true => VarDeclKind::Let,
+        false => VarDeclKind::Const,
+    }
+}
+
+fn build_var_decl_stmt(kind: VarDeclKind, name: Pat, expr: Expr) -> Stmt {
     Stmt::Decl(Decl::Var(Box::from(VarDecl {
         span: DUMMY_SP,
-        kind: VarDeclKind::Const,
+        kind,
         declare: false,
         decls: vec![VarDeclarator {
             span: DUMMY_SP,
@@ -1,5 +1,7 @@
 use crochet_codegen::d_ts::codegen_d_ts;
-use crochet_codegen::js::codegen_js;
+use crochet_codegen::js::{
+    codegen_js, codegen_js_with_options, JsOptions, JsxRuntime, ModuleFormat,
+};
 use crochet_infer::{infer_prog, Context};
 use crochet_parser::parse;
 
@@ -601,6 +603,21 @@ fn partial_application_with_spread() {
     "###);
 }
 
+#[test]
+fn pipelines() {
+    let src = r#"
+    let double = (x) => x * 2;
+    let add = (a, b) => a + b;
+    let result = 5 |> double |> add(1, _);
+    "#;
+
+    insta::assert_snapshot!(compile(src), @r###"
+    export const double = (x)=>x * 2;
+    export const add = (a, b)=>a + b;
+    export const result = add(1, double(5));
+    "###);
+}
+
 #[test]
 fn spread_args() {
     let src = r#"
@@ -656,14 +673,14 @@ fn mutable_obj() {
     let result = codegen_d_ts(&program, &ctx);
 
     // This should be:
-    // declare type MutablePoint = {x: number; y: number};
-    // declare type Point = Readonly<{x: number; y: number}>;
+    // export declare type MutablePoint = {x: number; y: number};
+    // export declare type Point = Readonly<{x: number; y: number}>;
     insta::assert_snapshot!(result, @r###"
-    declare type MutablePoint = {
+    export declare type MutablePoint = {
         x: number;
         y: number;
     };
-    declare type Point = {
+    export declare type Point = {
         readonly x: number;
         readonly y: number;
     };
@@ -683,11 +700,619 @@ fn mutable_indexer() {
     let result = codegen_d_ts(&program, &ctx);
 
     insta::assert_snapshot!(result, @r###"
-    declare type Dict = {
+    export declare type Dict = {
         readonly [key: string]: string;
     };
-    declare type MutableDict = {
+    export declare type MutableDict = {
         [key: string]: string;
     };
     "###);
 }
+
+#[test]
+fn common_js_exports() {
+    let src = r#"
+    let foo = "hello";
+    let {x, y} = {x: 5, y: 10};
+    "#;
+    let program = parse(src).unwrap();
+    let options = JsOptions {
+        module: ModuleFormat::CommonJs,
+        ..JsOptions::default()
+    };
+
+    insta::assert_snapshot!(codegen_js_with_options(&program, &options), @r###"
+    const foo = "hello";
+    exports.foo = foo;
+    const { x , y  } = {
+        x: 5,
+        y: 10
+    };
+    exports.x = x;
+    exports.y = y;
+    "###);
+}
+
+#[test]
+fn classic_jsx_runtime() {
+    let src = r#"
+    let elem = <div>Hello</div>;
+    "#;
+    let program = parse(src).unwrap();
+    let options = JsOptions {
+        jsx_runtime: JsxRuntime::Classic,
+        ..JsOptions::default()
+    };
+
+    insta::assert_snapshot!(codegen_js_with_options(&program, &options), @r###"
+    export const elem = React.createElement("div", null, "Hello");
+    "###);
+}
+
+const MODULE_SRC: &str = r#"
+import {add, sum as plus, type Point} from "./math";
+import type {Foo} from "./types";
+import * as utils from "./utils";
+export let x = 5;
+let y = 10;
+export {y as z, type Bar};
+export type Baz = number;
+type Bar = string;
+type Hidden = boolean;
+export {a, b as c, type T} from "./other";
+export * from "./all";
+export * as ns from "./ns";
+"#;
+
+#[test]
+fn imports_and_exports() {
+    insta::assert_snapshot!(compile(MODULE_SRC), @r###"
+    import { add, sum as plus } from "./math";
+    ;
+    import * as utils from "./utils";
+    export const x = 5;
+    const y = 10;
+    export { y as z };
+    ;
+    ;
+    ;
+    export { a, b as c } from "./other";
+    export * from "./all";
+    export * as ns from "./ns";
+    "###);
+}
+
+#[test]
+fn imports_and_exports_common_js() {
+    let program = parse(MODULE_SRC).unwrap();
+    let options = JsOptions {
+        module: ModuleFormat::CommonJs,
+        ..JsOptions::default()
+    };
+
+    insta::assert_snapshot!(codegen_js_with_options(&program, &options), @r###"
+    const { add: add , sum: plus  } = require("./math");
+    ;
+    const utils = require("./utils");
+    const x = 5;
+    exports.x = x;
+    const y = 10;
+    exports.z = y;
+    ;
+    ;
+    ;
+    exports.a = require("./other").a;
+    exports.c = require("./other").b;
+    Object.assign(exports, require("./all"));
+    exports.ns = require("./ns");
+    "###);
+}
+
+#[test]
+fn d_ts_only_includes_exports() {
+    let mut program = parse(MODULE_SRC).unwrap();
+    let mut ctx = Context::default();
+    let ctx = infer_prog(&mut program, &mut ctx).unwrap();
+
+    insta::assert_snapshot!(codegen_d_ts(&program, &ctx), @r###"
+    import { add, sum as plus, Point } from "./math";
+    import { Foo } from "./types";
+    import * as utils from "./utils";
+    declare type Hidden = boolean;
+    export declare type Bar = string;
+    export declare type Baz = number;
+    export declare const x: 5;
+    export declare const z: 10;
+    export { a, b as c, T } from "./other";
+    export * from "./all";
+    export * as ns from "./ns";
+    "###);
+}
+
+const CLASSES_SRC: &str = r#"
+class Point {
+    x: number;
+    mut y: number = 0;
+    static origin: number = 0;
+    constructor(x: number, y: number) {
+        this.x = x;
+        this.y = y;
+    }
+    add(other: Point) {
+        new Point(this.x + other.x, this.y + other.y)
+    }
+}
+class Point3d extends Point {
+    z: number = 0;
+    shift() {
+        super.add(this)
+    }
+}
+let p = new Point(1, 2);
+"#;
+
+#[test]
+fn classes() {
+    insta::assert_snapshot!(compile(CLASSES_SRC), @r###"
+    export class Point {
+        x;
+        y = 0;
+        static origin = 0;
+        constructor(x, y){
+            this.x = x;
+            this.y = y;
+        }
+        add(other) {
+            return new Point(this.x + other.x, this.y + other.y);
+        }
+    }
+    export class Point3d extends Point {
+        z = 0;
+        shift() {
+            return super.add(this);
+        }
+    }
+    export const p = new Point(1, 2);
+    "###);
+}
+
+#[test]
+fn classes_common_js() {
+    let program = parse("export class Foo {}").unwrap();
+    let options = JsOptions {
+        module: ModuleFormat::CommonJs,
+        ..JsOptions::default()
+    };
+
+    insta::assert_snapshot!(codegen_js_with_options(&program, &options), @r###"
+    class Foo {
+    }
+    exports.Foo = Foo;
+    "###);
+}
+
+#[test]
+fn classes_d_ts() {
+    let mut program = parse(CLASSES_SRC).unwrap();
+    let mut ctx = Context::default();
+    let ctx = infer_prog(&mut program, &mut ctx).unwrap();
+
+    insta::assert_snapshot!(codegen_d_ts(&program, &ctx), @r###"
+    export declare class Point {
+        readonly x: number;
+        y: number;
+        static readonly origin: number;
+        constructor(x: number, y: number);
+        add(other: Point): Point;
+    }
+    export declare class Point3d extends Point {
+        readonly z: number;
+        shift(): Point;
+    }
+    export declare const p: Point;
+    "###);
+}
+
+#[test]
+fn classes_d_ts_without_exports() {
+    let src = r#"
+    type Coord = number;
+    class Point {
+        x: Coord;
+        constructor(x: Coord) {
+            this.x = x;
+        }
+    }
+    "#;
+    let mut program = parse(src).unwrap();
+    let mut ctx = Context::default();
+    let ctx = infer_prog(&mut program, &mut ctx).unwrap();
+
+    insta::assert_snapshot!(codegen_d_ts(&program, &ctx), @r###"
+    export declare type Coord = number;
+    export declare class Point {
+        readonly x: Coord;
+        constructor(x: Coord);
+    }
+    "###);
+}
+
+#[test]
+fn try_catch() {
+    let src = r#"
+    let result = try {
+        console.log("parsing input");
+        JSON.parse(input)
+    } catch (e) {
+        console.log("parsing failed");
+        {x: 0, y: 0}
+    } finally {
+        console.log("done");
+    };
+    "#;
+
+    insta::assert_snapshot!(compile(src), @r###"
+    let $temp_0;
+    try {
+        console.log("parsing input");
+        $temp_0 = JSON.parse(input);
+    } catch (e) {
+        console.log("parsing failed");
+        $temp_0 = {
+            x: 0,
+            y: 0
+        };
+    } finally{
+        console.log("done");
+    }
+    export const result = $temp_0;
+    "###);
+}
+
+#[test]
+fn throw_expressions() {
+    let src = r#"
+    let fail = (msg) => throw msg;
+    let x = if (cond) { 5 } else { throw "error" };
+    "#;
+
+    insta::assert_snapshot!(compile(src), @r###"
+    export const fail = (msg)=>{
+        throw msg;
+    };
+    let $temp_0;
+    if (cond) {
+        $temp_0 = 5;
+    } else {
+        throw "error";
+    }
+    export const x = $temp_0;
+    "###);
+}
+
+#[test]
+fn do_expressions_as_arguments() {
+    let src = "let sum = add(do { let x = 5; x }, 10);";
+
+    insta::assert_snapshot!(compile(src), @r###"
+    let $temp_0;
+    {
+        const x = 5;
+        $temp_0 = x;
+    }export const sum = add($temp_0, 10);
+    "###);
+}
+
+#[test]
+fn loops() {
+    let src = r#"
+    let sum = (nums) => {
+        let mut total: number = 0;
+        for (let num of nums) {
+            if (num < 0) { continue; };
+            total = total + num;
+        }
+        total
+    };
+    let mut i: number = 0;
+    while (i < 10) {
+        i = i + 1;
+    }
+    "#;
+
+    insta::assert_snapshot!(compile(src), @r###"
+    export const sum = (nums)=>{
+        let total = 0;
+        for (const num of nums){
+            let $temp_0;
+            if (num < 0) {
+                continue;
+            }
+            $temp_0;
+            total = total + num;
+        }
+        return total;
+    };
+    export let i = 0;
+    while(i < 10){
+        i = i + 1;
+    }
+    "###);
+}
+
+#[test]
+fn while_conditions_are_evaluated_on_each_iteration() {
+    let src = r#"
+    let mut i: number = 10;
+    while (do { let j = i; j } > 0) {
+        i = i - 1;
+    }
+    "#;
+
+    insta::assert_snapshot!(compile(src), @r###"
+    export let i = 10;
+    while(true){
+        let $temp_0;
+        {
+            const j = i;
+            $temp_0 = j;
+        }
+        if (!($temp_0 > 0)) {
+            break;
+        }
+        i = i - 1;
+    }
+    "###);
+}
+
+#[test]
+fn enums() {
+    let src = r#"
+    enum IpAddr {
+        V4(string),
+        V6(string),
+    }
+    let home = IpAddr.V4("127.0.0.1");
+    let toAddr = IpAddr.V6;
+    declare let addr: IpAddr;
+    let addrStr = match (addr) {
+        IpAddr.V4(address) -> address,
+        IpAddr.V6("::1") -> "localhost",
+        IpAddr.V6(address) -> address
+    };
+    "#;
+
+    insta::assert_snapshot!(compile(src), @r###"
+    export const IpAddr = {
+        V4: class V4 {
+            constructor(payload){
+                this.payload = payload;
+            }
+        },
+        V6: class V6 {
+            constructor(payload){
+                this.payload = payload;
+            }
+        }
+    };
+    export const home = new IpAddr.V4("127.0.0.1");
+    export const toAddr = (payload)=>new IpAddr.V6(payload);
+    ;
+    let $temp_0;
+    const $temp_1 = addr;
+    if ($temp_1 instanceof IpAddr.V4) {
+        const { payload: address  } = $temp_1;
+        $temp_0 = address;
+    } else if ($temp_1 instanceof IpAddr.V6 && $temp_1.payload === "::1") {
+        $temp_0 = "localhost";
+    } else if ($temp_1 instanceof IpAddr.V6) {
+        const { payload: address  } = $temp_1;
+        $temp_0 = address;
+    }
+    export const addrStr = $temp_0;
+    "###);
+}
+
+#[test]
+fn enum_variants_without_payloads_are_constructed() {
+    let src = r#"
+    enum Option<T> {
+        Some(T),
+        None,
+    }
+    let none: Option<number> = Option.None;
+    "#;
+
+    insta::assert_snapshot!(compile(src), @r###"
+    export const Option = {
+        Some: class Some {
+            constructor(payload){
+                this.payload = payload;
+            }
+        },
+        None: class None {
+        }
+    };
+    export const none = new Option.None();
+    "###);
+}
+
+#[test]
+fn enums_d_ts() {
+    let src = r#"
+    enum Option<T> {
+        Some(T),
+        None,
+    }
+    let none: Option<number> = Option.None;
+    "#;
+    let mut program = parse(src).unwrap();
+    let mut ctx = Context::default();
+    let ctx = infer_prog(&mut program, &mut ctx).unwrap();
+
+    insta::assert_snapshot!(codegen_d_ts(&program, &ctx), @r###"
+    export declare type Option<A> = Option.Some<A> | Option.None<A>;
+    export declare module Option {
+        class Some<A> {
+            #private;
+            readonly payload: A;
+            constructor(payload: A);
+        }
+        class None<A> {
+            #private;
+        }
+    }
+    export declare const none: Option<number>;
+    "###);
+}
+
+#[test]
+fn structs() {
+    let src = r#"
+    struct User {
+        name: string,
+        mut age: number,
+    }
+    let user = new User({name: "Alice", age: 30});
+    let name = match (user) {
+        User {name, age: 30} -> name,
+        _ -> "someone else"
+    };
+    "#;
+
+    insta::assert_snapshot!(compile(src), @r###"
+    export class User {
+        constructor(values){
+            this.name = values.name;
+            this.age = values.age;
+        }
+    }
+    export const user = new User({
+        name: "Alice",
+        age: 30
+    });
+    ;
+    let $temp_0;
+    const $temp_1 = user;
+    if ($temp_1 instanceof User && $temp_1.age === 30) {
+        const { name  } = $temp_1;
+        $temp_0 = name;
+    } else {
+        $temp_0 = "someone else";
+    }
+    export const name = $temp_0;
+    "###);
+}
+
+#[test]
+fn structs_d_ts() {
+    let src = r#"
+    struct User {
+        name: string,
+        mut age: number,
+    }
+    let user = new User({name: "Alice", age: 30});
+    "#;
+    let mut program = parse(src).unwrap();
+    let mut ctx = Context::default();
+    let ctx = infer_prog(&mut program, &mut ctx).unwrap();
+
+    insta::assert_snapshot!(codegen_d_ts(&program, &ctx), @r###"
+    export declare class User {
+        readonly name: string;
+        age: number;
+        constructor(values: {
+            name: string;
+            age: number;
+        });
+    }
+    export declare const user: User;
+    "###);
+}
+
+#[test]
+fn traits() {
+    let src = r#"
+    trait Greet {
+        name(): string;
+        greet(): string {
+            `Hello, ${this.name()}`
+        }
+    }
+    struct User {
+        first: string,
+    }
+    impl Greet for User {
+        name() {
+            this.first
+        }
+    }
+    let user = new User({first: "Alice"});
+    let msg = user.greet();
+    "#;
+
+    insta::assert_snapshot!(compile(src), @r###"
+    export const Greet = {
+        greet () {
+            return `Hello, ${this.name()}`;
+        }
+    };
+    export class User {
+        constructor(values){
+            this.first = values.first;
+        }
+    }
+    Object.assign(User.prototype, Greet, {
+        name () {
+            return this.first;
+        }
+    });
+    export const user = new User({
+        first: "Alice"
+    });
+    export const msg = user.greet();
+    "###);
+}
+
+#[test]
+fn traits_d_ts() {
+    let src = r#"
+    trait Greet {
+        name(): string;
+        greet(): string {
+            `Hello, ${this.name()}`
+        }
+    }
+    struct User {
+        first: string,
+    }
+    impl Greet for User {
+        name() {
+            this.first
+        }
+    }
+    let user = new User({first: "Alice"});
+    "#;
+    let mut program = parse(src).unwrap();
+    let mut ctx = Context::default();
+    let ctx = infer_prog(&mut program, &mut ctx).unwrap();
+
+    insta::assert_snapshot!(codegen_d_ts(&program, &ctx), @r###"
+    export declare interface Greet {
+        name(): string;
+        greet(): string;
+    }
+    export declare class User {
+        readonly first: string;
+        constructor(values: {
+            first: string;
+        });
+    }
+    export declare interface User extends Greet {
+    }
+    export declare const Greet: {
+        readonly greet: () => string;
+    };
+    export declare const user: User;
+    "###);
+}
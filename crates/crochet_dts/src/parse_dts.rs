@@ -2,13 +2,13 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use types::{TCallable, TIndex, TObjElem, TObject, TypeKind};
 
-use swc_common::{comments::SingleThreadedComments, FileName, SourceMap};
+use swc_common::{comments::SingleThreadedComments, FileName, SourceMap, Spanned};
 use swc_ecma_ast::*;
 use swc_ecma_parser::{error::Error, parse_file_as_module, Syntax, TsConfig};
 use swc_ecma_visit::*;
 
 use crochet_ast::types::{self as types, RestPat, TFnParam, TKeyword, TPat, TProp, Type};
-use crochet_ast::values::Lit;
+use crochet_ast::values::{Lit, Span};
 use crochet_infer::{close_over, generalize, normalize, Context, Env, Subst, Substitutable};
 
 use crate::util;
@@ -185,7 +185,6 @@ fn infer_fn_params(params: &[TsFnParam], ctx: &Context) -> Result<Vec<TFnParam>,
             }
             TsFnParam::Array(_) => {
                 // TODO: create a tuple pattern
-                println!("skipping TsFnParam::Array(_)");
                 None
             }
             TsFnParam::Rest(rest) => {
@@ -208,7 +207,6 @@ fn infer_fn_params(params: &[TsFnParam], ctx: &Context) -> Result<Vec<TFnParam>,
             }
             TsFnParam::Object(_) => {
                 // TODO: create an object pattern
-                println!("skipping TsFnParam::Object(_)");
                 None
             }
         })
@@ -310,7 +308,6 @@ fn infer_ts_type_element(elem: &TsTypeElement, ctx: &Context) -> Result<TObjElem
         TsTypeElement::TsMethodSignature(sig) => {
             let t = infer_method_sig(sig, ctx)?;
             let gen_t = generalize(&HashMap::default(), &t);
-            // println!("t = {t}, gen_t = {gen_t}");
             let name = get_key_name(sig.key.as_ref())?;
             Ok(TObjElem::Prop(TProp {
                 name,
@@ -341,17 +338,8 @@ fn infer_interface_decl(decl: &TsInterfaceDecl, ctx: &Context) -> Result<Type, S
         .body
         .body
         .iter()
-        .filter_map(|elem| {
-            let prop = infer_ts_type_element(elem, ctx);
-
-            match prop {
-                Ok(prop) => Some(prop),
-                Err(msg) => {
-                    println!("Err: {msg}");
-                    None
-                }
-            }
-        })
+        // Elements we don't know how to infer yet are skipped.
+        .filter_map(|elem| infer_ts_type_element(elem, ctx).ok())
         .collect();
 
     // TODO: make this generic if the decl has any type params
@@ -369,21 +357,16 @@ fn infer_interface_decl(decl: &TsInterfaceDecl, ctx: &Context) -> Result<Type, S
 impl Visit for InterfaceCollector {
     fn visit_ts_interface_decl(&mut self, decl: &TsInterfaceDecl) {
         let name = decl.id.sym.to_string();
-        println!("inferring: {name}");
-        match infer_interface_decl(decl, &self.ctx) {
-            Ok(t) => {
-                match self.ctx.lookup_type(&name).ok() {
-                    Some(existing_t) => {
-                        let merged_t = util::merge_types(&existing_t, &t);
-                        let merged_t = normalize(&merged_t, &self.ctx);
-                        self.ctx.insert_type(name, merged_t)
-                    }
-                    None => self.ctx.insert_type(name, t),
-                };
-            }
-            Err(_) => {
-                println!("couldn't infer {name}");
-            }
+        // Interfaces that can't be inferred yet are left out of the context.
+        if let Ok(t) = infer_interface_decl(decl, &self.ctx) {
+            match self.ctx.lookup_type(&name).ok() {
+                Some(existing_t) => {
+                    let merged_t = util::merge_types(&existing_t, &t);
+                    let merged_t = normalize(&merged_t, &self.ctx);
+                    self.ctx.insert_type(name, merged_t)
+                }
+                None => self.ctx.insert_type(name, t),
+            };
         }
     }
 
@@ -391,7 +374,6 @@ impl Visit for InterfaceCollector {
         match &decl.id {
             TsModuleName::Ident(id) => {
                 let name = id.sym.to_string();
-                println!("module: {name}");
                 self.namespace.push(name);
                 decl.visit_children_with(self);
                 self.namespace.pop();
@@ -431,6 +413,17 @@ impl Visit for InterfaceCollector {
 
 impl InterfaceCollector {}
 
+/// Returns the message for an error from `parse_dts` along with the range
+/// of bytes in the source that it applies to.
+pub fn describe_error(err: &Error) -> (String, Span) {
+    // The first file in a `SourceMap` starts at 1, `parse_dts` only ever
+    // adds a single file.
+    let span = err.span();
+    let start = (span.lo.0 as usize).saturating_sub(1);
+    let end = (span.hi.0 as usize).saturating_sub(1);
+    (err.kind().msg().to_string(), start..end)
+}
+
 pub fn parse_dts(d_ts_source: &str) -> Result<Context, Error> {
     let cm = Arc::<SourceMap>::default();
     let fm = cm.new_source_file(FileName::Anon, d_ts_source.to_owned());
@@ -70,6 +70,8 @@ fn replace_aliases_rec(t: &Type, map: &HashMap<String, TVar>) -> Type {
         }),
         TypeKind::Lit(_) => return t.to_owned(),
         TypeKind::Keyword(_) => return t.to_owned(),
+        // Enums and traits are only declared in crochet code, never in .d.ts files.
+        TypeKind::Enum(_) | TypeKind::Trait(_) => return t.to_owned(),
         TypeKind::Union(types) => {
             TypeKind::Union(types.iter().map(|t| replace_aliases_rec(t, map)).collect())
         }
@@ -123,8 +125,6 @@ fn replace_aliases_rec(t: &Type, map: &HashMap<String, TVar>) -> Type {
                         })
                     }
                     TObjElem::Prop(prop) => {
-                        println!("replacing prop.t");
-                        println!("prop.t = {:#}", prop.t);
                         let t = replace_aliases_rec(&prop.t, map);
                         TObjElem::Prop(types::TProp {
                             t,
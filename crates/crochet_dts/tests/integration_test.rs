@@ -304,7 +304,7 @@ fn infer_index_with_incorrect_key_type_on_interface() {
         vec![
             "\"hello\" is an invalid key for object types",
             "Location",
-            "TypeError"
+            "TypeError[E0008]: \"hello\" is an invalid key for {[x: number]: number, bar: boolean}"
         ]
     );
 }
@@ -397,3 +397,13 @@ fn merging_generic_interfaces() {
         "<t0>{bar: (x: t0) => number, baz: (x: t0) => string}"
     );
 }
+
+#[test]
+fn describe_parse_error() {
+    let src = "interface Foo {\n  bar: ;\n}";
+    let err = parse_dts(src).unwrap_err();
+    let (message, span) = describe_error(&err);
+
+    assert!(!message.is_empty());
+    assert_eq!(&src[span], ";");
+}
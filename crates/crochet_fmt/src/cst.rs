@@ -0,0 +1,384 @@
+use tree_sitter::Node;
+
+use crate::doc::*;
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "catch", "const", "declare", "do", "else", "export", "extends",
+    "finally", "for", "from", "if", "import", "in", "is", "keyof", "let", "match", "mut", "new",
+    "of", "rec", "return", "throw", "try", "type", "typeof", "while",
+];
+
+// Nodes whose source text is kept as is.
+const VERBATIM: &[&str] = &[
+    "comment",
+    "hash_bang_line",
+    "jsx_closing_element",
+    "number",
+    "regex",
+    "string",
+    "template_string",
+];
+
+struct Delims {
+    open: &'static str,
+    close: &'static str,
+    // Whether to put spaces inside the delimiters when printed on one line
+    padded: bool,
+    // Whether each item should always be on its own line
+    force_break: bool,
+}
+
+fn delims_for_kind(kind: &str) -> Option<Delims> {
+    let (open, close, padded, force_break) = match kind {
+        "arguments" | "formal_parameters" => ("(", ")", false, false),
+        "array" | "array_pattern" | "refutable_array_pattern" | "tuple_type" => {
+            ("[", "]", false, false)
+        }
+        "type_arguments" | "type_parameters" => ("<", ">", false, false),
+        "object" | "object_pattern" | "object_type" | "refutable_object_pattern" => {
+            ("{", "}", true, false)
+        }
+        "match_arms" => ("{", "}", true, true),
+        _ => return None,
+    };
+    Some(Delims {
+        open,
+        close,
+        padded,
+        force_break,
+    })
+}
+
+fn node_text<'a>(node: &Node, src: &'a str) -> &'a str {
+    &src[node.byte_range()]
+}
+
+fn children<'a>(node: &Node<'a>) -> Vec<Node<'a>> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).collect()
+}
+
+fn is_line_comment(node: &Node, src: &str) -> bool {
+    node.kind() == "comment" && node_text(node, src).starts_with("//")
+}
+
+fn is_word(token: &str) -> bool {
+    match token.chars().next() {
+        Some(c) => c.is_alphanumeric() || c == '_' || c == '$' || c == '"' || c == '\'',
+        None => false,
+    }
+}
+
+fn is_keyword(token: &str) -> bool {
+    KEYWORDS.contains(&token)
+}
+
+/// Returns the first (or last) token of `node`, verbatim nodes count as a
+/// single token.
+fn edge_token<'a>(node: &Node, src: &'a str, first: bool) -> &'a str {
+    let mut node = *node;
+    while node.child_count() > 0 && !VERBATIM.contains(&node.kind()) {
+        let index = if first { 0 } else { node.child_count() - 1 };
+        node = node.child(index).unwrap();
+    }
+    node_text(&node, src)
+}
+
+/// Decides whether there should be a space between two adjacent siblings
+/// inside of `parent`.
+fn space_between(prev: &Node, next: &Node, parent: &Node, src: &str) -> bool {
+    if next.kind() == "comment" {
+        return true;
+    }
+
+    let p = edge_token(prev, src, false);
+    let n = edge_token(next, src, true);
+
+    if matches!(n, "," | ";" | ")" | "]" | "." | "?." | ":") {
+        return false;
+    }
+    if n == "!" && parent.kind() == "non_null_expression" {
+        return false;
+    }
+    if n == "?" && is_word(p) {
+        return false;
+    }
+    if matches!(p, "(" | "[" | "." | "?." | "...") {
+        return false;
+    }
+    if matches!(n, "(" | "[" | "<") {
+        // Calls, subscripts and type args hug whatever comes before them,
+        // but keywords like `if`, `match` and operators like `=>` don't.
+        return !(matches!(p, ")" | "]" | ">") || is_word(p) && !is_keyword(p));
+    }
+    if p == "<" && matches!(parent.kind(), "type_arguments" | "type_parameters") {
+        return false;
+    }
+
+    true
+}
+
+/// Converts a tree-sitter node into a `Doc`.
+pub fn build_doc(node: &Node, src: &str) -> Doc {
+    let kind = node.kind();
+
+    if VERBATIM.contains(&kind) || node.child_count() == 0 {
+        return text(node_text(node, src));
+    }
+
+    if let Some(delims) = delims_for_kind(kind) {
+        return build_delimited(node, &delims, src);
+    }
+
+    match kind {
+        "program" => match build_statements(&children(node), src) {
+            Doc::Nil => Doc::Nil,
+            doc => concat(vec![doc, Doc::HardLine]),
+        },
+        "statement_block" => build_block(node, src),
+        "binary_expression" => {
+            let left = node.child_by_field_name("left").unwrap();
+            let op = node.child_by_field_name("operator").unwrap();
+            let right = node.child_by_field_name("right").unwrap();
+            group(concat(vec![
+                build_doc(&left, src),
+                text(" "),
+                text(node_text(&op, src)),
+                indent(concat(vec![Doc::Line, build_doc(&right, src)])),
+            ]))
+        }
+        "unary_expression" => {
+            let op = node.child_by_field_name("operator").unwrap();
+            let arg = node.child_by_field_name("argument").unwrap();
+            let op = node_text(&op, src);
+            let sep = if is_word(op) { " " } else { "" };
+            concat(vec![text(op), text(sep), build_doc(&arg, src)])
+        }
+        "jsx_element" => build_jsx_element(node, src),
+        "jsx_opening_element" => build_jsx_opening_element(node, src, false),
+        "jsx_self_closing_element" => build_jsx_opening_element(node, src, true),
+        "jsx_attribute" | "jsx_expression" | "jsx_namespace_name" | "nested_identifier" => concat(
+            children(node)
+                .iter()
+                .map(|child| build_doc(child, src))
+                .collect(),
+        ),
+        _ => build_children(node, src),
+    }
+}
+
+/// Joins the children of `node` using `space_between` to decide where spaces
+/// go.  Line comments always end the line they're on.
+fn build_children(node: &Node, src: &str) -> Doc {
+    let mut docs: Vec<Doc> = vec![];
+    let mut prev: Option<Node> = None;
+
+    for child in children(node) {
+        if let Some(prev) = prev {
+            if is_line_comment(&prev, src) {
+                docs.push(Doc::HardLine);
+            } else if space_between(&prev, &child, node, src) {
+                docs.push(text(" "));
+            }
+        }
+        docs.push(build_doc(&child, src));
+        prev = Some(child);
+    }
+
+    if let Some(prev) = prev {
+        if is_line_comment(&prev, src) {
+            docs.push(Doc::HardLine);
+        }
+    }
+
+    concat(docs)
+}
+
+/// Puts each statement on its own line, keeping at most one blank line
+/// between statements and keeping trailing comments on the same line as the
+/// statement before them.
+fn build_statements(nodes: &[Node], src: &str) -> Doc {
+    let mut docs: Vec<Doc> = vec![];
+    let mut prev: Option<&Node> = None;
+
+    for node in nodes {
+        if let Some(prev) = prev {
+            let prev_row = prev.end_position().row;
+            let next_row = node.start_position().row;
+            if node.kind() == "comment" && next_row == prev_row {
+                docs.push(text(" "));
+            } else {
+                if next_row > prev_row + 1 {
+                    docs.push(Doc::BlankLine);
+                }
+                docs.push(Doc::HardLine);
+            }
+        }
+        docs.push(build_doc(node, src));
+        prev = Some(node);
+    }
+
+    match docs.is_empty() {
+        true => Doc::Nil,
+        false => concat(docs),
+    }
+}
+
+fn build_block(node: &Node, src: &str) -> Doc {
+    let inner: Vec<Node> = children(node)
+        .into_iter()
+        .filter(|child| !matches!(child.kind(), "{" | "}"))
+        .collect();
+
+    match build_statements(&inner, src) {
+        Doc::Nil => text("{}"),
+        doc => concat(vec![
+            text("{"),
+            indent(concat(vec![Doc::HardLine, doc])),
+            Doc::HardLine,
+            text("}"),
+        ]),
+    }
+}
+
+/// Formats comma separated lists such as arguments, arrays and objects.  The
+/// list is printed on one line if it fits, otherwise each item gets its own
+/// line and a trailing comma is added where the syntax allows it.
+fn build_delimited(node: &Node, delims: &Delims, src: &str) -> Doc {
+    let all_children = children(node);
+    let items: Vec<&Node> = all_children
+        .iter()
+        .enumerate()
+        .filter(|(i, child)| {
+            let is_open = *i == 0 && child.kind() == delims.open;
+            let is_close = *i == all_children.len() - 1 && child.kind() == delims.close;
+            !is_open && !is_close && !matches!(child.kind(), "," | ";")
+        })
+        .map(|(_, child)| child)
+        .collect();
+
+    if items.is_empty() {
+        return text(format!("{}{}", delims.open, delims.close));
+    }
+
+    let line = match (delims.force_break, delims.padded) {
+        (true, _) => Doc::HardLine,
+        (false, true) => Doc::Line,
+        (false, false) => Doc::SoftLine,
+    };
+    let sep = match delims.force_break {
+        true => Doc::HardLine,
+        false => Doc::Line,
+    };
+
+    // Rest elements can't be followed by a comma.
+    let last_item = items.iter().rev().find(|item| item.kind() != "comment");
+    let trailing_comma = match last_item {
+        Some(item) => !item.kind().contains("rest"),
+        None => false,
+    };
+
+    let mut docs: Vec<Doc> = vec![line.to_owned()];
+    let mut prev: Option<&Node> = None;
+    for item in &items {
+        if let Some(prev) = prev {
+            let same_row = item.start_position().row == prev.end_position().row;
+            if item.kind() == "comment" && same_row {
+                docs.push(text(" "));
+            } else if is_line_comment(prev, src) {
+                docs.push(Doc::HardLine);
+            } else {
+                docs.push(sep.to_owned());
+            }
+        }
+        docs.push(build_doc(item, src));
+        if item.kind() != "comment" {
+            if Some(item) == last_item {
+                if trailing_comma {
+                    docs.push(Doc::IfBreak(String::from(",")));
+                }
+            } else {
+                docs.push(text(","));
+            }
+        }
+        prev = Some(item);
+    }
+    if let Some(prev) = prev {
+        if is_line_comment(prev, src) {
+            docs.push(Doc::HardLine);
+        }
+    }
+
+    group(concat(vec![
+        text(delims.open),
+        indent(concat(docs)),
+        line,
+        text(delims.close),
+    ]))
+}
+
+fn build_jsx_opening_element(node: &Node, src: &str, self_closing: bool) -> Doc {
+    let mut name: Option<Doc> = None;
+    let mut attrs: Vec<Doc> = vec![];
+    for child in children(node) {
+        match child.kind() {
+            "<" | ">" | "/" | "/>" => (),
+            "jsx_attribute" | "jsx_expression" | "comment" => {
+                attrs.push(concat(vec![Doc::Line, build_doc(&child, src)]))
+            }
+            _ => name = Some(build_doc(&child, src)),
+        }
+    }
+
+    let close = match self_closing {
+        true => concat(vec![Doc::Line, text("/>")]),
+        false => concat(vec![Doc::SoftLine, text(">")]),
+    };
+
+    group(concat(vec![
+        text("<"),
+        name.unwrap_or(Doc::Nil),
+        indent(concat(attrs)),
+        close,
+    ]))
+}
+
+fn build_jsx_element(node: &Node, src: &str) -> Doc {
+    let all_children = children(node);
+    let (opening, rest) = all_children.split_first().unwrap();
+    let (closing, jsx_children) = rest.split_last().unwrap();
+
+    // Whitespace between JSX children is only insignificant when it contains
+    // a newline.  If there's any other text we keep the children as is so
+    // that the rendered output doesn't change.
+    let is_free_layout = jsx_children.iter().all(|child| {
+        if child.kind() != "jsx_text" {
+            return true;
+        }
+        let value = node_text(child, src);
+        value.trim().is_empty() && value.contains('\n')
+    });
+
+    let children_doc = if is_free_layout {
+        let docs: Vec<Doc> = jsx_children
+            .iter()
+            .filter(|child| child.kind() != "jsx_text")
+            .flat_map(|child| vec![Doc::SoftLine, build_doc(child, src)])
+            .collect();
+        if docs.is_empty() {
+            Doc::Nil
+        } else {
+            concat(vec![indent(concat(docs)), Doc::SoftLine])
+        }
+    } else {
+        let start = jsx_children.first().unwrap().start_byte();
+        let end = jsx_children.last().unwrap().end_byte();
+        text(&src[start..end])
+    };
+
+    group(concat(vec![
+        build_doc(opening, src),
+        children_doc,
+        build_doc(closing, src),
+    ]))
+}
@@ -0,0 +1,223 @@
+/// A document describing the layout of formatted code.  `Group`s are printed
+/// on a single line if they fit within the line width, otherwise all of the
+/// `Line`s and `SoftLine`s directly inside of them become newlines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Doc {
+    Nil,
+    Text(String),
+    /// A space when flat, a newline when broken
+    Line,
+    /// Nothing when flat, a newline when broken
+    SoftLine,
+    /// Always a newline, forces all enclosing groups to break
+    HardLine,
+    /// A newline without indentation, used for blank lines between statements
+    BlankLine,
+    /// Text that's only printed when the enclosing group is broken
+    IfBreak(String),
+    Indent(Box<Doc>),
+    Group(Box<Doc>),
+    Concat(Vec<Doc>),
+}
+
+pub fn text<S: Into<String>>(s: S) -> Doc {
+    Doc::Text(s.into())
+}
+
+pub fn indent(doc: Doc) -> Doc {
+    Doc::Indent(Box::from(doc))
+}
+
+pub fn group(doc: Doc) -> Doc {
+    Doc::Group(Box::from(doc))
+}
+
+pub fn concat(docs: Vec<Doc>) -> Doc {
+    Doc::Concat(docs)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// Prints `doc` trying to keep lines within `width` columns, using
+/// `indent_width` spaces for each level of indentation.
+pub fn print(doc: &Doc, width: usize, indent_width: usize) -> String {
+    let mut out = String::new();
+    let mut column: usize = 0;
+    let mut stack: Vec<(usize, Mode, &Doc)> = vec![(0, Mode::Break, doc)];
+
+    while let Some((level, mode, doc)) = stack.pop() {
+        match doc {
+            Doc::Nil => (),
+            Doc::Text(s) => {
+                out.push_str(s);
+                column += s.chars().count();
+            }
+            Doc::Line | Doc::SoftLine if mode == Mode::Flat => {
+                if let Doc::Line = doc {
+                    out.push(' ');
+                    column += 1;
+                }
+            }
+            Doc::Line | Doc::SoftLine | Doc::HardLine => {
+                trim_trailing_spaces(&mut out);
+                out.push('\n');
+                out.push_str(&" ".repeat(level));
+                column = level;
+            }
+            Doc::BlankLine => {
+                trim_trailing_spaces(&mut out);
+                out.push('\n');
+            }
+            Doc::IfBreak(s) => {
+                if mode == Mode::Break {
+                    out.push_str(s);
+                    column += s.chars().count();
+                }
+            }
+            Doc::Indent(doc) => stack.push((level + indent_width, mode, doc)),
+            Doc::Group(doc) => {
+                let mode = if mode == Mode::Flat || fits(doc, &stack, width.saturating_sub(column))
+                {
+                    Mode::Flat
+                } else {
+                    Mode::Break
+                };
+                stack.push((level, mode, doc));
+            }
+            Doc::Concat(docs) => {
+                for doc in docs.iter().rev() {
+                    stack.push((level, mode, doc));
+                }
+            }
+        }
+    }
+
+    trim_trailing_spaces(&mut out);
+    out
+}
+
+fn trim_trailing_spaces(out: &mut String) {
+    let len = out.trim_end_matches(' ').len();
+    out.truncate(len);
+}
+
+/// Returns true if `doc` printed flat, along with everything up to the next
+/// possible line break in `rest`, fits in `remaining` columns.
+fn fits(doc: &Doc, rest: &[(usize, Mode, &Doc)], remaining: usize) -> bool {
+    let mut remaining = remaining as isize;
+    let mut stack: Vec<(Mode, &Doc)> = vec![(Mode::Flat, doc)];
+    let mut rest = rest.iter().rev();
+
+    loop {
+        let (mode, doc) = match stack.pop() {
+            Some(item) => item,
+            None => match rest.next() {
+                Some((_, mode, doc)) => (*mode, *doc),
+                None => return true,
+            },
+        };
+
+        match doc {
+            Doc::Nil => (),
+            Doc::Text(s) => remaining -= s.chars().count() as isize,
+            Doc::Line | Doc::SoftLine => match mode {
+                Mode::Flat => {
+                    if let Doc::Line = doc {
+                        remaining -= 1;
+                    }
+                }
+                Mode::Break => return true,
+            },
+            Doc::HardLine | Doc::BlankLine => return mode == Mode::Break,
+            Doc::IfBreak(s) => {
+                if mode == Mode::Break {
+                    remaining -= s.chars().count() as isize;
+                }
+            }
+            Doc::Indent(doc) | Doc::Group(doc) => stack.push((mode, doc)),
+            Doc::Concat(docs) => {
+                for doc in docs.iter().rev() {
+                    stack.push((mode, doc));
+                }
+            }
+        }
+
+        if remaining < 0 {
+            return false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(items: &[&str]) -> Doc {
+        let mut elems = vec![Doc::SoftLine];
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                elems.push(text(","));
+                elems.push(Doc::Line);
+            }
+            elems.push(text(*item));
+        }
+        elems.push(Doc::IfBreak(String::from(",")));
+
+        group(concat(vec![
+            text("["),
+            indent(concat(elems)),
+            Doc::SoftLine,
+            text("]"),
+        ]))
+    }
+
+    #[test]
+    fn group_fits_on_one_line() {
+        let doc = list(&["a", "b", "c"]);
+        assert_eq!(print(&doc, 80, 2), "[a, b, c]");
+    }
+
+    #[test]
+    fn group_breaks_when_too_wide() {
+        let doc = list(&["aaaa", "bbbb", "cccc"]);
+        assert_eq!(print(&doc, 10, 2), "[\n  aaaa,\n  bbbb,\n  cccc,\n]");
+    }
+
+    #[test]
+    fn hard_line_breaks_enclosing_group() {
+        let doc = group(concat(vec![
+            text("{"),
+            indent(concat(vec![
+                Doc::Line,
+                text("// comment"),
+                Doc::HardLine,
+                text("a"),
+            ])),
+            Doc::Line,
+            text("}"),
+        ]));
+        assert_eq!(print(&doc, 80, 2), "{\n  // comment\n  a\n}");
+    }
+
+    #[test]
+    fn nested_groups_break_outside_in() {
+        let inner = list(&["1", "2"]);
+        let doc = group(concat(vec![
+            text("f("),
+            indent(concat(vec![
+                Doc::SoftLine,
+                text("xxxxxxxx"),
+                text(","),
+                Doc::Line,
+                inner,
+            ])),
+            Doc::SoftLine,
+            text(")"),
+        ]));
+        assert_eq!(print(&doc, 16, 2), "f(\n  xxxxxxxx,\n  [1, 2]\n)");
+    }
+}
@@ -0,0 +1,13 @@
+use error_stack::Context;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct FormatError;
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "FormatError")
+    }
+}
+
+impl Context for FormatError {}
@@ -0,0 +1,126 @@
+use error_stack::{Report, Result};
+
+mod cst;
+mod doc;
+mod format_error;
+
+pub use format_error::FormatError;
+
+#[derive(Clone, Debug)]
+pub struct FormatOptions {
+    /// The maximum line width that the formatter tries to stay within
+    pub width: usize,
+    /// The number of spaces used for each level of indentation
+    pub indent_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            width: 80,
+            indent_width: 2,
+        }
+    }
+}
+
+/// Formats Crochet source code.  Comments are preserved.  Code containing
+/// syntax errors is returned as an error instead of being formatted.
+pub fn format(src: &str, options: &FormatOptions) -> Result<String, FormatError> {
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(tree_sitter_crochet::language())
+        .expect("Error loading crochet language");
+
+    let tree = parser.parse(src, None).unwrap();
+    let root = tree.root_node();
+
+    if root.has_error() {
+        return Err(Report::new(FormatError)
+            .attach_printable("can't format code that contains syntax errors"));
+    }
+
+    let doc = cst::build_doc(&root, src);
+
+    Ok(doc::print(&doc, options.width, options.indent_width))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn fmt(src: &str) -> String {
+        format(src, &FormatOptions::default()).unwrap()
+    }
+
+    fn fmt_with_width(src: &str, width: usize) -> String {
+        let options = FormatOptions {
+            width,
+            ..FormatOptions::default()
+        };
+        format(src, &options).unwrap()
+    }
+
+    #[test]
+    fn normalizes_spacing_in_declarations() {
+        assert_eq!(fmt("let   x:number=5;"), "let x: number = 5;\n");
+        assert_eq!(
+            fmt("type Point={x:number,y:number};"),
+            "type Point = { x: number, y: number };\n"
+        );
+    }
+
+    #[test]
+    fn keeps_comments() {
+        let src = "// leading\nlet x = 5; // trailing\n\n\n/* block */\nlet y = x;\n";
+        assert_eq!(
+            fmt(src),
+            "// leading\nlet x = 5; // trailing\n\n/* block */\nlet y = x;\n"
+        );
+    }
+
+    #[test]
+    fn breaks_long_lambda_params() {
+        let src =
+            "let add = (first: number, second: number, third: number) => first + second + third;";
+        assert_eq!(
+            fmt_with_width(src, 40),
+            "let add = (\n  first: number,\n  second: number,\n  third: number,\n) => first + second + third;\n"
+        );
+    }
+
+    #[test]
+    fn match_arms_on_separate_lines() {
+        let src = "let x = match (y) { 0 -> \"zero\", n if (n > 0) -> \"pos\", _ -> \"neg\" };";
+        assert_eq!(
+            fmt(src),
+            "let x = match (y) {\n  0 -> \"zero\",\n  n if (n > 0) -> \"pos\",\n  _ -> \"neg\",\n};\n"
+        );
+    }
+
+    #[test]
+    fn if_else_blocks() {
+        let src = "let x = if (a) {5} else {10};";
+        assert_eq!(fmt(src), "let x = if (a) {\n  5\n} else {\n  10\n};\n");
+    }
+
+    #[test]
+    fn jsx_children_keep_significant_whitespace() {
+        assert_eq!(
+            fmt("let e = <div   id=\"a\">Hello, {name}</div>;"),
+            "let e = <div id=\"a\">Hello, {name}</div>;\n"
+        );
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        let src = "let f = (a, b) => {\n  let c = a * b; // product\n\n  c + 1\n};\n";
+        assert_eq!(fmt(src), src);
+        assert_eq!(fmt(&fmt(src)), src);
+    }
+
+    #[test]
+    fn syntax_errors_are_reported() {
+        assert!(format("let = ;", &FormatOptions::default()).is_err());
+    }
+}
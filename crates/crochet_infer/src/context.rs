@@ -1,11 +1,15 @@
 use crochet_ast::types::*;
+use crochet_ast::values::Span;
 use error_stack::{Report, Result};
-use std::cell::Cell;
-use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
 use crate::substitutable::*;
-use crate::type_error::TypeError;
+use crate::trace::Trace;
+use crate::type_error::{TypeError, TypeErrorKind};
 use crate::util::get_type_params;
+use crate::warning::{Warning, WarningKind};
 
 // NOTE: This is the same as the Assump type in assump.rs
 pub type Env = HashMap<String, Type>;
@@ -19,6 +23,9 @@ pub struct State {
 pub struct Binding {
     pub mutable: bool,
     pub t: Type,
+    /// Where the binding was introduced, `None` for bindings that don't come
+    /// from the source, e.g. those from lib.d.ts.
+    pub span: Option<Span>,
 }
 
 // TODO: each scope has separate namespaces for values and types.  Namespace module
@@ -28,13 +35,31 @@ pub struct Scope {
     pub namespaces: HashMap<String, Box<Scope>>,
     pub values: HashMap<String, Binding>,
     pub types: Env,
+    /// The classes declared in this scope, mapped to the class they extend.
+    /// The instance type of each class is in `types`.
+    pub classes: HashMap<String, Option<String>>,
+    /// The traits that have been implemented for each class or struct
+    /// declared or imported in this scope.
+    pub impls: HashMap<String, HashSet<String>>,
     pub is_async: bool,
+    /// Whether `break` and `continue` are allowed in this scope.
+    pub in_loop: bool,
+    /// The names of values in this scope that have been looked up.
+    pub used: RefCell<HashSet<String>>,
 }
 
 #[derive(Clone, Debug)]
 pub struct Context {
     pub scopes: Vec<Scope>,
     pub state: State,
+    /// Non-fatal problems found while inferring the program.
+    pub warnings: Vec<Warning>,
+    /// Only recorded if tracing has been enabled with `enable_trace`.
+    pub trace: Option<RefCell<Trace>>,
+    /// The exports of the modules this one imports, keyed by the specifier
+    /// used in the `import` statement.  It's an error if a module can't be
+    /// imported, e.g. because it's part of an import cycle.
+    pub modules: HashMap<String, std::result::Result<Rc<Scope>, TypeErrorKind>>,
 }
 
 impl Default for Context {
@@ -44,6 +69,9 @@ impl Default for Context {
             state: State {
                 count: Cell::from(0),
             },
+            warnings: vec![],
+            trace: None,
+            modules: HashMap::default(),
         }
     }
 }
@@ -56,8 +84,70 @@ impl Context {
         });
     }
 
+    /// Pushes a scope for a block, e.g. the body of a `let` or `do`.  Unlike
+    /// function scopes, it stays inside of any loop that encloses it.
+    pub fn push_block_scope(&mut self) {
+        self.scopes.push(Scope {
+            is_async: self.is_async(),
+            in_loop: self.in_loop(),
+            ..Scope::default()
+        });
+    }
+
+    /// Pushes a scope for the body of a loop.
+    pub fn push_loop_scope(&mut self) {
+        self.scopes.push(Scope {
+            is_async: self.is_async(),
+            in_loop: true,
+            ..Scope::default()
+        });
+    }
+
     pub fn pop_scope(&mut self) {
-        self.scopes.pop();
+        if let Some(scope) = self.scopes.pop() {
+            let used = scope.used.borrow();
+            let mut unused: Vec<_> = scope
+                .values
+                .iter()
+                .filter(|(name, _)| !name.starts_with('_') && !used.contains(*name))
+                .filter_map(|(name, b)| b.span.as_ref().map(|span| (name, span)))
+                .collect();
+            // HashMap iteration order isn't stable so we sort by position.
+            unused.sort_by_key(|(_, span)| span.start);
+            for (name, span) in unused {
+                self.warnings.push(Warning::new(
+                    WarningKind::UnusedBinding {
+                        name: name.to_owned(),
+                    },
+                    span,
+                ));
+            }
+        }
+    }
+
+    pub fn warn(&mut self, warning: Warning) {
+        self.warnings.push(warning);
+    }
+
+    /// Starts recording unifications, instantiations, and generalizations
+    /// made while inferring types, see `take_trace`.
+    pub fn enable_trace(&mut self) {
+        self.trace = Some(RefCell::default());
+    }
+
+    /// Stops tracing and returns everything that was recorded.
+    pub fn take_trace(&mut self) -> Option<Trace> {
+        self.trace.take().map(RefCell::into_inner)
+    }
+
+    /// Calls `f` with the trace if tracing is enabled.
+    pub fn with_trace<R>(&self, f: impl FnOnce(&mut Trace) -> R) -> Option<R> {
+        self.trace.as_ref().map(|trace| f(&mut trace.borrow_mut()))
+    }
+
+    /// Removes and returns all of the warnings collected so far.
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
     }
 
     pub fn is_async(&self) -> bool {
@@ -65,6 +155,11 @@ impl Context {
         current_scope.is_async
     }
 
+    pub fn in_loop(&self) -> bool {
+        let current_scope = self.scopes.last().unwrap();
+        current_scope.in_loop
+    }
+
     pub fn apply(&mut self, s: &Subst) {
         let current_scope = self.scopes.last_mut().unwrap();
         for (k, b) in current_scope.values.clone() {
@@ -74,6 +169,7 @@ impl Context {
                 Binding {
                     mutable: b.mutable,
                     t: b.t.apply(s),
+                    span: b.span,
                 },
             );
         }
@@ -87,12 +183,36 @@ impl Context {
 
     pub fn insert_value(&mut self, name: String, t: Type) {
         let current_scope = self.scopes.last_mut().unwrap();
-        current_scope
-            .values
-            .insert(name, Binding { mutable: false, t });
+        current_scope.values.insert(
+            name,
+            Binding {
+                mutable: false,
+                t,
+                span: None,
+            },
+        );
     }
 
     pub fn insert_binding(&mut self, name: String, b: Binding) {
+        if let Some(span) = &b.span {
+            let previous = self
+                .scopes
+                .iter()
+                .rev()
+                .find_map(|scope| scope.values.get(&name))
+                .and_then(|prev| prev.span.to_owned());
+            if let Some(previous) = previous {
+                if !name.starts_with('_') {
+                    self.warnings.push(Warning::new(
+                        WarningKind::ShadowedBinding {
+                            name: name.to_owned(),
+                            previous,
+                        },
+                        span,
+                    ));
+                }
+            }
+        }
         let current_scope = self.scopes.last_mut().unwrap();
         current_scope.values.insert(name, b);
     }
@@ -107,31 +227,149 @@ impl Context {
         current_scope.namespaces.insert(name, Box::from(namespace));
     }
 
+    pub fn insert_class(&mut self, name: String, super_class: Option<String>) {
+        let current_scope = self.scopes.last_mut().unwrap();
+        current_scope.classes.insert(name, super_class);
+    }
+
+    /// Returns the name of the class that `name` extends, `None` if `name`
+    /// isn't a class.
+    pub fn lookup_class(&self, name: &str) -> Option<Option<String>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.classes.get(name))
+            .cloned()
+    }
+
+    pub fn is_class(&self, name: &str) -> bool {
+        self.lookup_class(name).is_some()
+    }
+
+    /// Whether instances of the class `sub` can be used where instances of
+    /// the class `sup` are expected, i.e. `sub` is `sup` or extends it.
+    pub fn is_subclass(&self, sub: &str, sup: &str) -> bool {
+        let mut name = Some(sub.to_owned());
+        while let Some(current) = name {
+            if current == sup {
+                return true;
+            }
+            name = self.lookup_class(&current).flatten();
+        }
+        false
+    }
+
+    pub fn insert_impl(&mut self, type_name: String, trait_name: String) {
+        let current_scope = self.scopes.last_mut().unwrap();
+        current_scope
+            .impls
+            .entry(type_name)
+            .or_default()
+            .insert(trait_name);
+    }
+
+    /// Whether there's an `impl` of the trait `trait_name` for the class or
+    /// struct called `type_name`.
+    pub fn implements(&self, type_name: &str, trait_name: &str) -> bool {
+        self.scopes
+            .iter()
+            .any(|scope| match scope.impls.get(type_name) {
+                Some(traits) => traits.contains(trait_name),
+                None => false,
+            })
+    }
+
+    /// Returns the trait called `name` if there is one.
+    pub fn lookup_trait(&self, name: &str) -> Option<TTrait> {
+        match self.lookup_type(name) {
+            Ok(Type {
+                kind: TypeKind::Trait(tr),
+                ..
+            }) => Some(tr),
+            _ => None,
+        }
+    }
+
+    /// Returns the type of the method called `name` from one of the traits
+    /// that have been implemented for the class or struct `type_name`.
+    pub fn lookup_trait_method(&self, type_name: &str, name: &str) -> Option<Type> {
+        self.scopes
+            .iter()
+            .rev()
+            .filter_map(|scope| scope.impls.get(type_name))
+            .flatten()
+            .filter_map(|trait_name| self.lookup_trait(trait_name))
+            .find_map(|tr| tr.methods.into_iter().find(|method| method.name == name))
+            .map(|method| method.t)
+    }
+
+    /// Makes the `exports` of a module available to `import` statements that
+    /// use `specifier`.
+    pub fn insert_module(&mut self, specifier: String, exports: Scope) {
+        self.modules.insert(specifier, Ok(Rc::from(exports)));
+    }
+
+    /// Makes `import` statements that use `specifier` fail with `kind`.
+    pub fn insert_module_error(&mut self, specifier: String, kind: TypeErrorKind) {
+        self.modules.insert(specifier, Err(kind));
+    }
+
+    pub fn lookup_module(&self, specifier: &str) -> Result<Rc<Scope>, TypeError> {
+        match self.modules.get(specifier) {
+            Some(Ok(exports)) => Ok(Rc::clone(exports)),
+            Some(Err(kind)) => Err(Report::new(TypeError::new(kind.to_owned()))),
+            None => Err(Report::new(TypeError::new(
+                TypeErrorKind::UnresolvedModule {
+                    specifier: specifier.to_owned(),
+                },
+            ))),
+        }
+    }
+
+    /// Looks up a value that's being read, this is what keeps the binding from
+    /// being reported as unused.
     pub fn lookup_value_and_instantiate(&self, name: &str) -> Result<Type, TypeError> {
         for scope in self.scopes.iter().rev() {
             if let Some(b) = scope.values.get(name) {
+                scope.used.borrow_mut().insert(name.to_owned());
                 return Ok(self.instantiate(&b.t));
             }
         }
-        Err(Report::new(TypeError).attach_printable(format!("Can't find value: {name}")))
+        Err(Report::new(TypeError::new(
+            TypeErrorKind::UnboundIdentifier {
+                name: name.to_owned(),
+            },
+        )))
     }
 
+    /// Unlike `lookup_value_and_instantiate`, this doesn't count as a use of
+    /// the binding.
     pub fn lookup_value(&self, name: &str) -> Result<Type, TypeError> {
         for scope in self.scopes.iter().rev() {
             if let Some(b) = scope.values.get(name) {
                 return Ok(b.t.to_owned());
             }
         }
-        Err(Report::new(TypeError).attach_printable(format!("Can't find value: {name}")))
+        Err(Report::new(TypeError::new(
+            TypeErrorKind::UnboundIdentifier {
+                name: name.to_owned(),
+            },
+        )))
     }
 
+    /// Unlike `lookup_value_and_instantiate`, this doesn't count as a use of
+    /// the binding, e.g. assigning to a binding doesn't use it.
     pub fn lookup_binding(&self, name: &str) -> Result<Binding, TypeError> {
         for scope in self.scopes.iter().rev() {
             if let Some(b) = scope.values.get(name) {
                 return Ok(b.to_owned());
             }
         }
-        Err(Report::new(TypeError).attach_printable(format!("Can't find value: {name}")))
+        Err(Report::new(TypeError::new(
+            TypeErrorKind::UnboundIdentifier {
+                name: name.to_owned(),
+            },
+        )))
     }
 
     pub fn lookup_type_and_instantiate(&self, name: &str) -> Result<Type, TypeError> {
@@ -140,7 +378,9 @@ impl Context {
                 return Ok(self.instantiate(t));
             }
         }
-        Err(Report::new(TypeError).attach_printable(format!("Can't find type: {name}")))
+        Err(Report::new(TypeError::new(TypeErrorKind::UnboundType {
+            name: name.to_owned(),
+        })))
     }
 
     pub fn lookup_type(&self, name: &str) -> Result<Type, TypeError> {
@@ -149,7 +389,9 @@ impl Context {
                 return Ok(t.to_owned());
             }
         }
-        Err(Report::new(TypeError).attach_printable(format!("Can't find type: {name}")))
+        Err(Report::new(TypeError::new(TypeErrorKind::UnboundType {
+            name: name.to_owned(),
+        })))
     }
 
     pub fn lookup_namespace(&self, name: &str) -> Result<Box<Scope>, TypeError> {
@@ -158,7 +400,11 @@ impl Context {
                 return Ok(namespace.to_owned());
             }
         }
-        Err(Report::new(TypeError).attach_printable(format!("Can't find namespace: {name}")))
+        Err(Report::new(TypeError::new(
+            TypeErrorKind::UnboundNamespace {
+                name: name.to_owned(),
+            },
+        )))
     }
 
     pub fn lookup_ref_and_instantiate(&self, alias: &TRef) -> Result<Type, TypeError> {
@@ -171,21 +417,29 @@ impl Context {
                 // from the alias type.
                 let ids = type_params.iter().map(|tv| tv.id.to_owned());
                 let subs: Subst = match &alias.type_args {
-                    Some(type_params) => {
-                        if type_params.len() != type_params.len() {
-                            println!("type = {t}");
-                            println!("type_params = {type_params:#?}");
-                            return Err(Report::new(TypeError).attach_printable(
+                    Some(type_args) => {
+                        if type_args.len() != type_params.len() {
+                            return Err(Report::new(TypeError::new(
+                                TypeErrorKind::ArityMismatch {
+                                    expected: type_params.len(),
+                                    actual: type_args.len(),
+                                },
+                            ))
+                            .attach_printable(
                                 "mismatch between the number of qualifiers and type params",
                             ));
                         }
-                        ids.zip(type_params.iter().cloned()).collect()
+                        ids.zip(type_args.iter().cloned()).collect()
                     }
                     None => {
                         if !type_params.is_empty() {
-                            println!("type = {t}");
-                            println!("no type params");
-                            return Err(Report::new(TypeError).attach_printable(
+                            return Err(Report::new(TypeError::new(
+                                TypeErrorKind::ArityMismatch {
+                                    expected: type_params.len(),
+                                    actual: 0,
+                                },
+                            ))
+                            .attach_printable(
                                 "mismatch between the number of qualifiers and type params",
                             ));
                         }
@@ -202,12 +456,17 @@ impl Context {
                 return Ok(t.apply(&subs));
             }
         }
-        Err(Report::new(TypeError).attach_printable(format!("Can't find type: {name}")))
+        Err(Report::new(TypeError::new(TypeErrorKind::UnboundType {
+            name: name.to_owned(),
+        })))
     }
 
     pub fn instantiate(&self, t: &Type) -> Type {
         match &t.kind {
-            TypeKind::Generic(TGeneric { t, type_params }) => {
+            TypeKind::Generic(TGeneric {
+                t: inner,
+                type_params,
+            }) => {
                 let ids = type_params.iter().map(|tv| tv.id.to_owned());
                 let fresh_params = type_params.iter().map(|tp| {
                     Type::from(TypeKind::Var(TVar {
@@ -217,7 +476,9 @@ impl Context {
                 });
                 let subs: Subst = ids.zip(fresh_params).collect();
 
-                t.apply(&subs)
+                let result = inner.apply(&subs);
+                self.with_trace(|trace| trace.instantiate(t, &result));
+                result
             }
             _ => t.to_owned(),
         }
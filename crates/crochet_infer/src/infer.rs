@@ -1,38 +1,27 @@
-use crochet_ast::types::{TObjElem, TObject, TProp, Type, TypeKind};
+use crochet_ast::types::{TObject, Type, TypeKind};
 use crochet_ast::values::*;
 use error_stack::{Report, Result};
 
 use crate::context::Context;
+use crate::infer_class::infer_class;
+use crate::infer_enum::infer_enum;
 use crate::infer_expr::infer_expr as infer_expr_rec;
 use crate::infer_pattern::*;
+use crate::infer_struct::infer_struct;
+use crate::infer_trait::{infer_impl, infer_trait};
 use crate::infer_type_ann::*;
-use crate::type_error::TypeError;
+use crate::module::{check_reexport, infer_import};
+use crate::type_error::{TypeError, TypeErrorKind};
 use crate::update::*;
 use crate::util::*;
 
 pub fn infer_prog(prog: &mut Program, ctx: &mut Context) -> Result<Context, TypeError> {
-    // TODO: replace with Class type once it exists
-    // We use {_name: "Promise"} to differentiate it from other
-    // object types.
-    let elems = vec![TObjElem::Prop(TProp {
-        name: String::from("_name"),
-        optional: false,
-        mutable: false,
-        t: Type::from(Lit::str(String::from("Promise"), 0..0)),
-    })];
-    let promise_type = Type::from(TypeKind::Object(TObject { elems }));
-    ctx.insert_type(String::from("Promise"), promise_type);
-    // TODO: replace with Class type once it exists
-    // We use {_name: "JSXElement"} to differentiate it from other
-    // object types.
-    let elems = vec![TObjElem::Prop(TProp {
-        name: String::from("_name"),
-        optional: false,
-        mutable: false,
-        t: Type::from(Lit::str(String::from("JSXElement"), 0..0)),
-    })];
-    let jsx_element_type = Type::from(TypeKind::Object(TObject { elems }));
-    ctx.insert_type(String::from("JSXElement"), jsx_element_type);
+    // These are classes so that they're only compatible with themselves.
+    for name in ["Promise", "JSXElement"] {
+        let instance_type = Type::from(TypeKind::Object(TObject { elems: vec![] }));
+        ctx.insert_type(String::from(name), instance_type);
+        ctx.insert_class(String::from(name), None);
+    }
 
     // We push a scope here so that it's easy to differentiate globals from
     // module definitions.
@@ -42,97 +31,39 @@ pub fn infer_prog(prog: &mut Program, ctx: &mut Context) -> Result<Context, Type
 
     // TODO: figure out how report multiple errors
     for stmt in &mut prog.body {
-        match stmt {
-            Statement::VarDecl {
-                declare,
-                init,
-                pattern,
-                type_ann,
-                ..
-            } => {
-                match declare {
-                    true => {
-                        match &mut pattern.kind {
-                            PatternKind::Ident(BindingIdent {
-                                name,
-                                mutable: _,
-                                span: _,
-                            }) => {
-                                match type_ann {
-                                    Some(type_ann) => {
-                                        match infer_type_ann(type_ann, ctx, &mut None) {
-                                            Ok((s, t)) => {
-                                                let t = close_over(&s, &t, ctx);
-                                                ctx.insert_value(name.to_owned(), t);
-
-                                                update_type_ann(type_ann, &s);
-                                                update_pattern(pattern, &s);
-                                            }
-                                            Err(report) => reports.push(report),
-                                        }
-                                    }
-                                    None => {
-                                        // A type annotation should always be provided when using `declare`
-                                        return Err(Report::new(TypeError).attach_printable(
-                                            "missing type annotation in declare statement",
-                                        ));
-                                    }
-                                }
-                            }
-                            _ => todo!(),
-                        }
-                    }
-                    false => {
-                        // An initial value should always be used when using a normal
-                        // `let` statement
-                        let init = init.as_mut().unwrap();
-
-                        match infer_pattern_and_init(
-                            pattern,
-                            type_ann,
-                            init,
-                            ctx,
-                            &PatternUsage::Assign,
-                        ) {
-                            Ok((pa, s)) => {
-                                // Inserts the new variables from infer_pattern() into the
-                                // current context.
-                                for (name, mut binding) in pa {
-                                    binding.t = close_over(&s, &binding.t, ctx);
-                                    ctx.insert_binding(name, binding);
-                                }
+        let span = stmt.span();
+        ctx.with_trace(|trace| trace.push_span(&span));
 
-                                update_expr(init, &s);
-                                update_pattern(pattern, &s);
-                            }
-                            Err(report) => reports.push(report),
-                        }
+        infer_statement(stmt, ctx, &mut reports)?;
+
+        ctx.with_trace(|trace| trace.pop_span());
+    }
+
+    // `export {foo};` is allowed to come before `foo` is declared so the
+    // names are only checked once all of the declarations have been inferred.
+    for stmt in &prog.body {
+        if let Statement::Export {
+            specifiers,
+            source: None,
+            ..
+        } = stmt
+        {
+            for specifier in specifiers {
+                if let ExportSpecifier::Named { span, local, .. } = specifier {
+                    // The name can refer to a value, a type, or both.
+                    if ctx.lookup_value(&local.name).is_err()
+                        && ctx.lookup_type(&local.name).is_err()
+                    {
+                        reports.push(Report::new(TypeError::with_span(
+                            TypeErrorKind::UnboundIdentifier {
+                                name: local.name.to_owned(),
+                            },
+                            span,
+                        )));
                     }
-                };
-            }
-            Statement::TypeDecl {
-                id: Ident { name, .. },
-                type_ann,
-                type_params,
-                ..
-            } => match infer_type_ann(type_ann, ctx, type_params) {
-                Ok((s, t)) => {
-                    let t = close_over(&s, &t, ctx);
-                    ctx.insert_type(name.to_owned(), t);
-
-                    update_type_ann(type_ann, &s);
-                }
-                Err(report) => reports.push(report),
-            },
-            Statement::Expr { expr, .. } => {
-                match infer_expr_rec(ctx, expr) {
-                    // We ignore the type that was inferred, we only care that
-                    // it succeeds since we aren't assigning it to variable.
-                    Ok((s, _)) => update_expr(expr, &s),
-                    Err(report) => reports.push(report),
                 }
             }
-        };
+        }
     }
 
     if reports.is_empty() {
@@ -151,6 +82,152 @@ pub fn infer_prog(prog: &mut Program, ctx: &mut Context) -> Result<Context, Type
     Err(report.unwrap())
 }
 
+fn infer_statement(
+    stmt: &mut Statement,
+    ctx: &mut Context,
+    reports: &mut Vec<Report<TypeError>>,
+) -> Result<(), TypeError> {
+    match stmt {
+        Statement::VarDecl {
+            declare,
+            init,
+            pattern,
+            type_ann,
+            ..
+        } => {
+            match declare {
+                true => {
+                    match &mut pattern.kind {
+                        PatternKind::Ident(BindingIdent {
+                            name,
+                            mutable: _,
+                            span: _,
+                        }) => {
+                            match type_ann {
+                                Some(type_ann) => match infer_type_ann(type_ann, ctx, &mut None) {
+                                    Ok((s, t)) => {
+                                        let t = close_over(&s, &t, ctx);
+                                        ctx.insert_value(name.to_owned(), t);
+
+                                        update_type_ann(type_ann, &s);
+                                        update_pattern(pattern, &s);
+                                    }
+                                    Err(report) => reports.push(report),
+                                },
+                                None => {
+                                    // A type annotation should always be provided when using `declare`
+                                    return Err(Report::new(TypeError::with_span(
+                                        TypeErrorKind::MissingTypeAnnotation {
+                                            name: name.to_owned(),
+                                        },
+                                        &pattern.span,
+                                    )));
+                                }
+                            }
+                        }
+                        _ => todo!(),
+                    }
+                }
+                false => {
+                    // An initial value should always be used when using a normal
+                    // `let` statement
+                    let init = init.as_mut().unwrap();
+
+                    match infer_pattern_and_init(
+                        pattern,
+                        type_ann,
+                        init,
+                        ctx,
+                        &PatternUsage::Assign,
+                    ) {
+                        Ok((pa, s)) => {
+                            // Inserts the new variables from infer_pattern() into the
+                            // current context.
+                            for (name, mut binding) in pa {
+                                binding.t = close_over(&s, &binding.t, ctx);
+                                ctx.insert_binding(name, binding);
+                            }
+
+                            update_expr(init, &s);
+                            update_pattern(pattern, &s);
+                        }
+                        Err(report) => reports.push(report),
+                    }
+                }
+            };
+        }
+        Statement::TypeDecl {
+            id: Ident { name, .. },
+            type_ann,
+            type_params,
+            ..
+        } => match infer_type_ann(type_ann, ctx, type_params) {
+            Ok((s, t)) => {
+                let t = close_over(&s, &t, ctx);
+                ctx.insert_type(name.to_owned(), t);
+
+                update_type_ann(type_ann, &s);
+            }
+            Err(report) => reports.push(report),
+        },
+        Statement::ClassDecl { class, .. } => {
+            if let Err(report) = infer_class(class, ctx) {
+                reports.push(report);
+            }
+        }
+        Statement::EnumDecl { r#enum, .. } => {
+            if let Err(report) = infer_enum(r#enum, ctx) {
+                reports.push(report);
+            }
+        }
+        Statement::StructDecl { r#struct, .. } => {
+            if let Err(report) = infer_struct(r#struct, ctx) {
+                reports.push(report);
+            }
+        }
+        Statement::TraitDecl { r#trait, .. } => {
+            if let Err(report) = infer_trait(r#trait, ctx) {
+                reports.push(report);
+            }
+        }
+        Statement::ImplDecl { r#impl, .. } => {
+            if let Err(report) = infer_impl(r#impl, ctx) {
+                reports.push(report);
+            }
+        }
+        Statement::Expr { expr, .. } => {
+            match infer_expr_rec(ctx, expr) {
+                // We ignore the type that was inferred, we only care that
+                // it succeeds since we aren't assigning it to variable.
+                Ok((s, _)) => update_expr(expr, &s),
+                Err(report) => reports.push(report),
+            }
+        }
+        Statement::ExportDecl { decl, .. } => infer_statement(decl, ctx, reports)?,
+        Statement::Import {
+            specifiers, source, ..
+        } => {
+            if let Err(report) = infer_import(specifiers, source, ctx) {
+                reports.push(report);
+            }
+        }
+        Statement::Export {
+            specifiers,
+            source: Some(source),
+            ..
+        } => {
+            if let Err(report) = check_reexport(specifiers, source, ctx) {
+                reports.push(report);
+            }
+        }
+        // Local exports are checked once all of the declarations have been
+        // inferred, see `infer_prog`.
+        Statement::Export { source: None, .. } => (),
+    }
+
+    Ok(())
+}
+
 pub fn infer_expr(ctx: &mut Context, expr: &mut Expr) -> Result<Type, TypeError> {
     let (s, t) = infer_expr_rec(ctx, expr)?;
     Ok(close_over(&s, &t, ctx))
@@ -0,0 +1,353 @@
+use error_stack::{Report, Result};
+use std::collections::HashMap;
+
+use crochet_ast::types::{
+    self as types, TCallable, TFnParam, TObjElem, TObject, TProp, Type, TypeKind,
+};
+use crochet_ast::values::*;
+
+use crate::assump::Assump;
+use crate::context::{Binding, Context};
+use crate::infer_expr::infer_expr;
+use crate::infer_fn_param::infer_fn_param;
+use crate::infer_type_ann::*;
+use crate::substitutable::{Subst, Substitutable};
+use crate::type_error::{TypeError, TypeErrorKind};
+use crate::unify::unify;
+use crate::update::*;
+use crate::util::*;
+
+/// Infers the types of a class' members.  The instance type is added to the
+/// context as a type with the class' name and its constructor is added as a
+/// value with the same name, its type is an object with a `new` signature
+/// and the class' static members.
+///
+/// Instances are typed nominally, see `unify`, so the instance type is only
+/// compatible with itself and the instance types of its subclasses.
+pub fn infer_class(class: &mut Class, ctx: &mut Context) -> Result<(), TypeError> {
+    let name = class.ident.name.to_owned();
+    let this_type = Type::from(TypeKind::Ref(types::TRef {
+        name: name.to_owned(),
+        type_args: None,
+    }));
+
+    let (mut instance, parent) = match &class.super_class {
+        Some(Ident { name, span }) => {
+            if !ctx.is_class(name) {
+                return Err(Report::new(TypeError::with_span(
+                    TypeErrorKind::NotAClass {
+                        name: name.to_owned(),
+                    },
+                    span,
+                )));
+            }
+            (
+                instance_props(name, ctx)?,
+                Some(parent_constructor(name, ctx)?),
+            )
+        }
+        None => (vec![], None),
+    };
+    let mut statics: Vec<TProp> = vec![];
+
+    // Every member gets a type before any of their bodies are inferred so
+    // that they can refer to each other using `this`.
+    let mut s = Subst::default();
+    for member in &mut class.body {
+        let (is_static, prop) = match member {
+            ClassMember::Constructor(_) => continue,
+            ClassMember::Method(ClassMethod { key, is_static, .. }) => (
+                *is_static,
+                TProp {
+                    name: key.name.to_owned(),
+                    optional: false,
+                    mutable: false,
+                    t: ctx.fresh_var(),
+                },
+            ),
+            ClassMember::Prop(ClassProp {
+                key,
+                type_ann,
+                is_static,
+                is_mutable,
+                is_optional,
+                ..
+            }) => {
+                let t = match type_ann {
+                    Some(type_ann) => {
+                        let (type_ann_s, t) = infer_type_ann(type_ann, ctx, &mut None)?;
+                        s = compose_subs(&type_ann_s, &s);
+                        t
+                    }
+                    None => ctx.fresh_var(),
+                };
+                (
+                    *is_static,
+                    TProp {
+                        name: key.name.to_owned(),
+                        optional: *is_optional,
+                        mutable: *is_mutable,
+                        t,
+                    },
+                )
+            }
+        };
+        let props = if is_static {
+            &mut statics
+        } else {
+            &mut instance
+        };
+        // Members override the ones with the same name in the super class.
+        props.retain(|p| p.name != prop.name);
+        props.push(prop);
+    }
+
+    let mut param_bindings = Assump::default();
+    let params: Vec<TFnParam> = match class.body.iter_mut().find_map(|member| match member {
+        ClassMember::Constructor(constructor) => Some(constructor),
+        _ => None,
+    }) {
+        Some(constructor) => {
+            let mut params: Vec<TFnParam> = vec![];
+            for param in &mut constructor.params {
+                let (ps, pa, t_param) = infer_fn_param(param, ctx, &HashMap::default())?;
+                s = compose_subs(&ps, &s);
+                param_bindings.extend(pa);
+                params.push(t_param);
+            }
+            params
+        }
+        // Subclasses without a constructor are constructed the same way
+        // as the class they extend.
+        None => match &parent {
+            Some(parent) => parent.params.to_owned(),
+            None => vec![],
+        },
+    };
+
+    ctx.insert_class(
+        name.to_owned(),
+        class.super_class.as_ref().map(|id| id.name.to_owned()),
+    );
+    ctx.insert_type(name.to_owned(), object_type(&instance, &s));
+    ctx.insert_binding(
+        name.to_owned(),
+        Binding {
+            mutable: false,
+            t: constructor_type(&params, &this_type, &statics, &s),
+            span: Some(class.ident.span.to_owned()),
+        },
+    );
+
+    for member in &mut class.body {
+        // Later members see what was inferred from earlier ones.
+        ctx.insert_type(name.to_owned(), object_type(&instance, &s));
+
+        match member {
+            ClassMember::Constructor(Constructor { body, .. }) => {
+                ctx.push_scope(false);
+                // Fields can be initialized by the constructor even if they
+                // aren't mutable.
+                let mutable_instance: Vec<TProp> = instance
+                    .iter()
+                    .map(|prop| TProp {
+                        mutable: true,
+                        ..prop.to_owned()
+                    })
+                    .collect();
+                ctx.insert_value(String::from("this"), object_type(&mutable_instance, &s));
+                if let Some(parent) = &parent {
+                    let super_t = Type::from(TypeKind::Lam(types::TLam {
+                        params: parent.params.to_owned(),
+                        ret: Box::from(Type::from(TypeKind::Keyword(types::TKeyword::Undefined))),
+                    }));
+                    ctx.insert_value(String::from("super"), super_t);
+                }
+                for (name, binding) in param_bindings.apply(&s) {
+                    ctx.insert_binding(name, binding);
+                }
+
+                let result = infer_expr(ctx, body);
+                ctx.pop_scope();
+                let (body_s, _) = result?;
+                s = compose_subs(&body_s, &s);
+            }
+            ClassMember::Method(ClassMethod {
+                span,
+                key,
+                lambda,
+                is_static,
+            }) => {
+                let mut expr = Expr {
+                    span: span.to_owned(),
+                    kind: ExprKind::Lambda(lambda.to_owned()),
+                    inferred_type: None,
+                };
+                let result =
+                    infer_member(ctx, &mut expr, *is_static, &this_type, &class.super_class);
+                if let ExprKind::Lambda(inferred) = expr.kind {
+                    *lambda = inferred;
+                }
+                let (method_s, method_t) = result?;
+                s = compose_subs(&method_s, &s);
+
+                let props = if *is_static { &statics } else { &instance };
+                let placeholder = find_prop(props, &key.name).t.apply(&s);
+                // The placeholder comes first since it may have been called
+                // by an earlier member.
+                let unify_s = unify(&placeholder, &method_t, ctx)?;
+                s = compose_subs(&unify_s, &s);
+            }
+            ClassMember::Prop(ClassProp {
+                key,
+                value: Some(value),
+                is_static,
+                ..
+            }) => {
+                let (value_s, value_t) =
+                    infer_member(ctx, value, *is_static, &this_type, &class.super_class)?;
+                s = compose_subs(&value_s, &s);
+
+                let props = if *is_static { &statics } else { &instance };
+                let declared = find_prop(props, &key.name).t.apply(&s);
+                let unify_s = unify(&value_t, &declared, ctx)?;
+                s = compose_subs(&unify_s, &s);
+            }
+            ClassMember::Prop(ClassProp { value: None, .. }) => (),
+        }
+    }
+
+    // Each member is generalized separately since the instance type itself
+    // can't have type params.
+    let instance: Vec<TProp> = instance
+        .iter()
+        .map(|prop| TProp {
+            t: close_over(&s, &prop.t, ctx),
+            ..prop.to_owned()
+        })
+        .collect();
+    ctx.insert_type(name.to_owned(), object_type(&instance, &Subst::default()));
+
+    let constructor_t = constructor_type(&params, &this_type, &statics, &s);
+    ctx.insert_binding(
+        name,
+        Binding {
+            mutable: false,
+            t: close_over(&Subst::default(), &constructor_t, ctx),
+            span: Some(class.ident.span.to_owned()),
+        },
+    );
+
+    for member in &mut class.body {
+        match member {
+            ClassMember::Constructor(Constructor { body, .. }) => update_expr(body, &s),
+            ClassMember::Method(ClassMethod { lambda, .. }) => update_expr(&mut lambda.body, &s),
+            ClassMember::Prop(ClassProp {
+                value, type_ann, ..
+            }) => {
+                if let Some(value) = value {
+                    update_expr(value, &s);
+                }
+                if let Some(type_ann) = type_ann {
+                    update_type_ann(type_ann, &s);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Infers a method or a field's initial value.  `this` refers to the
+/// instance in non-static members and `super` to the instance of the super
+/// class.
+fn infer_member(
+    ctx: &mut Context,
+    expr: &mut Expr,
+    is_static: bool,
+    this_type: &Type,
+    super_class: &Option<Ident>,
+) -> Result<(Subst, Type), TypeError> {
+    ctx.push_scope(false);
+    if !is_static {
+        ctx.insert_value(String::from("this"), this_type.to_owned());
+        if let Some(super_class) = super_class {
+            let super_t = Type::from(TypeKind::Ref(types::TRef {
+                name: super_class.name.to_owned(),
+                type_args: None,
+            }));
+            ctx.insert_value(String::from("super"), super_t);
+        }
+    }
+    let result = infer_expr(ctx, expr);
+    ctx.pop_scope();
+    result
+}
+
+/// The props of the instance type of the class called `name`.
+fn instance_props(name: &str, ctx: &Context) -> Result<Vec<TProp>, TypeError> {
+    match ctx.lookup_type(name)?.kind {
+        TypeKind::Object(TObject { elems }) => Ok(elems
+            .into_iter()
+            .filter_map(|elem| match elem {
+                TObjElem::Prop(prop) => Some(prop),
+                _ => None,
+            })
+            .collect()),
+        _ => Err(Report::new(TypeError::new(TypeErrorKind::NotAClass {
+            name: name.to_owned(),
+        }))),
+    }
+}
+
+/// The `new` signature of the class called `name`.
+fn parent_constructor(name: &str, ctx: &Context) -> Result<TCallable, TypeError> {
+    let t = ctx.lookup_value_and_instantiate(name)?;
+    let constructor = match &t.kind {
+        TypeKind::Object(TObject { elems }) => elems.iter().find_map(|elem| match elem {
+            TObjElem::Constructor(callable) => Some(callable.to_owned()),
+            _ => None,
+        }),
+        _ => None,
+    };
+    constructor.ok_or_else(|| {
+        Report::new(TypeError::new(TypeErrorKind::NotAClass {
+            name: name.to_owned(),
+        }))
+    })
+}
+
+fn find_prop<'a>(props: &'a [TProp], name: &str) -> &'a TProp {
+    props
+        .iter()
+        .find(|prop| prop.name == name)
+        .expect("every member has a prop")
+}
+
+fn object_type(props: &[TProp], s: &Subst) -> Type {
+    let elems = props
+        .iter()
+        .map(|prop| {
+            TObjElem::Prop(TProp {
+                t: prop.t.apply(s),
+                ..prop.to_owned()
+            })
+        })
+        .collect();
+    Type::from(TypeKind::Object(TObject { elems }))
+}
+
+fn constructor_type(params: &[TFnParam], this_type: &Type, statics: &[TProp], s: &Subst) -> Type {
+    let mut elems = vec![TObjElem::Constructor(TCallable {
+        params: params.to_owned(),
+        ret: Box::from(this_type.to_owned()),
+        type_params: vec![],
+    })];
+    if let TypeKind::Object(TObject {
+        elems: static_elems,
+    }) = object_type(statics, s).kind
+    {
+        elems.extend(static_elems);
+    }
+    Type::from(TypeKind::Object(TObject { elems })).apply(s)
+}
@@ -0,0 +1,112 @@
+use error_stack::Result;
+use std::collections::HashMap;
+
+use crochet_ast::types::{
+    self as types, BindingIdent, TEnum, TEnumVariant, TFnParam, TLam, TObjElem, TObject, TPat,
+    TProp, Type, TypeKind,
+};
+use crochet_ast::values::*;
+
+use crate::context::{Binding, Context};
+use crate::infer_type_ann::*;
+use crate::substitutable::Subst;
+use crate::type_error::TypeError;
+use crate::update::*;
+use crate::util::*;
+
+/// Infers the types of an enum's payloads.  The enum itself is added to the
+/// context as a type with the enum's name and a value with the same name is
+/// added that has a constructor for each variant.  Variants with a payload
+/// are constructed by calling them, e.g. `IpAddr.V4("127.0.0.1")`, while
+/// variants without one are used as is, e.g. `Option.None`.
+///
+/// Each enum is a distinct type, see `unify`, so the only way to create a
+/// value of an enum's type is by using one of its constructors.
+pub fn infer_enum(r#enum: &mut Enum, ctx: &mut Context) -> Result<(), TypeError> {
+    let name = r#enum.ident.name.to_owned();
+
+    // All of the variants share the enum's type params.
+    let type_params: Vec<(String, Type)> = match &r#enum.type_params {
+        Some(type_params) => type_params
+            .iter()
+            .map(|tp| (tp.name.name.to_owned(), ctx.fresh_var()))
+            .collect(),
+        None => vec![],
+    };
+    let type_param_map: HashMap<String, Type> = type_params.iter().cloned().collect();
+    let type_args = r#enum
+        .type_params
+        .as_ref()
+        .map(|_| type_params.iter().map(|(_, t)| t.to_owned()).collect());
+
+    let mut s = Subst::default();
+    let mut variants: Vec<TEnumVariant> = vec![];
+    for variant in &mut r#enum.variants {
+        let payload = match &mut variant.payload {
+            Some(type_ann) => {
+                let (type_ann_s, t) = infer_type_ann_with_params(type_ann, ctx, &type_param_map)?;
+                s = compose_subs(&type_ann_s, &s);
+                Some(t)
+            }
+            None => None,
+        };
+        variants.push(TEnumVariant {
+            name: variant.ident.name.to_owned(),
+            payload,
+        });
+    }
+
+    let enum_t = Type::from(TypeKind::Enum(TEnum {
+        name: name.to_owned(),
+        type_args: type_args.to_owned(),
+        variants: variants.to_owned(),
+    }));
+    ctx.insert_type(name.to_owned(), close_over(&s, &enum_t, ctx));
+
+    let ret = Type::from(TypeKind::Ref(types::TRef {
+        name: name.to_owned(),
+        type_args,
+    }));
+    let elems: Vec<TObjElem> = variants
+        .iter()
+        .map(|variant| {
+            let t = match &variant.payload {
+                Some(payload) => Type::from(TypeKind::Lam(TLam {
+                    params: vec![TFnParam {
+                        pat: TPat::Ident(BindingIdent {
+                            name: String::from("payload"),
+                            mutable: false,
+                        }),
+                        t: payload.to_owned(),
+                        optional: false,
+                    }],
+                    ret: Box::from(ret.to_owned()),
+                })),
+                None => ret.to_owned(),
+            };
+            TObjElem::Prop(TProp {
+                name: variant.name.to_owned(),
+                optional: false,
+                mutable: false,
+                t,
+            })
+        })
+        .collect();
+    let constructors_t = Type::from(TypeKind::Object(TObject { elems }));
+    ctx.insert_binding(
+        name,
+        Binding {
+            mutable: false,
+            t: close_over(&s, &constructors_t, ctx),
+            span: Some(r#enum.ident.span.to_owned()),
+        },
+    );
+
+    for variant in &mut r#enum.variants {
+        if let Some(type_ann) = &mut variant.payload {
+            update_type_ann(type_ann, &s);
+        }
+    }
+
+    Ok(())
+}
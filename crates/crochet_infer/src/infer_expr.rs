@@ -2,7 +2,7 @@ use error_stack::{Report, Result};
 use std::collections::HashMap;
 
 use crochet_ast::types::{
-    self as types, Provenance, TFnParam, TKeyword, TObject, TPat, TVar, Type, TypeKind,
+    self as types, Provenance, TFnParam, TKeyword, TObject, TPat, TTrait, TVar, Type, TypeKind,
 };
 use crochet_ast::values::*;
 
@@ -13,32 +13,30 @@ use crate::infer_fn_param::infer_fn_param;
 use crate::infer_pattern::*;
 use crate::infer_type_ann::*;
 use crate::substitutable::{Subst, Substitutable};
-use crate::type_error::TypeError;
+use crate::type_error::{fill_span, TypeError, TypeErrorKind};
 use crate::unify::unify;
 use crate::util::*;
 use crate::visitor::Visitor;
+use crate::warning::{Warning, WarningKind};
 
 pub fn infer_expr(ctx: &mut Context, expr: &mut Expr) -> Result<(Subst, Type), TypeError> {
+    ctx.with_trace(|trace| trace.push_span(&expr.span));
+    // Errors that don't have a span yet, e.g. those coming from `unify()`, are
+    // attributed to the inner most expression that was being inferred.
+    let result = infer_expr_kind(ctx, expr).map_err(|report| fill_span(report, &expr.span));
+    ctx.with_trace(|trace| trace.pop_span());
+    result
+}
+
+fn infer_expr_kind(ctx: &mut Context, expr: &mut Expr) -> Result<(Subst, Type), TypeError> {
     let result = match &mut expr.kind {
         ExprKind::App(App { lam, args, .. }) => {
             let mut ss: Vec<Subst> = vec![];
-            let mut arg_types: Vec<Type> = vec![];
 
             let (s1, lam_type) = infer_expr(ctx, lam)?;
             ss.push(s1);
 
-            for arg in args {
-                let (arg_s, mut arg_t) = infer_expr(ctx, &mut arg.expr)?;
-                ss.push(arg_s);
-                if arg.spread.is_some() {
-                    match &mut arg_t.kind {
-                        TypeKind::Tuple(types) => arg_types.append(types),
-                        _ => arg_types.push(Type::from(TypeKind::Rest(Box::from(arg_t)))),
-                    }
-                } else {
-                    arg_types.push(arg_t);
-                }
-            }
+            let arg_types = infer_args(ctx, args, &mut ss)?;
 
             let ret_type = ctx.fresh_var();
             // Are we missing an `apply()` call here?
@@ -58,6 +56,58 @@ pub fn infer_expr(ctx: &mut Context, expr: &mut Expr) -> Result<(Subst, Type), T
             // return (s3 `compose` s2 `compose` s1, apply s3 tv)
             Ok((s, t))
         }
+        ExprKind::Pipe(pipe) => {
+            // Pipes are inferred as the application they're the same as so
+            // that generic functions and constraints are handled the same way.
+            let mut app = Expr {
+                span: expr.span.to_owned(),
+                kind: ExprKind::App(pipe.to_app()),
+                inferred_type: None,
+            };
+            let result = infer_expr(ctx, &mut app);
+            if let ExprKind::App(app) = app.kind {
+                *pipe = Pipe::from_app(app, pipe.placeholder);
+            }
+            result
+        }
+        ExprKind::New(New { expr, args }) => {
+            let mut ss: Vec<Subst> = vec![];
+
+            let (s1, t) = infer_expr(ctx, expr)?;
+            ss.push(s1);
+
+            let constructors = get_constructors(&t, ctx)?;
+            if constructors.is_empty() {
+                return Err(
+                    Report::new(TypeError::new(TypeErrorKind::NotCallable { t }))
+                        .attach_printable("Only types with a `new` signature can be constructed"),
+                );
+            }
+
+            let arg_types = infer_args(ctx, args, &mut ss)?;
+
+            let ret_type = ctx.fresh_var();
+            let call_type = Type::from(TypeKind::App(types::TApp {
+                args: arg_types,
+                ret: Box::from(ret_type.clone()),
+            }));
+
+            // Classes declared in .d.ts files can have more than one
+            // constructor, the first one that matches the args is used.
+            let mut result = unify(&call_type, &constructors[0], ctx);
+            for constructor in &constructors[1..] {
+                if result.is_ok() {
+                    break;
+                }
+                result = unify(&call_type, constructor, ctx);
+            }
+            ss.push(result?);
+
+            let s = compose_many_subs(&ss);
+            let t = ret_type.apply(&s);
+
+            Ok((s, t))
+        }
         ExprKind::Fix(Fix { expr, .. }) => {
             let (s1, t) = infer_expr(ctx, expr)?;
             let tv = ctx.fresh_var();
@@ -83,8 +133,9 @@ pub fn infer_expr(ctx: &mut Context, expr: &mut Expr) -> Result<(Subst, Type), T
             // instead of a TApp.
             let t = match t.kind {
                 TypeKind::Lam(types::TLam { ret, .. }) => Ok(ret.as_ref().to_owned()),
-                _ => Err(Report::new(TypeError)
-                    .attach_printable("Expr::Fix should always infer a lambda")),
+                _ => Err(Report::new(TypeError::new(TypeErrorKind::Unsupported {
+                    msg: "Expr::Fix should always infer a lambda".to_string(),
+                }))),
             }?;
 
             Ok((s, t))
@@ -101,34 +152,32 @@ pub fn infer_expr(ctx: &mut Context, expr: &mut Expr) -> Result<(Subst, Type), T
             alternate,
             ..
         }) => match alternate {
-            Some(alternate) => {
-                match &mut cond.kind {
-                    ExprKind::LetExpr(LetExpr { pat, expr, .. }) => {
-                        // TODO: warn if the pattern isn't refutable
-                        let (s1, t1) =
-                            infer_let(pat, &mut None, expr, consequent, ctx, &PatternUsage::Match)?;
-                        let (s2, t2) = infer_expr(ctx, alternate)?;
-
-                        let s = compose_many_subs(&[s1, s2]);
-                        let t = union_types(&t1, &t2);
-                        Ok((s, t))
-                    }
-                    _ => {
-                        let (s1, t1) = infer_expr(ctx, cond)?;
-                        let (s2, t2) = infer_expr(ctx, consequent)?;
-                        let (s3, t3) = infer_expr(ctx, alternate)?;
-                        let s4 =
-                            unify(&t1, &Type::from(TypeKind::Keyword(TKeyword::Boolean)), ctx)?;
+            Some(alternate) => match &mut cond.kind {
+                ExprKind::LetExpr(LetExpr { pat, expr, .. }) => {
+                    warn_if_irrefutable(ctx, pat);
+                    let (s1, t1) =
+                        infer_let(pat, &mut None, expr, consequent, ctx, &PatternUsage::Match)?;
+                    let (s2, t2) = infer_expr(ctx, alternate)?;
 
-                        let s = compose_many_subs(&[s1, s2, s3, s4]);
-                        let t = union_types(&t2, &t3);
+                    let s = compose_many_subs(&[s1, s2]);
+                    let t = union_types(&t1, &t2);
+                    Ok((s, t))
+                }
+                _ => {
+                    let (s1, t1) = infer_expr(ctx, cond)?;
+                    let (s2, t2) = infer_expr(ctx, consequent)?;
+                    let (s3, t3) = infer_expr(ctx, alternate)?;
+                    let s4 = unify(&t1, &Type::from(TypeKind::Keyword(TKeyword::Boolean)), ctx)?;
 
-                        Ok((s, t))
-                    }
+                    let s = compose_many_subs(&[s1, s2, s3, s4]);
+                    let t = union_types(&t2, &t3);
+
+                    Ok((s, t))
                 }
-            }
+            },
             None => match &mut cond.kind {
                 ExprKind::LetExpr(LetExpr { pat, expr, .. }) => {
+                    warn_if_irrefutable(ctx, pat);
                     let (s1, t1) =
                         infer_let(pat, &mut None, expr, consequent, ctx, &PatternUsage::Match)?;
                     let s2 = match unify(
@@ -137,9 +186,9 @@ pub fn infer_expr(ctx: &mut Context, expr: &mut Expr) -> Result<(Subst, Type), T
                         ctx,
                     ) {
                         Ok(s) => Ok(s),
-                        Err(_) => Err(Report::new(TypeError).attach_printable(
-                            "Consequent for 'if' without 'else' must not return a value",
-                        )),
+                        Err(_) => Err(Report::new(TypeError::new(
+                            TypeErrorKind::NonUndefinedIfConsequent { t: t1.to_owned() },
+                        ))),
                     }?;
 
                     let s = compose_subs(&s2, &s1);
@@ -156,9 +205,10 @@ pub fn infer_expr(ctx: &mut Context, expr: &mut Expr) -> Result<(Subst, Type), T
                         ctx,
                     ) {
                         Ok(s) => Ok(s),
-                        Err(_) => Err(Report::new(TypeError).attach_printable(
-                            "Consequent for 'if' without 'else' must not return a value",
-                        )),
+                        Err(_) => Err(Report::new(TypeError::with_span(
+                            TypeErrorKind::NonUndefinedIfConsequent { t: t2.to_owned() },
+                            &consequent.span,
+                        ))),
                     }?;
 
                     let s = compose_many_subs(&[s1, s2, s3, s4]);
@@ -168,6 +218,100 @@ pub fn infer_expr(ctx: &mut Context, expr: &mut Expr) -> Result<(Subst, Type), T
                 }
             },
         },
+        ExprKind::Do(Do { body }) => {
+            ctx.push_block_scope();
+            let (s, t) = infer_expr(ctx, body)?;
+            ctx.pop_scope();
+            Ok((s, t))
+        }
+        ExprKind::Try(Try {
+            body,
+            catch_param,
+            catch_body,
+            finally,
+        }) => {
+            let (s1, t1) = infer_expr(ctx, body)?;
+            let mut ss = vec![s1];
+            let mut ts = vec![t1];
+
+            if let Some(catch_body) = catch_body {
+                let (s2, t2) = infer_catch(catch_param, catch_body, ctx)?;
+                ss.push(s2);
+                ts.push(t2);
+            }
+
+            // The value of a `finally` block is discarded.
+            if let Some(finally) = finally {
+                let (s3, _) = infer_expr(ctx, finally)?;
+                ss.push(s3);
+            }
+
+            let s = compose_many_subs(&ss);
+            let t = union_many_types(&ts);
+            Ok((s, t))
+        }
+        ExprKind::Throw(Throw { arg }) => {
+            let (s, _) = infer_expr(ctx, arg)?;
+            let t = Type::from(TypeKind::Keyword(TKeyword::Never));
+            Ok((s, t))
+        }
+        ExprKind::ForOf(ForOf {
+            pattern,
+            expr,
+            body,
+        }) => {
+            let (s1, t1) = infer_expr(ctx, expr)?;
+
+            ctx.push_loop_scope();
+            let (ps, pa, pt) = infer_pattern(pattern, &mut None, ctx, &HashMap::default())?;
+
+            // Each element of the array (or tuple) must be a sub-type of the
+            // loop's pattern.
+            let array_type = Type::from(TypeKind::Array(Box::from(pt)));
+            let s2 = unify(&t1, &array_type, ctx)?;
+
+            let s = compose_many_subs(&[s1, ps, s2]);
+            for (name, binding) in pa.apply(&s) {
+                ctx.insert_binding(name, binding);
+            }
+
+            // The value of the loop's body is discarded.
+            let (s3, _) = infer_expr(ctx, body)?;
+            ctx.pop_scope();
+
+            let s = compose_subs(&s3, &s);
+            let t = Type::from(TypeKind::Keyword(TKeyword::Undefined));
+            Ok((s, t))
+        }
+        ExprKind::While(While { cond, body }) => {
+            let (s1, t1) = infer_expr(ctx, cond)?;
+            let s2 = unify(&t1, &Type::from(TypeKind::Keyword(TKeyword::Boolean)), ctx)?;
+
+            ctx.push_loop_scope();
+            let (s3, _) = infer_expr(ctx, body)?;
+            ctx.pop_scope();
+
+            let s = compose_many_subs(&[s1, s2, s3]);
+            let t = Type::from(TypeKind::Keyword(TKeyword::Undefined));
+            Ok((s, t))
+        }
+        ExprKind::Break | ExprKind::Continue => {
+            if !ctx.in_loop() {
+                let keyword = match &expr.kind {
+                    ExprKind::Break => "break",
+                    _ => "continue",
+                };
+                return Err(Report::new(TypeError::new(
+                    TypeErrorKind::JumpOutsideLoop {
+                        keyword: keyword.to_owned(),
+                    },
+                )));
+            }
+
+            let s = Subst::default();
+            let t = Type::from(TypeKind::Keyword(TKeyword::Undefined));
+            Ok((s, t))
+        }
         ExprKind::JSXElement(JSXElement {
             name,
             attrs,
@@ -227,9 +371,10 @@ pub fn infer_expr(ctx: &mut Context, expr: &mut Expr) -> Result<(Subst, Type), T
                         return Ok((s, t));
                     }
                     _ => {
-                        return Err(
-                            Report::new(TypeError).attach_printable("Component must be a function")
-                        )
+                        return Err(Report::new(TypeError::new(TypeErrorKind::NotCallable {
+                            t: t.to_owned(),
+                        }))
+                        .attach_printable("Component must be a function"))
                     }
                 }
             }
@@ -336,11 +481,7 @@ pub fn infer_expr(ctx: &mut Context, expr: &mut Expr) -> Result<(Subst, Type), T
         }) => match pattern {
             Some(pat) => infer_let(pat, type_ann, init, body, ctx, &PatternUsage::Assign),
             None => {
-                let (init_s, init_t) = infer_expr(ctx, init)?;
-
-                if init_t.kind != TypeKind::Keyword(TKeyword::Undefined) {
-                    println!("WARNING: {init_t} was not assigned");
-                }
+                let (init_s, _) = infer_expr(ctx, init)?;
 
                 let (body_s, body_t) = infer_expr(ctx, body)?;
 
@@ -354,19 +495,52 @@ pub fn infer_expr(ctx: &mut Context, expr: &mut Expr) -> Result<(Subst, Type), T
             // TODO:
             // - if left is an identifier look it up to see if it exists in the context
             // - if it does, check if its mutable or not
-            if let ExprKind::Ident(id) = &left.kind {
-                let name = &id.name;
-                let binding = ctx.lookup_binding(name)?;
-                if !binding.mutable {
-                    return Err(Report::new(TypeError)
-                        .attach_printable(format!("can't assign to non-mutable binder '{name}'")));
+            let binding = match &left.kind {
+                ExprKind::Ident(id) => {
+                    let name = &id.name;
+                    let binding = ctx.lookup_binding(name)?;
+                    if !binding.mutable {
+                        return Err(Report::new(TypeError::with_span(
+                            TypeErrorKind::AssignToImmutable {
+                                name: name.to_owned(),
+                            },
+                            &left.span,
+                        )));
+                    }
+                    Some(binding)
                 }
-            }
+                _ => None,
+            };
 
             // This is similar to infer let, but without the type annotation and
             // with pat being an expression instead of a pattern.
             let (rs, rt) = infer_expr(ctx, right)?;
-            let (ls, lt) = infer_expr(ctx, left)?;
+            let (ls, lt) = match binding {
+                // Identifiers aren't inferred like other expressions since
+                // assigning to a binding doesn't count as reading it.
+                Some(binding) => {
+                    let lt = ctx.instantiate(&binding.t);
+                    left.inferred_type = Some(lt.to_owned());
+                    (Subst::default(), lt)
+                }
+                None => infer_expr(ctx, left)?,
+            };
+
+            if let ExprKind::Member(Member {
+                obj,
+                prop: MemberProp::Ident(prop),
+            }) = &left.kind
+            {
+                if is_immutable_field(obj, &prop.name, ctx)? {
+                    return Err(Report::new(TypeError::with_span(
+                        TypeErrorKind::AssignToImmutable {
+                            name: prop.name.to_owned(),
+                        },
+                        &left.span,
+                    ))
+                    .attach_printable("Only fields declared with `mut` can be assigned to"));
+                }
+            }
 
             if op != &AssignOp::Eq {
                 todo!("handle update assignment operators");
@@ -479,8 +653,9 @@ pub fn infer_expr(ctx: &mut Context, expr: &mut Expr) -> Result<(Subst, Type), T
         }
         ExprKind::Await(Await { expr, .. }) => {
             if !ctx.is_async() {
-                return Err(Report::new(TypeError)
-                    .attach_printable("Can't use `await` inside non-async lambda"));
+                return Err(Report::new(TypeError::new(
+                    TypeErrorKind::AwaitOutsideAsync,
+                )));
             }
 
             let (s1, t1) = infer_expr(ctx, expr)?;
@@ -510,9 +685,11 @@ pub fn infer_expr(ctx: &mut Context, expr: &mut Expr) -> Result<(Subst, Type), T
                         match &mut t.kind {
                             TypeKind::Tuple(types) => ts.append(types),
                             _ => {
-                                return Err(Report::new(TypeError).attach_printable(
-                                    "Can only spread tuple types inside a tuple",
+                                return Err(Report::new(TypeError::with_span(
+                                    TypeErrorKind::InvalidSpread { t: t.to_owned() },
+                                    &expr.span,
                                 ))
+                                .attach_printable("Can only spread tuple types inside a tuple"))
                             }
                         }
                     }
@@ -538,6 +715,20 @@ pub fn infer_expr(ctx: &mut Context, expr: &mut Expr) -> Result<(Subst, Type), T
 
             Ok((s, t))
         }
+        // `this` and `super` are bound by `infer_class` for the members that
+        // can use them.
+        ExprKind::This => {
+            let s = Subst::default();
+            let t = ctx.lookup_value_and_instantiate("this")?;
+
+            Ok((s, t))
+        }
+        ExprKind::Super => {
+            let s = Subst::default();
+            let t = ctx.lookup_value_and_instantiate("super")?;
+
+            Ok((s, t))
+        }
         ExprKind::Empty => {
             let t = Type::from(TypeKind::Keyword(TKeyword::Undefined));
             let s = Subst::default();
@@ -565,7 +756,16 @@ pub fn infer_expr(ctx: &mut Context, expr: &mut Expr) -> Result<(Subst, Type), T
             todo!()
         }
         ExprKind::Match(Match { expr, arms, .. }) => {
-            // TODO: warn if the pattern isn't refutable
+            // Arms after one that always matches can never be reached.
+            if let Some(index) = arms
+                .iter()
+                .position(|arm| arm.guard.is_none() && is_irrefutable(&arm.pattern))
+            {
+                for arm in &arms[index + 1..] {
+                    ctx.warn(Warning::new(WarningKind::UnreachableMatchArm, &arm.span));
+                }
+            }
+
             let mut ss: Vec<Subst> = vec![];
             let mut ts: Vec<Type> = vec![];
             for arm in arms {
@@ -606,7 +806,7 @@ fn infer_let(
     ctx: &mut Context,
     pu: &PatternUsage,
 ) -> Result<(Subst, Type), TypeError> {
-    ctx.push_scope(ctx.is_async());
+    ctx.push_block_scope();
     let (pa, s1) = infer_pattern_and_init(pat, type_ann, init, ctx, pu)?;
 
     // Inserts the new variables from infer_pattern_and_init() into the
@@ -625,6 +825,118 @@ fn infer_let(
     Ok((s, t))
 }
 
+fn infer_catch(
+    param: &mut Option<EFnParam>,
+    body: &mut Expr,
+    ctx: &mut Context,
+) -> Result<(Subst, Type), TypeError> {
+    ctx.push_block_scope();
+
+    // Anything can be thrown so the param is `unknown` unless it's annotated.
+    let s1 = match param {
+        Some(param) => {
+            let (ps, pa, pt) = infer_fn_param(param, ctx, &HashMap::default())?;
+            let s = match param.type_ann {
+                Some(_) => ps,
+                None => {
+                    let unknown = Type::from(TypeKind::Keyword(TKeyword::Unknown));
+                    let s = unify(&unknown, &pt.t, ctx)?;
+                    compose_subs(&s, &ps)
+                }
+            };
+            for (name, binding) in pa {
+                ctx.insert_binding(name, binding.apply(&s));
+            }
+            s
+        }
+        None => Subst::default(),
+    };
+
+    let (s2, t2) = infer_expr(ctx, body)?;
+
+    ctx.pop_scope();
+
+    let s = compose_subs(&s2, &s1);
+    Ok((s, t2))
+}
+
+fn warn_if_irrefutable(ctx: &mut Context, pat: &Pattern) {
+    if is_irrefutable(pat) {
+        ctx.warn(Warning::new(WarningKind::IrrefutableIfLet, &pat.span));
+    }
+}
+
+fn infer_args(
+    ctx: &mut Context,
+    args: &mut [ExprOrSpread],
+    ss: &mut Vec<Subst>,
+) -> Result<Vec<Type>, TypeError> {
+    let mut arg_types: Vec<Type> = vec![];
+    for arg in args {
+        let (arg_s, mut arg_t) = infer_expr(ctx, &mut arg.expr)?;
+        ss.push(arg_s);
+        if arg.spread.is_some() {
+            match &mut arg_t.kind {
+                TypeKind::Tuple(types) => arg_types.append(types),
+                _ => arg_types.push(Type::from(TypeKind::Rest(Box::from(arg_t)))),
+            }
+        } else {
+            arg_types.push(arg_t);
+        }
+    }
+    Ok(arg_types)
+}
+
+/// Returns the `new` signatures of `t` as lambda types.
+fn get_constructors(t: &Type, ctx: &Context) -> Result<Vec<Type>, TypeError> {
+    match &t.kind {
+        TypeKind::Generic(_) => get_constructors(&ctx.instantiate(t), ctx),
+        TypeKind::Ref(alias) => get_constructors(&ctx.lookup_ref_and_instantiate(alias)?, ctx),
+        TypeKind::Object(obj) => Ok(obj
+            .elems
+            .iter()
+            .filter_map(|elem| match elem {
+                TObjElem::Constructor(callable) => {
+                    let lam = Type::from(TypeKind::Lam(types::TLam {
+                        params: callable.params.to_owned(),
+                        ret: callable.ret.to_owned(),
+                    }));
+                    let t = match callable.type_params.is_empty() {
+                        true => lam,
+                        false => ctx.instantiate(&Type::from(TypeKind::Generic(types::TGeneric {
+                            t: Box::from(lam),
+                            type_params: callable.type_params.to_owned(),
+                        }))),
+                    };
+                    Some(t)
+                }
+                _ => None,
+            })
+            .collect()),
+        _ => Ok(vec![]),
+    }
+}
+
+/// Fields of class instances can only be assigned to if they're `mut`,
+/// other objects are checked when they're unified.
+fn is_immutable_field(obj: &Expr, name: &str, ctx: &Context) -> Result<bool, TypeError> {
+    let alias = match &obj.inferred_type {
+        Some(Type {
+            kind: TypeKind::Ref(alias),
+            ..
+        }) if ctx.is_class(&alias.name) => alias,
+        _ => return Ok(false),
+    };
+    let immutable = match ctx.lookup_ref_and_instantiate(alias)?.kind {
+        TypeKind::Object(obj) => obj
+            .elems
+            .iter()
+            .any(|elem| matches!(elem, TObjElem::Prop(prop) if prop.name == name && !prop.mutable)),
+        _ => false,
+    };
+    Ok(immutable)
+}
+
 fn is_promise(t: &Type) -> bool {
     matches!(&t, Type {kind: TypeKind::Ref(types::TRef { name, .. }), ..} if name == "Promise")
 }
@@ -645,11 +957,24 @@ fn infer_property_type(
         }
         TypeKind::Var(TVar { constraint, .. }) => match constraint {
             Some(constraint) => infer_property_type(constraint, prop, ctx),
-            None => Err(Report::new(TypeError)
-                .attach_printable("Cannot read property on unconstrained type param")),
+            None => Err(
+                Report::new(TypeError::new(TypeErrorKind::PropertyAccessOnNonObject {
+                    t: obj_t.to_owned(),
+                }))
+                .attach_printable("Cannot read property on unconstrained type param"),
+            ),
         },
         TypeKind::Object(obj) => get_prop_value(obj, prop, ctx),
+        TypeKind::Trait(TTrait { methods, .. }) => {
+            let elems = methods.iter().cloned().map(TObjElem::Prop).collect();
+            get_prop_value(&TObject { elems }, prop, ctx)
+        }
         TypeKind::Ref(alias) => {
+            if let MemberProp::Ident(Ident { name, .. }) = prop {
+                if let Some(t) = ctx.lookup_trait_method(&alias.name, name) {
+                    return Ok((Subst::new(), t));
+                }
+            }
             let t = ctx.lookup_ref_and_instantiate(alias)?;
             infer_property_type(&t, prop, ctx)
         }
@@ -662,25 +987,40 @@ fn infer_property_type(
             infer_property_type(&t, prop, ctx)
         }
         TypeKind::Keyword(keyword) => {
-            let t =
-                match keyword {
-                    TKeyword::Number => ctx.lookup_type_and_instantiate("Number")?,
-                    TKeyword::Boolean => ctx.lookup_type_and_instantiate("Boolean")?,
-                    TKeyword::String => ctx.lookup_type_and_instantiate("String")?,
-                    TKeyword::Symbol => ctx.lookup_type_and_instantiate("Symbol")?,
-                    TKeyword::Null => {
-                        return Err(Report::new(TypeError)
-                            .attach_printable("Cannot read property on 'null'"))
-                    }
-                    TKeyword::Undefined => {
-                        return Err(Report::new(TypeError)
-                            .attach_printable("Cannot read property on 'undefined'"))
-                    }
-                    TKeyword::Never => {
-                        return Err(Report::new(TypeError)
-                            .attach_printable("Cannot read property on 'never'"))
-                    }
-                };
+            let t = match keyword {
+                TKeyword::Number => ctx.lookup_type_and_instantiate("Number")?,
+                TKeyword::Boolean => ctx.lookup_type_and_instantiate("Boolean")?,
+                TKeyword::String => ctx.lookup_type_and_instantiate("String")?,
+                TKeyword::Symbol => ctx.lookup_type_and_instantiate("Symbol")?,
+                TKeyword::Null => {
+                    return Err(Report::new(TypeError::new(
+                        TypeErrorKind::PropertyAccessOnNonObject {
+                            t: obj_t.to_owned(),
+                        },
+                    )))
+                }
+                TKeyword::Undefined => {
+                    return Err(Report::new(TypeError::new(
+                        TypeErrorKind::PropertyAccessOnNonObject {
+                            t: obj_t.to_owned(),
+                        },
+                    )))
+                }
+                TKeyword::Never => {
+                    return Err(Report::new(TypeError::new(
+                        TypeErrorKind::PropertyAccessOnNonObject {
+                            t: obj_t.to_owned(),
+                        },
+                    )))
+                }
+                TKeyword::Unknown => {
+                    return Err(Report::new(TypeError::new(
+                        TypeErrorKind::PropertyAccessOnNonObject {
+                            t: obj_t.to_owned(),
+                        },
+                    )))
+                }
+            };
             infer_property_type(&t, prop, ctx)
         }
         TypeKind::Array(type_param) => {
@@ -737,7 +1077,11 @@ fn infer_property_type(
                                 let t = Type::from(TypeKind::Union(elem_types));
                                 Ok((prop_s, t))
                             }
-                            _ => Err(Report::new(TypeError).attach_printable(format!(
+                            _ => Err(Report::new(TypeError::new(TypeErrorKind::InvalidKey {
+                                obj: obj_t.to_owned(),
+                                key: prop_t.to_owned(),
+                            }))
+                            .attach_printable(format!(
                                 "{keyword} is an invalid indexer for tuple types"
                             ))),
                         },
@@ -746,16 +1090,27 @@ fn infer_property_type(
                                 let index: usize = index.parse().unwrap();
                                 match elem_types.get(index) {
                                     Some(t) => Ok((prop_s, t.to_owned())),
-                                    None => Err(Report::new(TypeError).attach_printable(format!(
-                                        "{index} is out of bounds for {obj_t}"
+                                    None => Err(Report::new(TypeError::new(
+                                        TypeErrorKind::TupleIndexOutOfBounds {
+                                            tuple: obj_t.to_owned(),
+                                            index,
+                                        },
                                     ))),
                                 }
                             }
-                            _ => Err(Report::new(TypeError).attach_printable(format!(
+                            _ => Err(Report::new(TypeError::new(TypeErrorKind::InvalidKey {
+                                obj: obj_t.to_owned(),
+                                key: prop_t.to_owned(),
+                            }))
+                            .attach_printable(format!(
                                 "{lit} is an invalid indexer for tuple types"
                             ))),
                         },
-                        _ => Err(Report::new(TypeError).attach_printable(format!(
+                        _ => Err(Report::new(TypeError::new(TypeErrorKind::InvalidKey {
+                            obj: obj_t.to_owned(),
+                            key: prop_t.to_owned(),
+                        }))
+                        .attach_printable(format!(
                             "{prop_t} is an invalid indexer for tuple types"
                         ))),
                     }
@@ -794,8 +1149,12 @@ fn get_prop_value(
                     let t = get_property_type(prop);
                     Ok((Subst::default(), t))
                 }
-                None => Err(Report::new(TypeError)
-                    .attach_printable(format!("Object type doesn't contain key {name}."))),
+                None => Err(Report::new(TypeError::new(
+                    TypeErrorKind::MissingProperty {
+                        obj: Type::from(TypeKind::Object(obj.to_owned())),
+                        name: name.to_owned(),
+                    },
+                ))),
             }
         }
         MemberProp::Computed(ComputedPropName { expr, .. }) => {
@@ -830,8 +1189,11 @@ fn get_prop_value(
 
                         Ok((prop_s, t))
                     }
-                    _ => Err(Report::new(TypeError)
-                        .attach_printable(format!("{keyword} is an invalid key for object types"))),
+                    _ => Err(Report::new(TypeError::new(TypeErrorKind::InvalidKey {
+                        obj: Type::from(TypeKind::Object(obj.to_owned())),
+                        key: prop_t.to_owned(),
+                    }))
+                    .attach_printable(format!("{keyword} is an invalid key for object types"))),
                 },
                 TypeKind::Lit(lit) => match lit {
                     types::TLit::Str(key) => {
@@ -853,16 +1215,25 @@ fn get_prop_value(
                                 // TODO: handle generic object properties
                                 Ok((Subst::default(), prop.t.to_owned()))
                             }
-                            None => Err(Report::new(TypeError).attach_printable(format!(
-                                "Object type doesn't contain key {key}."
+                            None => Err(Report::new(TypeError::new(
+                                TypeErrorKind::MissingProperty {
+                                    obj: Type::from(TypeKind::Object(obj.to_owned())),
+                                    name: key.to_owned(),
+                                },
                             ))),
                         }
                     }
-                    _ => Err(Report::new(TypeError)
-                        .attach_printable(format!("{lit} is an invalid key for object types"))),
+                    _ => Err(Report::new(TypeError::new(TypeErrorKind::InvalidKey {
+                        obj: Type::from(TypeKind::Object(obj.to_owned())),
+                        key: prop_t.to_owned(),
+                    }))
+                    .attach_printable(format!("{lit} is an invalid key for object types"))),
                 },
-                _ => Err(Report::new(TypeError)
-                    .attach_printable(format!("{prop_t} is an invalid key for object types"))),
+                _ => Err(Report::new(TypeError::new(TypeErrorKind::InvalidKey {
+                    obj: Type::from(TypeKind::Object(obj.to_owned())),
+                    key: prop_t.to_owned(),
+                }))
+                .attach_printable(format!("{prop_t} is an invalid key for object types"))),
             };
 
             match result {
@@ -892,7 +1263,11 @@ fn get_prop_value(
                                 return Ok((s, t));
                             }
                         }
-                        Err(Report::new(TypeError).attach_printable(format!(
+                        Err(Report::new(TypeError::new(TypeErrorKind::InvalidKey {
+                            obj: Type::from(TypeKind::Object(obj.to_owned())),
+                            key: prop_t_clone.to_owned(),
+                        }))
+                        .attach_printable(format!(
                             "{prop_t_clone} is an invalid key for object types"
                         )))
                     }
@@ -38,6 +38,7 @@ pub fn infer_fn_param(
                     binding.t.to_owned(),
                     Type::from(TypeKind::Keyword(TKeyword::Undefined)),
                 ])),
+                span: binding.span.to_owned(),
             };
             pa.insert(name.to_owned(), binding);
         };
@@ -107,5 +108,9 @@ pub fn pattern_to_tpat(pattern: &Pattern) -> TPat {
         PatternKind::Lit(_) => panic!("Literal patterns not allowed in function params"),
         PatternKind::Is(_) => panic!("'is' patterns not allowed in function params"),
         PatternKind::Wildcard(_) => panic!("Wildcard patterns not allowed in function params"),
+        PatternKind::EnumVariant(_) => {
+            panic!("Enum variant patterns not allowed in function params")
+        }
+        PatternKind::Struct(_) => panic!("Struct patterns not allowed in function params"),
     }
 }
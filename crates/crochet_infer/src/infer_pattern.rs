@@ -9,7 +9,7 @@ use crate::context::{Binding, Context};
 use crate::infer_expr::infer_expr;
 use crate::infer_type_ann::*;
 use crate::substitutable::{Subst, Substitutable};
-use crate::type_error::TypeError;
+use crate::type_error::{TypeError, TypeErrorKind};
 use crate::unify::unify;
 use crate::util::*;
 
@@ -58,7 +58,7 @@ fn infer_pattern_rec(
         PatternKind::Ident(values::BindingIdent {
             name,
             mutable,
-            span: _,
+            span,
         }) => {
             let tv = ctx.fresh_var();
             if assump
@@ -67,13 +67,16 @@ fn infer_pattern_rec(
                     Binding {
                         mutable: *mutable,
                         t: tv.clone(),
+                        span: Some(span.to_owned()),
                     },
                 )
                 .is_some()
             {
-                return Err(
-                    Report::new(TypeError).attach_printable("Duplicate identifier in pattern")
-                );
+                return Err(Report::new(TypeError::new(
+                    TypeErrorKind::DuplicateIdentifier {
+                        name: name.to_owned(),
+                    },
+                )));
             }
             Ok(tv)
         }
@@ -108,16 +111,183 @@ fn infer_pattern_rec(
                     Binding {
                         mutable: false,
                         t: t.clone(),
+                        span: Some(ident.span.to_owned()),
                     },
                 )
                 .is_some()
             {
-                return Err(
-                    Report::new(TypeError).attach_printable("Duplicate identifier in pattern")
-                );
+                return Err(Report::new(TypeError::new(
+                    TypeErrorKind::DuplicateIdentifier {
+                        name: ident.name.to_owned(),
+                    },
+                )));
             }
             Ok(t)
         }
+        PatternKind::EnumVariant(EnumVariantPat {
+            r#enum,
+            variant,
+            payload,
+        }) => {
+            let mut t_enum = match ctx.lookup_type_and_instantiate(&r#enum.name) {
+                Ok(Type {
+                    kind: TypeKind::Enum(t_enum),
+                    ..
+                }) => t_enum,
+                _ => {
+                    return Err(Report::new(TypeError::with_span(
+                        TypeErrorKind::NotAnEnum {
+                            name: r#enum.name.to_owned(),
+                        },
+                        &r#enum.span,
+                    )))
+                }
+            };
+            let t_variant = match t_enum
+                .variants
+                .iter_mut()
+                .find(|t_variant| t_variant.name == variant.name)
+            {
+                Some(t_variant) => t_variant,
+                None => {
+                    return Err(Report::new(TypeError::with_span(
+                        TypeErrorKind::UnknownVariant {
+                            name: r#enum.name.to_owned(),
+                            variant: variant.name.to_owned(),
+                        },
+                        &variant.span,
+                    )))
+                }
+            };
+            // The payload's type is whatever the sub-pattern matches, it gets
+            // constrained by the variant's declared payload when the pattern
+            // is unified with the value being matched against.
+            match (&mut t_variant.payload, payload) {
+                (Some(t_payload), Some(payload)) => {
+                    *t_payload = infer_pattern_rec(payload, ctx, assump)?;
+                }
+                (None, None) => (),
+                (t_payload, payload) => {
+                    return Err(Report::new(TypeError::new(TypeErrorKind::ArityMismatch {
+                        expected: t_payload.iter().count(),
+                        actual: payload.iter().count(),
+                    }))
+                    .attach_printable(format!(
+                        "{}.{} {} a payload",
+                        r#enum.name,
+                        variant.name,
+                        match t_payload {
+                            Some(_) => "has",
+                            None => "doesn't have",
+                        }
+                    )))
+                }
+            }
+            Ok(Type::from(TypeKind::Enum(t_enum)))
+        }
+        PatternKind::Struct(StructPat { ident, props }) => {
+            let not_a_struct = || {
+                Report::new(TypeError::with_span(
+                    TypeErrorKind::NotAStruct {
+                        name: ident.name.to_owned(),
+                    },
+                    &ident.span,
+                ))
+            };
+            if !ctx.is_class(&ident.name) {
+                return Err(not_a_struct());
+            }
+            let mut fields: Vec<types::TProp> = match ctx.lookup_type_and_instantiate(&ident.name) {
+                Ok(Type {
+                    kind: TypeKind::Object(TObject { elems }),
+                    ..
+                }) => elems
+                    .into_iter()
+                    .filter_map(|elem| match elem {
+                        types::TObjElem::Prop(prop) => Some(prop),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => return Err(not_a_struct()),
+            };
+            let struct_t = Type::from(TypeKind::Ref(types::TRef {
+                name: ident.name.to_owned(),
+                type_args: None,
+            }));
+
+            // Unlike object patterns, the type of each field is already
+            // known so the sub-patterns are constrained by the fields here
+            // instead of when the pattern is unified with the value being
+            // matched against, which is the struct's nominal type.
+            let mut s = Subst::default();
+            let mut rest: Option<&mut Pattern> = None;
+            for prop in props.iter_mut() {
+                let (name, span, value) = match prop {
+                    ObjectPatProp::KeyValue(KeyValuePatProp { key, value, .. }) => {
+                        (&key.name, &key.span, Some(value))
+                    }
+                    ObjectPatProp::Shorthand(ShorthandPatProp { ident, .. }) => {
+                        (&ident.name, &ident.span, None)
+                    }
+                    ObjectPatProp::Rest(RestPat { arg }) => {
+                        rest = Some(arg);
+                        continue;
+                    }
+                };
+                let index = match fields.iter().position(|field| &field.name == name) {
+                    Some(index) => index,
+                    None => {
+                        return Err(Report::new(TypeError::with_span(
+                            TypeErrorKind::MissingProperty {
+                                obj: struct_t,
+                                name: name.to_owned(),
+                            },
+                            span,
+                        )))
+                    }
+                };
+                let field = fields.remove(index);
+                match value {
+                    Some(value) => {
+                        let value_t = infer_pattern_rec(value, ctx, assump)?;
+                        let unify_s = unify(&value_t, &field.t.apply(&s), ctx)?;
+                        s = compose_subs(&unify_s, &s);
+                    }
+                    None => {
+                        if assump
+                            .insert(
+                                name.to_owned(),
+                                Binding {
+                                    mutable: false,
+                                    t: field.t,
+                                    span: Some(span.to_owned()),
+                                },
+                            )
+                            .is_some()
+                        {
+                            return Err(Report::new(TypeError::with_span(
+                                TypeErrorKind::DuplicateIdentifier {
+                                    name: name.to_owned(),
+                                },
+                                span,
+                            )));
+                        }
+                    }
+                }
+            }
+            // The rest pattern gets whichever fields weren't matched.
+            if let Some(rest) = rest {
+                let rest_t = infer_pattern_rec(rest, ctx, assump)?;
+                let remaining_t = Type::from(TypeKind::Object(TObject {
+                    elems: fields.into_iter().map(types::TObjElem::Prop).collect(),
+                }));
+                let unify_s = unify(&rest_t, &remaining_t.apply(&s), ctx)?;
+                s = compose_subs(&unify_s, &s);
+            }
+            *assump = assump.apply(&s);
+
+            Ok(struct_t)
+        }
         PatternKind::Rest(RestPat { arg, .. }) => {
             let t = infer_pattern_rec(arg, ctx, assump)?;
             Ok(Type::from(TypeKind::Rest(Box::from(t))))
@@ -193,6 +363,7 @@ fn infer_pattern_rec(
                                     Binding {
                                         mutable: false,
                                         t: tv.clone(),
+                                        span: Some(ident.span.to_owned()),
                                     },
                                 )
                                 .is_some()
@@ -0,0 +1,87 @@
+use error_stack::Result;
+
+use crochet_ast::types::{
+    self as types, BindingIdent, TCallable, TFnParam, TObjElem, TObject, TPat, TProp, Type,
+    TypeKind,
+};
+use crochet_ast::values::*;
+
+use crate::context::{Binding, Context};
+use crate::infer_type_ann::*;
+use crate::substitutable::Subst;
+use crate::type_error::TypeError;
+use crate::update::*;
+use crate::util::*;
+
+/// Infers the types of a struct's fields.  The instance type is added to the
+/// context as a type with the struct's name and its constructor is added as
+/// a value with the same name.  The constructor takes a single object with a
+/// value for each field, e.g. `new Point({x: 5, y: 10})`.
+///
+/// Structs are typed nominally, the same way classes are, see `unify`.
+pub fn infer_struct(r#struct: &mut Struct, ctx: &mut Context) -> Result<(), TypeError> {
+    let name = r#struct.ident.name.to_owned();
+
+    let mut s = Subst::default();
+    let mut fields: Vec<TProp> = vec![];
+    for field in &mut r#struct.fields {
+        let (type_ann_s, t) = infer_type_ann(&mut field.type_ann, ctx, &mut None)?;
+        s = compose_subs(&type_ann_s, &s);
+        fields.push(TProp {
+            name: field.key.name.to_owned(),
+            optional: false,
+            mutable: field.is_mutable,
+            t,
+        });
+    }
+
+    let instance_t = Type::from(TypeKind::Object(TObject {
+        elems: fields.iter().cloned().map(TObjElem::Prop).collect(),
+    }));
+    // The values passed to the constructor are only read from.
+    let values_t = Type::from(TypeKind::Object(TObject {
+        elems: fields
+            .iter()
+            .map(|field| {
+                TObjElem::Prop(TProp {
+                    mutable: false,
+                    ..field.to_owned()
+                })
+            })
+            .collect(),
+    }));
+    let constructor_t = Type::from(TypeKind::Object(TObject {
+        elems: vec![TObjElem::Constructor(TCallable {
+            params: vec![TFnParam {
+                pat: TPat::Ident(BindingIdent {
+                    name: String::from("values"),
+                    mutable: false,
+                }),
+                t: values_t,
+                optional: false,
+            }],
+            ret: Box::from(Type::from(TypeKind::Ref(types::TRef {
+                name: name.to_owned(),
+                type_args: None,
+            }))),
+            type_params: vec![],
+        })],
+    }));
+
+    ctx.insert_class(name.to_owned(), None);
+    ctx.insert_type(name.to_owned(), close_over(&s, &instance_t, ctx));
+    ctx.insert_binding(
+        name,
+        Binding {
+            mutable: false,
+            t: close_over(&s, &constructor_t, ctx),
+            span: Some(r#struct.ident.span.to_owned()),
+        },
+    );
+
+    for field in &mut r#struct.fields {
+        update_type_ann(&mut field.type_ann, &s);
+    }
+
+    Ok(())
+}
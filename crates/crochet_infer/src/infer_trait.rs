@@ -0,0 +1,259 @@
+use error_stack::{Report, Result};
+use std::collections::HashMap;
+
+use crochet_ast::types::{
+    self as types, TFnParam, TObjElem, TObject, TProp, TTrait, Type, TypeKind,
+};
+use crochet_ast::values::*;
+
+use crate::context::{Binding, Context};
+use crate::infer_expr::infer_expr;
+use crate::infer_fn_param::infer_fn_param;
+use crate::infer_type_ann::*;
+use crate::substitutable::{Subst, Substitutable};
+use crate::type_error::{TypeError, TypeErrorKind};
+use crate::unify::unify;
+use crate::update::*;
+use crate::util::*;
+
+/// Infers the signatures of a trait's methods and checks its default
+/// implementations against them.  The trait is added to the context as a
+/// type with the trait's name and its default implementations are added as
+/// a value with the same name, see `build_trait` in crochet_codegen.
+///
+/// All of the params and the return type of each method must be annotated
+/// so that the methods' types don't depend on their default implementations.
+pub fn infer_trait(r#trait: &mut Trait, ctx: &mut Context) -> Result<(), TypeError> {
+    let name = r#trait.ident.name.to_owned();
+
+    let mut s = Subst::default();
+    let mut methods: Vec<(TProp, &mut TraitMethod)> = vec![];
+    for method in &mut r#trait.methods {
+        let mut params: Vec<TFnParam> = vec![];
+        for (index, param) in method.params.iter_mut().enumerate() {
+            if param.type_ann.is_none() {
+                return Err(Report::new(TypeError::with_span(
+                    TypeErrorKind::MissingTypeAnnotation {
+                        name: param.get_name(&index),
+                    },
+                    &param.pat.span,
+                ))
+                .attach_printable("the params of trait methods must have type annotations"));
+            }
+            let (ps, _, t_param) = infer_fn_param(param, ctx, &HashMap::default())?;
+            s = compose_subs(&ps, &s);
+            params.push(t_param);
+        }
+        let (ret_s, ret) = infer_type_ann(&mut method.return_type, ctx, &mut None)?;
+        s = compose_subs(&ret_s, &s);
+
+        let prop = TProp {
+            name: method.key.name.to_owned(),
+            optional: false,
+            mutable: false,
+            t: Type::from(TypeKind::Lam(types::TLam {
+                params,
+                ret: Box::from(ret),
+            })),
+        };
+        methods.push((prop, method));
+    }
+
+    let trait_t = Type::from(TypeKind::Trait(TTrait {
+        name: name.to_owned(),
+        methods: methods.iter().map(|(prop, _)| prop.to_owned()).collect(),
+    }))
+    .apply(&s);
+    ctx.insert_type(name.to_owned(), trait_t.to_owned());
+
+    // Default implementations can call the trait's other methods using
+    // `this`, whichever type implements the trait will have them.
+    let mut defaults: Vec<TObjElem> = vec![];
+    for (prop, method) in &mut methods {
+        let body = match &mut method.body {
+            Some(body) => body,
+            None => continue,
+        };
+        let mut lambda = Lambda {
+            params: method.params.to_owned(),
+            body: body.to_owned(),
+            is_async: false,
+            return_type: Some(method.return_type.to_owned()),
+            type_params: None,
+        };
+        let (method_s, method_t) = infer_method(ctx, &method.span, &mut lambda, &trait_t)?;
+        s = compose_subs(&method_s, &s);
+        *body = lambda.body;
+
+        let declared = prop.t.apply(&s);
+        let unify_s = unify(&method_t, &declared, ctx)?;
+        s = compose_subs(&unify_s, &s);
+
+        defaults.push(TObjElem::Prop(TProp {
+            name: method.key.name.to_owned(),
+            optional: false,
+            mutable: false,
+            t: declared,
+        }));
+    }
+
+    ctx.insert_binding(
+        name,
+        Binding {
+            mutable: false,
+            t: Type::from(TypeKind::Object(TObject { elems: defaults })).apply(&s),
+            span: Some(r#trait.ident.span.to_owned()),
+        },
+    );
+
+    for (_, method) in methods {
+        if let Some(body) = &mut method.body {
+            update_expr(body, &s);
+        }
+        update_type_ann(&mut method.return_type, &s);
+    }
+
+    Ok(())
+}
+
+/// Checks the methods of an `impl` block against the trait's signatures.
+/// Methods that aren't implemented by the `impl` block use the trait's
+/// default implementation, it's an error if there isn't one.
+///
+/// Traits can only be implemented for classes and structs since their
+/// methods are added to the prototype of the class at runtime.
+pub fn infer_impl(r#impl: &mut Impl, ctx: &mut Context) -> Result<(), TypeError> {
+    let trait_name = &r#impl.r#trait.name;
+    let type_name = &r#impl.type_ident.name;
+
+    let TTrait { methods, .. } = match ctx.lookup_trait(trait_name) {
+        Some(tr) => tr,
+        None => {
+            return Err(Report::new(TypeError::with_span(
+                TypeErrorKind::NotATrait {
+                    name: trait_name.to_owned(),
+                },
+                &r#impl.r#trait.span,
+            )))
+        }
+    };
+    if !ctx.is_class(type_name) {
+        return Err(Report::new(TypeError::with_span(
+            TypeErrorKind::NotAClass {
+                name: type_name.to_owned(),
+            },
+            &r#impl.type_ident.span,
+        ))
+        .attach_printable("traits can only be implemented for classes and structs"));
+    }
+    let members: Vec<String> = match ctx.lookup_type(type_name)?.kind {
+        TypeKind::Object(TObject { elems }) => elems
+            .iter()
+            .filter_map(|elem| match elem {
+                TObjElem::Prop(prop) => Some(prop.name.to_owned()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    };
+    let defaults: Vec<String> = match ctx.lookup_value(trait_name)?.kind {
+        TypeKind::Object(TObject { elems }) => elems
+            .iter()
+            .filter_map(|elem| match elem {
+                TObjElem::Prop(prop) => Some(prop.name.to_owned()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    };
+
+    // The impl is added first so that methods can call each other.
+    ctx.insert_impl(type_name.to_owned(), trait_name.to_owned());
+
+    let this_type = Type::from(TypeKind::Ref(types::TRef {
+        name: type_name.to_owned(),
+        type_args: None,
+    }));
+    let mut s = Subst::default();
+    for ClassMethod {
+        span, key, lambda, ..
+    } in &mut r#impl.methods
+    {
+        let declared = match methods.iter().find(|method| method.name == key.name) {
+            Some(method) => method.t.to_owned(),
+            None => {
+                return Err(Report::new(TypeError::with_span(
+                    TypeErrorKind::MissingTraitMethod {
+                        trait_name: trait_name.to_owned(),
+                        method: key.name.to_owned(),
+                    },
+                    &key.span,
+                )));
+            }
+        };
+        // Trait methods are added to the class' prototype so they can't
+        // replace any of its members.
+        if members.contains(&key.name) {
+            return Err(Report::new(TypeError::with_span(
+                TypeErrorKind::DuplicateIdentifier {
+                    name: key.name.to_owned(),
+                },
+                &key.span,
+            ))
+            .attach_printable(format!(
+                "{type_name} already has a member named {}",
+                key.name
+            )));
+        }
+
+        let (method_s, method_t) = infer_method(ctx, span, lambda, &this_type)?;
+        s = compose_subs(&method_s, &s);
+        let unify_s = unify(&method_t, &declared.apply(&s), ctx)?;
+        s = compose_subs(&unify_s, &s);
+    }
+
+    for method in &methods {
+        let implemented = r#impl
+            .methods
+            .iter()
+            .any(|ClassMethod { key, .. }| key.name == method.name);
+        if !implemented && !defaults.contains(&method.name) {
+            return Err(Report::new(TypeError::with_span(
+                TypeErrorKind::NotImplemented {
+                    t: this_type,
+                    name: trait_name.to_owned(),
+                },
+                &r#impl.span,
+            ))
+            .attach_printable(format!("{} isn't implemented", method.name)));
+        }
+    }
+
+    for ClassMethod { lambda, .. } in &mut r#impl.methods {
+        update_expr(&mut lambda.body, &s);
+    }
+
+    Ok(())
+}
+
+/// Infers a trait method's implementation with `this` set to `this_type`.
+fn infer_method(
+    ctx: &mut Context,
+    span: &Span,
+    lambda: &mut Lambda,
+    this_type: &Type,
+) -> Result<(Subst, Type), TypeError> {
+    let mut expr = Expr {
+        span: span.to_owned(),
+        kind: ExprKind::Lambda(lambda.to_owned()),
+        inferred_type: None,
+    };
+    ctx.push_scope(false);
+    ctx.insert_value(String::from("this"), this_type.to_owned());
+    let result = infer_expr(ctx, &mut expr);
+    ctx.pop_scope();
+    if let ExprKind::Lambda(inferred) = expr.kind {
+        *lambda = inferred;
+    }
+    result
+}
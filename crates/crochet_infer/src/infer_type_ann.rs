@@ -10,7 +10,7 @@ use crochet_ast::values::*;
 use crate::context::Context;
 use crate::infer_expr::infer_expr;
 use crate::infer_fn_param::pattern_to_tpat;
-use crate::type_error::TypeError;
+use crate::type_error::{TypeError, TypeErrorKind};
 use crate::util::compose_many_subs;
 use crate::util::get_type_params;
 use crate::Subst;
@@ -165,6 +165,13 @@ fn infer_type_ann_rec(
                 Ok((s, t))
             }
             None => {
+                // Traits are used as is so that bounds can be checked by `unify`.
+                if let Some(tr) = ctx.lookup_trait(name) {
+                    let t = Type::from(TypeKind::Trait(tr));
+                    type_ann.inferred_type = Some(t.clone());
+                    return Ok((Subst::new(), t));
+                }
+
                 let mut type_args: Vec<Type> = vec![];
                 let mut ss: Vec<Subst> = vec![];
 
@@ -292,13 +299,19 @@ fn infer_property_type(
                 types::TLit::Num(_) => {
                     // TODO: support number literals if the ojbect has an indexer
                     // that supports them
-                    Err(Report::new(TypeError).attach_printable(
-                        "a number literal can't be used as an indexed type's index",
-                    ))
+                    Err(Report::new(TypeError::new(TypeErrorKind::InvalidKey {
+                        obj: obj_t.to_owned(),
+                        key: index_t.to_owned(),
+                    }))
+                    .attach_printable("a number literal can't be used as an indexed type's index"))
+                }
+                types::TLit::Bool(_) => {
+                    Err(Report::new(TypeError::new(TypeErrorKind::InvalidKey {
+                        obj: obj_t.to_owned(),
+                        key: index_t.to_owned(),
+                    }))
+                    .attach_printable("a boolean literal can't be used as an indexed type's index"))
                 }
-                types::TLit::Bool(_) => Err(Report::new(TypeError).attach_printable(
-                    "a boolean literal can't be used as an indexed type's index",
-                )),
                 types::TLit::Str(name) => {
                     let mut t = elems.iter().find_map(|elem| match elem {
                         types::TObjElem::Prop(prop) => {
@@ -326,12 +339,19 @@ fn infer_property_type(
                             let s = Subst::new();
                             Ok((s, t))
                         }
-                        None => Err(Report::new(TypeError)
-                            .attach_printable(format!("'{name}' not found in {obj_t}"))),
+                        None => Err(Report::new(TypeError::new(TypeErrorKind::MissingProperty {
+                            obj: obj_t.to_owned(),
+                            name: name.to_owned(),
+                        }))
+                        .attach_printable(format!("'{name}' not found in {obj_t}"))),
                     }
                 }
             },
-            _ => Err(Report::new(TypeError).attach_printable(format!(
+            _ => Err(Report::new(TypeError::new(TypeErrorKind::InvalidKey {
+                obj: obj_t.to_owned(),
+                key: index_t.to_owned(),
+            }))
+            .attach_printable(format!(
                 "{index_t} can't be used as an indexed type's index",
             ))),
         },
@@ -364,15 +384,26 @@ fn infer_property_type(
                 let t = ctx.lookup_type_and_instantiate("Symbol")?;
                 infer_property_type(&t, index_t, ctx)
             }
-            TKeyword::Null => {
-                Err(Report::new(TypeError).attach_printable("Cannot read property on 'null'"))
-            }
-            TKeyword::Undefined => {
-                Err(Report::new(TypeError).attach_printable("Cannot read property on 'undefined'"))
-            }
-            TKeyword::Never => {
-                Err(Report::new(TypeError).attach_printable("Cannot read property on 'never'"))
-            }
+            TKeyword::Null => Err(Report::new(TypeError::new(
+                TypeErrorKind::PropertyAccessOnNonObject {
+                    t: obj_t.to_owned(),
+                },
+            ))),
+            TKeyword::Undefined => Err(Report::new(TypeError::new(
+                TypeErrorKind::PropertyAccessOnNonObject {
+                    t: obj_t.to_owned(),
+                },
+            ))),
+            TKeyword::Never => Err(Report::new(TypeError::new(
+                TypeErrorKind::PropertyAccessOnNonObject {
+                    t: obj_t.to_owned(),
+                },
+            ))),
+            TKeyword::Unknown => Err(Report::new(TypeError::new(
+                TypeErrorKind::PropertyAccessOnNonObject {
+                    t: obj_t.to_owned(),
+                },
+            ))),
         },
         TypeKind::Array(type_param) => {
             // TODO: Do this for all interfaces that we lookup
@@ -388,7 +419,6 @@ fn infer_property_type(
         TypeKind::Tuple(elem_types) => {
             // TODO: Do this for all interfaces that we lookup
             let t = ctx.lookup_type("ReadonlyArray")?;
-            println!("ReadonlyArray = {t}");
             // TODO: Instead of instantiating the whole interface for one method, do
             // the lookup call first and then instantiate the method.
             // TODO: remove duplicate types
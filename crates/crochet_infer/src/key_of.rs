@@ -1,10 +1,10 @@
 use crochet_ast::types::{
-    TFnParam, TGeneric, TIndex, TKeyword, TLit, TObjElem, TObject, Type, TypeKind,
+    TFnParam, TGeneric, TIndex, TKeyword, TLit, TObjElem, TObject, TTrait, Type, TypeKind,
 };
-use error_stack::{Report, Result, ResultExt};
+use error_stack::{Report, Result};
 
 use crate::context::Context;
-use crate::type_error::TypeError;
+use crate::type_error::{TypeError, TypeErrorKind};
 use crate::util::union_many_types;
 
 const NEVER_TYPE: Type = Type {
@@ -17,12 +17,11 @@ const NEVER_TYPE: Type = Type {
 pub fn key_of(t: &Type, ctx: &Context) -> Result<Type, TypeError> {
     match &t.kind {
         TypeKind::Generic(TGeneric { t, type_params: _ }) => key_of(t, ctx),
-        TypeKind::Var(_) => Err(Report::new(TypeError)
-            .attach_printable("There isn't a way to infer a type from its keys")),
+        TypeKind::Var(_) => Err(Report::new(TypeError::new(TypeErrorKind::Unsupported {
+            msg: "There isn't a way to infer a type from its keys".to_string(),
+        }))),
         TypeKind::Ref(alias) => {
-            let t = ctx
-                .lookup_ref_and_instantiate(alias)
-                .change_context(TypeError)?;
+            let t = ctx.lookup_ref_and_instantiate(alias)?;
             key_of(&t, ctx)
         }
         TypeKind::Object(TObject { elems }) => {
@@ -62,6 +61,7 @@ pub fn key_of(t: &Type, ctx: &Context) -> Result<Type, TypeError> {
                 TKeyword::Null => return Ok(NEVER_TYPE),
                 TKeyword::Undefined => return Ok(NEVER_TYPE),
                 TKeyword::Never => return Ok(NEVER_TYPE),
+                TKeyword::Unknown => return Ok(NEVER_TYPE),
             };
             key_of(&t, ctx)
         }
@@ -90,6 +90,13 @@ pub fn key_of(t: &Type, ctx: &Context) -> Result<Type, TypeError> {
         TypeKind::Lam(_) => key_of(&ctx.lookup_type_and_instantiate("Function")?, ctx),
         TypeKind::App(_) => todo!(), // What does this even mean?
         TypeKind::Union(_) => todo!(),
+        // Variants are opaque, the only way to get at their payloads is
+        // by matching on them.
+        TypeKind::Enum(_) => Ok(NEVER_TYPE),
+        TypeKind::Trait(TTrait { methods, .. }) => {
+            let elems = methods.iter().cloned().map(TObjElem::Prop).collect();
+            key_of(&Type::from(TypeKind::Object(TObject { elems })), ctx)
+        }
         TypeKind::Intersection(elems) => {
             let elems: Result<Vec<_>, TypeError> =
                 elems.iter().map(|elem| key_of(elem, ctx)).collect();
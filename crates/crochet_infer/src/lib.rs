@@ -1,25 +1,35 @@
 mod assump;
 mod context;
+mod infer_class;
+mod infer_enum;
 mod infer_expr;
 mod infer_fn_param;
 mod infer_pattern;
+mod infer_struct;
+mod infer_trait;
 mod infer_type_ann;
 mod key_of;
+mod module;
 mod substitutable;
+mod trace;
 mod type_error;
 mod unify;
 mod unify_mut;
 mod update;
 mod util;
 mod visitor;
+mod warning;
 
 pub mod infer;
 
 pub use context::*;
 pub use infer::*;
+pub use module::{exports_of, lib_exports};
 pub use substitutable::{Subst, Substitutable};
-pub use type_error::TypeError;
+pub use trace::{Trace, TraceEvent, TraceEventKind};
+pub use type_error::{span_of_type, TypeError, TypeErrorKind};
 pub use util::{close_over, generalize, get_type_params, normalize, set_type_params};
+pub use warning::{Warning, WarningKind};
 
 #[cfg(test)]
 mod tests {
@@ -1656,9 +1666,8 @@ mod tests {
         assert_eq!(
             error_messages,
             vec![
-                "4 is out of bounds for [5, \"hello\", true]",
                 "Location",
-                "TypeError"
+                "TypeError[E0007]: 4 is out of bounds for [5, \"hello\", true]"
             ]
         );
     }
@@ -1700,7 +1709,7 @@ mod tests {
             vec![
                 "[5, \"hello\", true] is an invalid indexer for tuple types",
                 "Location",
-                "TypeError"
+                "TypeError[E0008]: [5, \"hello\", true] is an invalid key for [5, \"hello\", true]"
             ]
         );
     }
@@ -2537,6 +2546,47 @@ mod tests {
         infer_prog(src);
     }
 
+    #[test]
+    fn infer_pipelines() {
+        let src = r#"
+        let double = (x: number) => x * 2;
+        let add = (a: number, b: number) => a + b;
+        let result = 5 |> double |> add(1, _);
+        "#;
+
+        let ctx = infer_prog(src);
+
+        assert_eq!(get_value_type("result", &ctx), "number");
+    }
+
+    #[test]
+    fn infer_pipelines_with_generic_functions() {
+        let src = r#"
+        let fst = <T>(a: T, b: T): T => a;
+
+        let a: number = 5;
+        let b: number = 10;
+        let fst_num = a |> fst(_, b);
+        "#;
+
+        let ctx = infer_prog(src);
+
+        assert_eq!(get_value_type("fst_num", &ctx), "number");
+    }
+
+    #[test]
+    #[should_panic = "Can't unify string with number"]
+    fn pipelines_check_constraints() {
+        let src = r#"
+        let add = <T extends number>(a: T, b: T): T => a + b;
+        let a: string = "hello, ";
+        let b: string = "world";
+        let string_sum = a |> add(_, b);
+        "#;
+
+        infer_prog(src);
+    }
+
     #[test]
     fn test_member_access_on_constraint_type_param_success() {
         let src = r#"
@@ -2916,13 +2966,756 @@ mod tests {
                     vec![
                         "Unification failure",
                         "Location",
-                        "TypeError",
+                        "TypeError[E0004]: Can't unify \"hello\" with number",
                         "Unification failure",
                         "Location",
-                        "TypeError"
+                        "TypeError[E0004]: Can't unify true with number"
                     ]
                 );
             }
         }
     }
+
+    #[test]
+    fn unbound_identifier_has_error_code() {
+        let ctx = Context::default();
+        let report = ctx.lookup_value("foo").unwrap_err();
+        let error = report.current_context();
+
+        assert_eq!(error.code(), "E0001");
+        assert_eq!(
+            error.kind,
+            TypeErrorKind::UnboundIdentifier {
+                name: String::from("foo")
+            }
+        );
+        assert_eq!(error.to_string(), "TypeError[E0001]: Can't find value: foo");
+    }
+
+    #[test]
+    fn type_errors_are_attributed_to_the_inner_most_expr() {
+        let mut prog = parse("let x = 5 + \"hello\";").unwrap();
+        let mut ctx: Context = Context::default();
+        let report = infer::infer_prog(&mut prog, &mut ctx).unwrap_err();
+        let error = report.current_context();
+
+        assert_eq!(error.code(), "E0004");
+        assert_eq!(error.span, Some(12..19));
+    }
+
+    #[test]
+    fn generic_aliases_with_the_wrong_number_of_type_args_fail() {
+        let src = r#"
+        type Pair<A, B> = [A, B];
+        declare let pair: Pair<number>;
+        let x: [number, number] = pair;
+        "#;
+        let mut prog = parse(src).unwrap();
+        let mut ctx: Context = Context::default();
+        let report = infer::infer_prog(&mut prog, &mut ctx).unwrap_err();
+        let error = report.current_context();
+
+        assert_eq!(error.code(), "E0005");
+        assert_eq!(
+            error.kind,
+            TypeErrorKind::ArityMismatch {
+                expected: 2,
+                actual: 1
+            }
+        );
+    }
+
+    fn infer_warnings(src: &str) -> Vec<WarningKind> {
+        let mut ctx = infer_prog(src);
+        ctx.take_warnings()
+            .into_iter()
+            .map(|warning| warning.kind)
+            .collect()
+    }
+
+    #[test]
+    fn irrefutable_if_let_is_a_warning() {
+        let src = r#"
+        declare let point: {x: number, y: number};
+        let result = if (let {x, y} = point) {
+            x + y
+        } else {
+            0
+        };
+        "#;
+
+        assert_eq!(infer_warnings(src), vec![WarningKind::IrrefutableIfLet]);
+    }
+
+    #[test]
+    fn match_arms_after_an_irrefutable_arm_are_unreachable() {
+        let src = r#"
+        declare let count: number;
+        let result = match (count) {
+            n -> n,
+            0 -> 0
+        };
+        "#;
+
+        assert_eq!(infer_warnings(src), vec![WarningKind::UnreachableMatchArm]);
+    }
+
+    #[test]
+    fn unused_bindings_are_warnings() {
+        // Top-level bindings and those starting with `_` aren't reported.
+        let src = "let fst = (a, b, _c) => a;";
+
+        assert_eq!(
+            infer_warnings(src),
+            vec![WarningKind::UnusedBinding {
+                name: String::from("b")
+            }]
+        );
+    }
+
+    #[test]
+    fn assigning_to_a_binding_doesnt_use_it() {
+        let src = r#"
+        let reset = () => {
+            let mut count = 0;
+            count = 1;
+        };
+        "#;
+
+        assert_eq!(
+            infer_warnings(src),
+            vec![WarningKind::UnusedBinding {
+                name: String::from("count")
+            }]
+        );
+    }
+
+    #[test]
+    fn shadowed_bindings_are_warnings() {
+        let src = r#"
+        let x = 5;
+        let id = (x) => x;
+        "#;
+        let warnings = infer_warnings(src);
+
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(
+            &warnings[0],
+            WarningKind::ShadowedBinding { name, .. } if name == "x"
+        ));
+    }
+
+    const CLASSES_SRC: &str = r#"
+    class Point {
+        x: number;
+        mut y: number = 0;
+        static origin: number = 0;
+        constructor(x: number, y: number) {
+            this.x = x;
+            this.y = y;
+        }
+        add(other: Point) {
+            new Point(this.x + other.x, this.y + other.y)
+        }
+    }
+    class Point3d extends Point {
+        z: number = 0;
+        shift() {
+            super.add(this)
+        }
+    }
+    let p = new Point(1, 2);
+    "#;
+
+    #[test]
+    fn infer_class_declarations() {
+        let ctx = infer_prog(CLASSES_SRC);
+
+        assert_eq!(
+            get_type_type("Point", &ctx),
+            "{x: number, mut y: number, add: (other: Point) => Point}"
+        );
+        assert_eq!(
+            get_value_type("Point", &ctx),
+            "{new (x: number, y: number) => Point, origin: number}"
+        );
+        assert_eq!(get_value_type("p", &ctx), "Point");
+    }
+
+    #[test]
+    fn subclasses_inherit_members_and_constructors() {
+        let ctx = infer_prog(CLASSES_SRC);
+
+        assert_eq!(
+            get_type_type("Point3d", &ctx),
+            "{x: number, mut y: number, add: (other: Point) => Point, z: number, shift: () => Point}"
+        );
+        assert_eq!(
+            get_value_type("Point3d", &ctx),
+            "{new (x: number, y: number) => Point3d}"
+        );
+    }
+
+    #[test]
+    fn instances_of_subclasses_can_be_used_as_instances_of_their_super_class() {
+        let src = format!("{CLASSES_SRC}let q: Point = new Point3d(1, 2);");
+        let ctx = infer_prog(&src);
+
+        assert_eq!(get_value_type("q", &ctx), "Point");
+    }
+
+    #[test]
+    #[should_panic = "Point doesn't extend Point3d"]
+    fn instances_of_super_classes_cant_be_used_as_instances_of_subclasses() {
+        let src = format!("{CLASSES_SRC}let q: Point3d = new Point(1, 2);");
+        infer_prog(&src);
+    }
+
+    #[test]
+    #[should_panic = "only instances of Point can be used here"]
+    fn objects_cant_be_used_as_instances() {
+        let src = format!("{CLASSES_SRC}let q: Point = {{x: 1, y: 2, add: p.add}};");
+        infer_prog(&src);
+    }
+
+    #[test]
+    #[should_panic = "Only fields declared with `mut` can be assigned to"]
+    fn fields_without_mut_cant_be_assigned_to() {
+        let src = format!("{CLASSES_SRC}p.x = 5;");
+        infer_prog(&src);
+    }
+
+    #[test]
+    fn fields_with_mut_can_be_assigned_to() {
+        let src = format!("{CLASSES_SRC}p.y = 5;");
+        infer_prog(&src);
+    }
+
+    #[test]
+    #[should_panic = "Shape is not a class"]
+    fn classes_can_only_extend_classes() {
+        let src = r#"
+        type Shape = {name: string};
+        class Circle extends Shape {}
+        "#;
+        infer_prog(src);
+    }
+
+    #[test]
+    fn infer_try_catch() {
+        let src = r#"
+        declare let parse: (input: string) => number;
+        let result = try {
+            parse("5")
+        } catch (_) {
+            "error"
+        };
+        "#;
+        let ctx = infer_prog(src);
+
+        assert_eq!(get_value_type("result", &ctx), "\"error\" | number");
+    }
+
+    #[test]
+    fn infer_try_catch_with_annotated_param() {
+        let src = r#"
+        let result = try { 5 } catch (e: string) { e } finally { 10 };
+        "#;
+        let ctx = infer_prog(src);
+
+        assert_eq!(get_value_type("result", &ctx), "5 | string");
+    }
+
+    #[test]
+    #[should_panic = "Can't unify unknown with string"]
+    fn unannotated_catch_params_are_unknown() {
+        let src = r#"
+        declare let log: (msg: string) => undefined;
+        let result = try { 5 } catch (e) { log(e) };
+        "#;
+        infer_prog(src);
+    }
+
+    #[test]
+    fn infer_throw() {
+        assert_eq!(
+            infer("(msg: string) => throw msg"),
+            "(msg: string) => never"
+        );
+    }
+
+    #[test]
+    fn throw_can_be_used_as_any_type() {
+        let src = r#"
+        declare let cond: boolean;
+        let x = if (cond) { 5 } else { throw "error" };
+        let add = (a: number, b: number) => a + b;
+        let sum = add(5, throw "error");
+        "#;
+        let ctx = infer_prog(src);
+
+        assert_eq!(get_value_type("x", &ctx), "5");
+        assert_eq!(get_value_type("sum", &ctx), "number");
+    }
+
+    #[test]
+    fn infer_do_expressions() {
+        let src = r#"
+        let add = (a: number, b: number) => a + b;
+        let sum = add(do { let x = 5; x }, 10);
+        let obj = {x: do { let y = 5; y * 2 }};
+        "#;
+        let ctx = infer_prog(src);
+
+        assert_eq!(get_value_type("sum", &ctx), "number");
+        assert_eq!(get_value_type("obj", &ctx), "{x: number}");
+    }
+
+    #[test]
+    #[should_panic = "Can't find value: x"]
+    fn do_expression_bindings_are_scoped_to_the_block() {
+        infer_prog("let y = do { let x = 5; x }; let z = x;");
+    }
+
+    #[test]
+    fn infer_loops() {
+        let src = r#"
+        let sum = (nums: number[]) => {
+            let mut total: number = 0;
+            for (let num of nums) {
+                total = total + num;
+            }
+            total
+        };
+        let count_down = (start: number) => {
+            let mut n: number = start;
+            while (n > 0) {
+                if (n == 5) { break; };
+                n = n - 1;
+            }
+        };
+        "#;
+        let ctx = infer_prog(src);
+
+        assert_eq!(get_value_type("sum", &ctx), "(nums: number[]) => number");
+        assert_eq!(
+            get_value_type("count_down", &ctx),
+            "(start: number) => undefined"
+        );
+    }
+
+    #[test]
+    #[should_panic = "can't assign to non-mutable binder 'total'"]
+    fn reassigning_immutable_bindings_in_loops_fails() {
+        let src = r#"
+        let sum = (nums: number[]) => {
+            let total: number = 0;
+            for (let num of nums) {
+                total = total + num;
+            }
+            total
+        };
+        "#;
+        infer_prog(src);
+    }
+
+    #[test]
+    #[should_panic = "can't assign to non-mutable binder 'num'"]
+    fn reassigning_immutable_loop_bindings_fails() {
+        infer_prog("declare let nums: number[]; for (let num of nums) { num = 0; }");
+    }
+
+    #[test]
+    #[should_panic]
+    fn while_condition_must_be_a_boolean() {
+        infer_prog("while (5) { }");
+    }
+
+    #[test]
+    #[should_panic = "`break` can only be used inside of a loop"]
+    fn break_outside_of_loop_fails() {
+        infer_prog("let foo = () => { break; };");
+    }
+
+    #[test]
+    #[should_panic = "`continue` can only be used inside of a loop"]
+    fn continue_inside_of_function_inside_of_loop_fails() {
+        let src = r#"
+        declare let nums: number[];
+        for (let num of nums) {
+            let foo = () => { continue; };
+        }
+        "#;
+        infer_prog(src);
+    }
+
+    const ENUMS_SRC: &str = r#"
+    enum IpAddr {
+        V4(string),
+        V6(string),
+    }
+    enum Option<T> {
+        Some(T),
+        None,
+    }
+    "#;
+
+    #[test]
+    fn infer_enum_declarations() {
+        let ctx = infer_prog(ENUMS_SRC);
+
+        assert_eq!(get_type_type("IpAddr", &ctx), "IpAddr");
+        assert_eq!(
+            get_value_type("IpAddr", &ctx),
+            "{V4: (payload: string) => IpAddr, V6: (payload: string) => IpAddr}"
+        );
+        assert_eq!(get_type_type("Option", &ctx), "<t0>Option<t0>");
+        assert_eq!(
+            get_value_type("Option", &ctx),
+            "<t0>{Some: (payload: t0) => Option<t0>, None: Option<t0>}"
+        );
+    }
+
+    #[test]
+    fn infer_enum_construction() {
+        let src = format!(
+            r#"{ENUMS_SRC}
+            let home = IpAddr.V4("127.0.0.1");
+            let some = Option.Some(5);
+            let none: Option<string> = Option.None;
+            "#
+        );
+        let ctx = infer_prog(&src);
+
+        assert_eq!(get_value_type("home", &ctx), "IpAddr");
+        assert_eq!(get_value_type("some", &ctx), "Option<5>");
+        assert_eq!(get_value_type("none", &ctx), "Option<string>");
+    }
+
+    #[test]
+    fn infer_matching_on_enum_variants() {
+        let src = format!(
+            r#"{ENUMS_SRC}
+            declare let addr: IpAddr;
+            let addrStr = match (addr) {{
+                IpAddr.V4(address) -> address,
+                IpAddr.V6(address) -> address
+            }};
+            let unwrap = (opt: Option<number>) => match (opt) {{
+                Option.Some(value) -> value,
+                Option.None -> 0
+            }};
+            "#
+        );
+        let ctx = infer_prog(&src);
+
+        assert_eq!(get_value_type("addrStr", &ctx), "string");
+        assert_eq!(
+            get_value_type("unwrap", &ctx),
+            "(opt: Option<number>) => number"
+        );
+    }
+
+    #[test]
+    fn infer_functions_returning_enum_values_from_arrays() {
+        let src = format!(
+            r#"{ENUMS_SRC}
+            type ReadonlyArray<T> = {{
+                [key: number]: T;
+            }};
+            declare let repeat: <T>(value: T, count: number) => T[];
+            let localhost = () => repeat(IpAddr.V4("127.0.0.1"), 2)[0];
+            "#
+        );
+        let ctx = infer_prog(&src);
+
+        assert_eq!(
+            get_value_type("localhost", &ctx),
+            "() => IpAddr | undefined"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn enums_are_nominal() {
+        let src = format!(r#"{ENUMS_SRC}let addr: IpAddr = {{payload: "::1"}};"#);
+        infer_prog(&src);
+    }
+
+    #[test]
+    #[should_panic]
+    fn variants_of_different_enums_are_incompatible() {
+        let src = format!("{ENUMS_SRC}let addr: IpAddr = Option.None;");
+        infer_prog(&src);
+    }
+
+    #[test]
+    #[should_panic = "IpAddr doesn't have a variant named V5"]
+    fn matching_unknown_variants_fails() {
+        let src = format!(
+            r#"{ENUMS_SRC}
+            declare let addr: IpAddr;
+            let addrStr = match (addr) {{
+                IpAddr.V5(address) -> address,
+                _ -> "unknown"
+            }};
+            "#
+        );
+        infer_prog(&src);
+    }
+
+    #[test]
+    #[should_panic = "Point is not an enum"]
+    fn matching_variants_of_non_enums_fails() {
+        let src = r#"
+        type Point = {x: number, y: number};
+        declare let p: Point;
+        let x = match (p) {
+            Point.Origin -> 0,
+            _ -> 1
+        };
+        "#;
+        infer_prog(src);
+    }
+
+    const STRUCTS_SRC: &str = r#"
+    struct User {
+        name: string,
+        mut age: number,
+    }
+    let user = new User({name: "Alice", age: 30});
+    "#;
+
+    #[test]
+    fn infer_struct_declarations() {
+        let ctx = infer_prog(STRUCTS_SRC);
+
+        assert_eq!(
+            get_type_type("User", &ctx),
+            "{name: string, mut age: number}"
+        );
+        assert_eq!(
+            get_value_type("User", &ctx),
+            "{new (values: {name: string, age: number}) => User}"
+        );
+        assert_eq!(get_value_type("user", &ctx), "User");
+    }
+
+    #[test]
+    #[should_panic = "only instances of User can be used here"]
+    fn structs_are_nominal() {
+        let src = format!(r#"{STRUCTS_SRC}let bob: User = {{name: "Bob", age: 25}};"#);
+        infer_prog(&src);
+    }
+
+    #[test]
+    #[should_panic = "Only fields declared with `mut` can be assigned to"]
+    fn struct_fields_without_mut_cant_be_assigned_to() {
+        let src = format!(r#"{STRUCTS_SRC}user.name = "Bob";"#);
+        infer_prog(&src);
+    }
+
+    #[test]
+    fn struct_fields_with_mut_can_be_assigned_to() {
+        let src = format!("{STRUCTS_SRC}user.age = 31;");
+        infer_prog(&src);
+    }
+
+    #[test]
+    fn infer_matching_on_structs() {
+        let src = format!(
+            r#"{STRUCTS_SRC}
+            let greeting = match (user) {{
+                User {{name, age: 30}} -> name,
+                User {{age}} -> age
+            }};
+            "#
+        );
+        let ctx = infer_prog(&src);
+
+        assert_eq!(get_value_type("greeting", &ctx), "number | string");
+    }
+
+    #[test]
+    #[should_panic = "Point is not a struct"]
+    fn matching_structs_that_arent_structs_fails() {
+        let src = r#"
+        type Point = {x: number, y: number};
+        declare let p: Point;
+        let x = match (p) {
+            Point {x} -> x,
+            _ -> 0
+        };
+        "#;
+        infer_prog(src);
+    }
+
+    const TRAITS_SRC: &str = r#"
+    trait Greet {
+        name(): string;
+        greet(): string {
+            `Hello, ${this.name()}`
+        }
+    }
+    struct User {
+        first: string,
+    }
+    impl Greet for User {
+        name() {
+            this.first
+        }
+    }
+    let user = new User({first: "Alice"});
+    "#;
+
+    #[test]
+    fn infer_trait_declarations() {
+        let ctx = infer_prog(TRAITS_SRC);
+
+        assert_eq!(get_type_type("Greet", &ctx), "Greet");
+        assert_eq!(get_value_type("Greet", &ctx), "{greet: () => string}");
+    }
+
+    #[test]
+    fn infer_calling_trait_methods() {
+        let src = format!("{TRAITS_SRC}let msg = user.greet();\nlet name = user.name();");
+        let ctx = infer_prog(&src);
+
+        assert_eq!(get_value_type("msg", &ctx), "string");
+        assert_eq!(get_value_type("name", &ctx), "string");
+    }
+
+    #[test]
+    fn infer_trait_bounds() {
+        let src = format!(
+            r#"{TRAITS_SRC}
+            let greet = <T extends Greet>(x: T) => x.greet();
+            let msg = greet(user);
+            "#
+        );
+        let ctx = infer_prog(&src);
+
+        assert_eq!(
+            get_value_type("greet", &ctx),
+            "<t0 extends Greet>(x: t0) => string"
+        );
+        assert_eq!(get_value_type("msg", &ctx), "string");
+    }
+
+    #[test]
+    fn infer_trait_bounds_with_arrays_of_traits() {
+        let src = format!(
+            r#"{TRAITS_SRC}
+            type ReadonlyArray<T> = {{
+                [key: number]: T;
+            }};
+            let pick = <T extends Greet>(x: T, others: Greet[]) => others[0];
+            "#
+        );
+        let ctx = infer_prog(&src);
+
+        assert_eq!(
+            get_value_type("pick", &ctx),
+            "<t0 extends Greet>(x: t0, others: Greet[]) => Greet | undefined"
+        );
+    }
+
+    #[test]
+    #[should_panic = "doesn't implement Greet"]
+    fn trait_bounds_require_an_impl() {
+        let src = format!(
+            r#"{TRAITS_SRC}
+            struct Robot {{
+                id: number,
+            }}
+            let greet = <T extends Greet>(x: T) => x.greet();
+            let msg = greet(new Robot({{id: 5}}));
+            "#
+        );
+        infer_prog(&src);
+    }
+
+    #[test]
+    fn trait_bounds_are_satisfied_by_aliases_of_types_with_an_impl() {
+        let src = format!(
+            r#"{TRAITS_SRC}
+            type Person = User;
+            declare let person: Person;
+            let greet = <T extends Greet>(x: T) => x.greet();
+            let msg = greet(person);
+            "#
+        );
+        let ctx = infer_prog(&src);
+
+        assert_eq!(get_value_type("msg", &ctx), "string");
+    }
+
+    #[test]
+    fn trait_method_params_must_be_annotated() {
+        let src = "trait Greet { greet(name): string; }";
+        let mut prog = parse(src).unwrap();
+        let mut ctx: Context = Context::default();
+        let report = infer::infer_prog(&mut prog, &mut ctx).unwrap_err();
+        let error = report.current_context();
+
+        assert_eq!(error.code(), "E0019");
+        assert_eq!(error.span, Some(20..24));
+    }
+
+    #[test]
+    #[should_panic = "Greet doesn't have a method named wave"]
+    fn impls_can_only_implement_methods_declared_by_the_trait() {
+        let src = r#"
+        trait Greet {
+            greet(): string;
+        }
+        struct User {
+            first: string,
+        }
+        impl Greet for User {
+            greet() {
+                "Hi"
+            }
+            wave() {
+                "👋"
+            }
+        }
+        "#;
+        infer_prog(src);
+    }
+
+    #[test]
+    #[should_panic = "doesn't implement Greet"]
+    fn impls_must_implement_methods_without_defaults() {
+        let src = r#"
+        trait Greet {
+            name(): string;
+            greet(): string {
+                "Hello"
+            }
+        }
+        struct User {
+            first: string,
+        }
+        impl Greet for User {
+            greet() {
+                "Hi"
+            }
+        }
+        "#;
+        infer_prog(src);
+    }
+
+    #[test]
+    #[should_panic = "Point is not a trait"]
+    fn impls_must_be_for_traits() {
+        let src = r#"
+        type Point = {x: number, y: number};
+        struct User {
+            first: string,
+        }
+        impl Point for User {}
+        "#;
+        infer_prog(src);
+    }
 }
@@ -0,0 +1,376 @@
+use crochet_ast::types::{TObjElem, TObject, TProp, Type, TypeKind};
+use crochet_ast::values::*;
+use error_stack::{Report, Result};
+use std::rc::Rc;
+
+use crate::context::{Binding, Context, Scope};
+use crate::type_error::{TypeError, TypeErrorKind};
+
+/// Binds the names imported by `specifiers` from the module that `source`
+/// resolved to in the current scope.
+pub fn infer_import(
+    specifiers: &[ImportSpecifier],
+    source: &Str,
+    ctx: &mut Context,
+) -> Result<(), TypeError> {
+    let exports = lookup_source(source, ctx)?;
+
+    // Importing anything from a module brings its impls along with it.
+    for (type_name, traits) in &exports.impls {
+        for trait_name in traits {
+            ctx.insert_impl(type_name.to_owned(), trait_name.to_owned());
+        }
+    }
+
+    for specifier in specifiers {
+        match specifier {
+            ImportSpecifier::Named {
+                span,
+                local,
+                imported,
+                is_type,
+            } => {
+                let imported = imported.as_ref().unwrap_or(local);
+                let value = exports.values.get(&imported.name).filter(|_| !is_type);
+                let t = exports.types.get(&imported.name);
+                let namespace = exports.namespaces.get(&imported.name);
+
+                if value.is_none() && t.is_none() && namespace.is_none() {
+                    return Err(Report::new(TypeError::with_span(
+                        TypeErrorKind::MissingExport {
+                            specifier: source.value.to_owned(),
+                            name: imported.name.to_owned(),
+                        },
+                        span,
+                    )));
+                }
+
+                if let Some(b) = value {
+                    ctx.insert_binding(
+                        local.name.to_owned(),
+                        Binding {
+                            mutable: false,
+                            t: b.t.to_owned(),
+                            span: Some(span.to_owned()),
+                        },
+                    );
+                }
+                if let Some(t) = t {
+                    ctx.insert_type(local.name.to_owned(), t.to_owned());
+                }
+                if let Some(super_class) = exports.classes.get(&imported.name) {
+                    ctx.insert_class(local.name.to_owned(), super_class.to_owned());
+                }
+                if let Some(namespace) = namespace {
+                    ctx.insert_namespace(local.name.to_owned(), namespace.as_ref().to_owned());
+                }
+            }
+            ImportSpecifier::Namespace {
+                span,
+                local,
+                is_type,
+            } => {
+                if !is_type {
+                    ctx.insert_binding(
+                        local.name.to_owned(),
+                        Binding {
+                            mutable: false,
+                            t: namespace_type(&exports),
+                            span: Some(span.to_owned()),
+                        },
+                    );
+                }
+                ctx.insert_namespace(local.name.to_owned(), exports.as_ref().to_owned());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the names re-exported by `export {...} from "..."` exist in
+/// the module that `source` resolved to.
+pub fn check_reexport(
+    specifiers: &[ExportSpecifier],
+    source: &Str,
+    ctx: &Context,
+) -> Result<(), TypeError> {
+    let exports = lookup_source(source, ctx)?;
+
+    for specifier in specifiers {
+        if let ExportSpecifier::Named { span, local, .. } = specifier {
+            if !has_export(&exports, &local.name) {
+                return Err(Report::new(TypeError::with_span(
+                    TypeErrorKind::MissingExport {
+                        specifier: source.value.to_owned(),
+                        name: local.name.to_owned(),
+                    },
+                    span,
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the values, types, and namespaces exported by `prog` once it's
+/// been inferred using `ctx`.  Programs without any `import` or `export`
+/// statements export all of their top-level declarations.
+pub fn exports_of(prog: &Program, ctx: &Context) -> Scope {
+    if prog.exports_all() {
+        let module_scope = ctx.scopes.last().unwrap();
+        return Scope {
+            namespaces: module_scope.namespaces.to_owned(),
+            values: module_scope.values.to_owned(),
+            types: module_scope.types.to_owned(),
+            classes: module_scope.classes.to_owned(),
+            impls: module_scope.impls.to_owned(),
+            ..Scope::default()
+        };
+    }
+
+    // Impls don't have names so they're always exported.
+    let mut exports = Scope {
+        impls: ctx.scopes.last().unwrap().impls.to_owned(),
+        ..Scope::default()
+    };
+
+    for (exported, local) in prog.exported_names() {
+        if let Ok(b) = ctx.lookup_binding(&local) {
+            exports.values.insert(exported.to_owned(), b);
+        }
+        if let Ok(t) = ctx.lookup_type(&local) {
+            exports.types.insert(exported.to_owned(), t);
+        }
+        if let Some(super_class) = ctx.lookup_class(&local) {
+            exports.classes.insert(exported.to_owned(), super_class);
+        }
+        if let Ok(namespace) = ctx.lookup_namespace(&local) {
+            exports.namespaces.insert(exported, namespace);
+        }
+    }
+
+    for stmt in &prog.body {
+        if let Statement::Export {
+            specifiers,
+            source: Some(source),
+            ..
+        } = stmt
+        {
+            let source_exports = match ctx.lookup_module(&source.value) {
+                Ok(source_exports) => source_exports,
+                Err(_) => continue,
+            };
+            for specifier in specifiers {
+                match specifier {
+                    ExportSpecifier::Named {
+                        local, exported, ..
+                    } => {
+                        let exported = &exported.as_ref().unwrap_or(local).name;
+                        copy_export(&source_exports, &local.name, &mut exports, exported);
+                    }
+                    ExportSpecifier::All {
+                        exported: Some(exported),
+                        ..
+                    } => {
+                        exports.values.insert(
+                            exported.name.to_owned(),
+                            Binding {
+                                mutable: false,
+                                t: namespace_type(&source_exports),
+                                span: None,
+                            },
+                        );
+                        exports.namespaces.insert(
+                            exported.name.to_owned(),
+                            Box::from(source_exports.as_ref().to_owned()),
+                        );
+                    }
+                    ExportSpecifier::All { exported: None, .. } => {
+                        let names = source_exports
+                            .values
+                            .keys()
+                            .chain(source_exports.types.keys())
+                            .chain(source_exports.namespaces.keys());
+                        for name in names {
+                            copy_export(&source_exports, name, &mut exports, name);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    exports
+}
+
+/// Returns all of the declarations in a context loaded from a .d.ts file,
+/// these are what the file exports when it's imported.
+pub fn lib_exports(ctx: &Context) -> Scope {
+    let mut exports = Scope::default();
+    for scope in &ctx.scopes {
+        exports.values.extend(scope.values.to_owned());
+        exports.types.extend(scope.types.to_owned());
+        exports.classes.extend(scope.classes.to_owned());
+        exports.impls.extend(scope.impls.to_owned());
+        exports.namespaces.extend(scope.namespaces.to_owned());
+    }
+    exports
+}
+
+fn lookup_source(source: &Str, ctx: &Context) -> Result<Rc<Scope>, TypeError> {
+    ctx.lookup_module(&source.value).map_err(|report| {
+        let kind = report.current_context().kind.to_owned();
+        Report::new(TypeError::with_span(kind, &source.span))
+    })
+}
+
+fn has_export(exports: &Scope, name: &str) -> bool {
+    exports.values.contains_key(name)
+        || exports.types.contains_key(name)
+        || exports.namespaces.contains_key(name)
+}
+
+fn copy_export(from: &Scope, name: &str, to: &mut Scope, exported: &str) {
+    if let Some(b) = from.values.get(name) {
+        to.values.insert(exported.to_owned(), b.to_owned());
+    }
+    if let Some(t) = from.types.get(name) {
+        to.types.insert(exported.to_owned(), t.to_owned());
+    }
+    if let Some(super_class) = from.classes.get(name) {
+        to.classes
+            .insert(exported.to_owned(), super_class.to_owned());
+    }
+    if let Some(namespace) = from.namespaces.get(name) {
+        to.namespaces
+            .insert(exported.to_owned(), namespace.to_owned());
+    }
+}
+
+/// The type of the object that `import * as ns from "..."` binds `ns` to.
+fn namespace_type(exports: &Scope) -> Type {
+    let mut names: Vec<_> = exports.values.keys().collect();
+    // HashMap iteration order isn't stable so we sort the props by name.
+    names.sort();
+    let elems = names
+        .into_iter()
+        .map(|name| {
+            TObjElem::Prop(TProp {
+                name: name.to_owned(),
+                optional: false,
+                mutable: false,
+                t: exports.values[name].t.to_owned(),
+            })
+        })
+        .collect();
+    Type::from(TypeKind::Object(TObject { elems }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infer::infer_prog;
+
+    fn infer_module(src: &str, ctx: &Context) -> Result<(Program, Context), TypeError> {
+        let mut prog = crochet_parser::parse(src).unwrap();
+        let mut ctx = ctx.to_owned();
+        let ctx = infer_prog(&mut prog, &mut ctx)?;
+        Ok((prog, ctx))
+    }
+
+    fn value_type(ctx: &Context, name: &str) -> String {
+        ctx.lookup_value(name).unwrap().to_string()
+    }
+
+    #[test]
+    fn imported_bindings_are_in_scope() {
+        let (prog, math_ctx) = infer_module(
+            "export let add = (a: number, b: number) => a + b; export type Point = {x: number, y: number};",
+            &Context::default(),
+        )
+        .unwrap();
+
+        let mut ctx = Context::default();
+        ctx.insert_module(String::from("./math"), exports_of(&prog, &math_ctx));
+        let (_, ctx) = infer_module(
+            r#"import {add as sum, Point} from "./math"; let p: Point = {x: sum(1, 2), y: 3};"#,
+            &ctx,
+        )
+        .unwrap();
+
+        assert_eq!(value_type(&ctx, "sum"), "(a: number, b: number) => number");
+        assert_eq!(
+            ctx.lookup_type("Point").unwrap().to_string(),
+            "{x: number, y: number}"
+        );
+    }
+
+    #[test]
+    fn namespace_imports() {
+        let (prog, math_ctx) =
+            infer_module("export let zero = 0; let one = 1;", &Context::default()).unwrap();
+
+        let mut ctx = Context::default();
+        ctx.insert_module(String::from("./math"), exports_of(&prog, &math_ctx));
+        let (_, ctx) = infer_module(
+            r#"import * as math from "./math"; let x = math.zero;"#,
+            &ctx,
+        )
+        .unwrap();
+
+        assert_eq!(value_type(&ctx, "math"), "{zero: 0}");
+        assert_eq!(value_type(&ctx, "x"), "0");
+    }
+
+    #[test]
+    fn missing_exports_and_modules_are_errors() {
+        let (prog, math_ctx) =
+            infer_module("export let zero = 0; let one = 1;", &Context::default()).unwrap();
+
+        let mut ctx = Context::default();
+        ctx.insert_module(String::from("./math"), exports_of(&prog, &math_ctx));
+
+        let report = infer_module(r#"import {one} from "./math";"#, &ctx).unwrap_err();
+        assert_eq!(
+            report.current_context().kind,
+            TypeErrorKind::MissingExport {
+                specifier: String::from("./math"),
+                name: String::from("one"),
+            }
+        );
+
+        let report = infer_module(r#"import {zero} from "./missing";"#, &ctx).unwrap_err();
+        assert_eq!(
+            report.current_context().kind,
+            TypeErrorKind::UnresolvedModule {
+                specifier: String::from("./missing"),
+            }
+        );
+    }
+
+    #[test]
+    fn reexports() {
+        let (prog, math_ctx) = infer_module(
+            "export let zero = 0; export let one = 1;",
+            &Context::default(),
+        )
+        .unwrap();
+        let math_exports = exports_of(&prog, &math_ctx);
+
+        let mut ctx = Context::default();
+        ctx.insert_module(String::from("./math"), math_exports);
+        let (prog, index_ctx) = infer_module(
+            r#"export {zero as nil} from "./math"; export * as math from "./math";"#,
+            &ctx,
+        )
+        .unwrap();
+        let exports = exports_of(&prog, &index_ctx);
+
+        let mut names: Vec<_> = exports.values.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(names, vec!["math", "nil"]);
+        assert!(exports.namespaces.contains_key("math"));
+    }
+}
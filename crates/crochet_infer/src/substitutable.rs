@@ -81,6 +81,8 @@ impl Substitutable for Type {
                     index: Box::from(index.apply(sub)),
                 })
             }
+            TypeKind::Enum(e) => TypeKind::Enum(e.apply(sub)),
+            TypeKind::Trait(tr) => TypeKind::Trait(tr.apply(sub)),
         };
         norm_type(Type {
             kind,
@@ -122,6 +124,8 @@ impl Substitutable for Type {
                 result.append(&mut index.ftv());
                 result
             }
+            TypeKind::Enum(e) => e.ftv(),
+            TypeKind::Trait(tr) => tr.ftv(),
         }
     }
 }
@@ -174,6 +178,42 @@ impl Substitutable for TRef {
     }
 }
 
+impl Substitutable for TEnum {
+    fn apply(&self, sub: &Subst) -> Self {
+        TEnum {
+            name: self.name.to_owned(),
+            type_args: self.type_args.apply(sub),
+            variants: self
+                .variants
+                .iter()
+                .map(|variant| TEnumVariant {
+                    name: variant.name.to_owned(),
+                    payload: variant.payload.apply(sub),
+                })
+                .collect(),
+        }
+    }
+    fn ftv(&self) -> Vec<TVar> {
+        let mut result = self.type_args.ftv();
+        for variant in &self.variants {
+            result.append(&mut variant.payload.ftv());
+        }
+        result.unique_via(|a, b| a.id == b.id)
+    }
+}
+
+impl Substitutable for TTrait {
+    fn apply(&self, sub: &Subst) -> Self {
+        TTrait {
+            name: self.name.to_owned(),
+            methods: self.methods.apply(sub),
+        }
+    }
+    fn ftv(&self) -> Vec<TVar> {
+        self.methods.ftv()
+    }
+}
+
 impl Substitutable for TCallable {
     fn apply(&self, sub: &Subst) -> Self {
         // QUESTION: Do we really need to be filtering out type_params from
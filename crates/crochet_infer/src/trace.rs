@@ -0,0 +1,157 @@
+use crochet_ast::types::Type;
+use crochet_ast::values::Span;
+
+use crate::substitutable::Subst;
+
+#[derive(Clone, Debug)]
+pub enum TraceEventKind {
+    /// A call to `unify(t1, t2)`, `result` is `None` if it failed.
+    Unify {
+        t1: Type,
+        t2: Type,
+        result: Option<Subst>,
+    },
+    /// A generic type being instantiated with fresh type variables.
+    Instantiate { t: Type, result: Type },
+    /// An inferred type being generalized, e.g. before it's bound to a name.
+    Generalize { t: Type, result: Type },
+}
+
+#[derive(Clone, Debug)]
+pub struct TraceEvent {
+    pub kind: TraceEventKind,
+    /// The inner most expression (or statement) being inferred when the
+    /// event happened.
+    pub span: Option<Span>,
+    /// How many `unify()` calls this event is nested inside of.
+    pub depth: usize,
+}
+
+/// A record of the steps taken while inferring a program.  Tracing is off by
+/// default, see `Context::enable_trace`.
+#[derive(Clone, Debug, Default)]
+pub struct Trace {
+    pub events: Vec<TraceEvent>,
+    spans: Vec<Span>,
+    depth: usize,
+}
+
+impl Trace {
+    pub fn push_span(&mut self, span: &Span) {
+        self.spans.push(span.to_owned());
+    }
+
+    pub fn pop_span(&mut self) {
+        self.spans.pop();
+    }
+
+    fn push_event(&mut self, kind: TraceEventKind) -> usize {
+        self.events.push(TraceEvent {
+            kind,
+            span: self.spans.last().cloned(),
+            depth: self.depth,
+        });
+        self.events.len() - 1
+    }
+
+    /// Records the start of a `unify()` call, the returned index is passed to
+    /// `exit_unify` once the result is known.  Calls made in between are
+    /// nested under this one.
+    pub fn enter_unify(&mut self, t1: &Type, t2: &Type) -> usize {
+        let index = self.push_event(TraceEventKind::Unify {
+            t1: t1.to_owned(),
+            t2: t2.to_owned(),
+            result: None,
+        });
+        self.depth += 1;
+        index
+    }
+
+    pub fn exit_unify(&mut self, index: usize, result: Option<&Subst>) {
+        self.depth -= 1;
+        if let Some(TraceEvent {
+            kind: TraceEventKind::Unify { result: r, .. },
+            ..
+        }) = self.events.get_mut(index)
+        {
+            *r = result.cloned();
+        }
+    }
+
+    pub fn instantiate(&mut self, t: &Type, result: &Type) {
+        self.push_event(TraceEventKind::Instantiate {
+            t: t.to_owned(),
+            result: result.to_owned(),
+        });
+    }
+
+    pub fn generalize(&mut self, t: &Type, result: &Type) {
+        self.push_event(TraceEventKind::Generalize {
+            t: t.to_owned(),
+            result: result.to_owned(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crochet_ast::types::{TKeyword, TypeKind};
+
+    use super::*;
+    use crate::context::Context;
+    use crate::unify::unify;
+
+    #[test]
+    fn tracing_is_off_by_default() {
+        let mut ctx = Context::default();
+        let t = Type::from(TypeKind::Keyword(TKeyword::Number));
+        unify(&t, &t, &ctx).unwrap();
+
+        assert!(ctx.take_trace().is_none());
+    }
+
+    #[test]
+    fn nested_unify_calls_are_indented() {
+        let mut ctx = Context::default();
+        ctx.enable_trace();
+
+        let tv = ctx.fresh_var();
+        let number = Type::from(TypeKind::Keyword(TKeyword::Number));
+        let t1 = Type::from(TypeKind::Array(Box::from(tv.clone())));
+        let t2 = Type::from(TypeKind::Array(Box::from(number.clone())));
+        unify(&t1, &t2, &ctx).unwrap();
+
+        let trace = ctx.take_trace().unwrap();
+        let steps: Vec<_> = trace
+            .events
+            .iter()
+            .map(|event| match &event.kind {
+                TraceEventKind::Unify { t1, t2, result } => (
+                    event.depth,
+                    t1.to_string(),
+                    t2.to_string(),
+                    result.as_ref().map(|s| s.get(&1).unwrap().to_string()),
+                ),
+                kind => panic!("unexpected event: {kind:?}"),
+            })
+            .collect();
+
+        assert_eq!(
+            steps,
+            vec![
+                (
+                    0,
+                    String::from("t1[]"),
+                    String::from("number[]"),
+                    Some(String::from("number"))
+                ),
+                (
+                    1,
+                    String::from("t1"),
+                    String::from("number"),
+                    Some(String::from("number"))
+                ),
+            ]
+        );
+    }
+}
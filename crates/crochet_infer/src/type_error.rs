@@ -1,13 +1,335 @@
-use error_stack::Context;
+use crochet_ast::types::{Provenance, Type};
+use crochet_ast::values::Span;
+use error_stack::{Context, Report};
 use std::fmt;
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct TypeError;
+/// The different ways that type checking can fail.  Each kind has a stable
+/// error code, see `TypeErrorKind::code()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeErrorKind {
+    UnboundIdentifier {
+        name: String,
+    },
+    UnboundType {
+        name: String,
+    },
+    UnboundNamespace {
+        name: String,
+    },
+    TypeMismatch {
+        expected: Type,
+        actual: Type,
+    },
+    ArityMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    MissingProperty {
+        obj: Type,
+        name: String,
+    },
+    TupleIndexOutOfBounds {
+        tuple: Type,
+        index: usize,
+    },
+    InvalidKey {
+        obj: Type,
+        key: Type,
+    },
+    PropertyAccessOnNonObject {
+        t: Type,
+    },
+    NotCallable {
+        t: Type,
+    },
+    AssignToImmutable {
+        name: String,
+    },
+    MutabilityMismatch {
+        expected: Type,
+        actual: Type,
+    },
+    AwaitOutsideAsync,
+    DuplicateIdentifier {
+        name: String,
+    },
+    InfiniteType {
+        t: Type,
+    },
+    InvalidSpread {
+        t: Type,
+    },
+    InvalidRestPattern,
+    NonUndefinedIfConsequent {
+        t: Type,
+    },
+    MissingTypeAnnotation {
+        name: String,
+    },
+    UndecidableUnification {
+        t1: Type,
+        t2: Type,
+    },
+    Unsupported {
+        msg: String,
+    },
+    UnresolvedModule {
+        specifier: String,
+    },
+    MissingExport {
+        specifier: String,
+        name: String,
+    },
+    /// The paths of the modules in the cycle, starting and ending with the
+    /// same module.
+    ImportCycle {
+        cycle: Vec<String>,
+    },
+    /// A class extends something that isn't a class.
+    NotAClass {
+        name: String,
+    },
+    /// A `break` or `continue` that isn't inside of a loop.
+    JumpOutsideLoop {
+        keyword: String,
+    },
+    /// A variant pattern, e.g. `Foo.Bar(x)`, where `Foo` isn't an enum.
+    NotAnEnum {
+        name: String,
+    },
+    UnknownVariant {
+        name: String,
+        variant: String,
+    },
+    /// A struct pattern, e.g. `Foo {bar}`, where `Foo` isn't a struct.
+    NotAStruct {
+        name: String,
+    },
+    /// An `impl` block for something that isn't a trait.
+    NotATrait {
+        name: String,
+    },
+    /// A type is used where a trait is expected, but it doesn't have an
+    /// `impl` of that trait.
+    NotImplemented {
+        t: Type,
+        name: String,
+    },
+    /// An `impl` block with a method that isn't declared by the trait.
+    MissingTraitMethod {
+        trait_name: String,
+        method: String,
+    },
+}
+
+impl TypeErrorKind {
+    /// A stable identifier for this kind of error that tools can match on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            TypeErrorKind::UnboundIdentifier { .. } => "E0001",
+            TypeErrorKind::UnboundType { .. } => "E0002",
+            TypeErrorKind::UnboundNamespace { .. } => "E0003",
+            TypeErrorKind::TypeMismatch { .. } => "E0004",
+            TypeErrorKind::ArityMismatch { .. } => "E0005",
+            TypeErrorKind::MissingProperty { .. } => "E0006",
+            TypeErrorKind::TupleIndexOutOfBounds { .. } => "E0007",
+            TypeErrorKind::InvalidKey { .. } => "E0008",
+            TypeErrorKind::PropertyAccessOnNonObject { .. } => "E0009",
+            TypeErrorKind::NotCallable { .. } => "E0010",
+            TypeErrorKind::AssignToImmutable { .. } => "E0011",
+            TypeErrorKind::MutabilityMismatch { .. } => "E0012",
+            TypeErrorKind::AwaitOutsideAsync => "E0013",
+            TypeErrorKind::DuplicateIdentifier { .. } => "E0014",
+            TypeErrorKind::InfiniteType { .. } => "E0015",
+            TypeErrorKind::InvalidSpread { .. } => "E0016",
+            TypeErrorKind::InvalidRestPattern => "E0017",
+            TypeErrorKind::NonUndefinedIfConsequent { .. } => "E0018",
+            TypeErrorKind::MissingTypeAnnotation { .. } => "E0019",
+            TypeErrorKind::UndecidableUnification { .. } => "E0020",
+            TypeErrorKind::Unsupported { .. } => "E0021",
+            TypeErrorKind::UnresolvedModule { .. } => "E0022",
+            TypeErrorKind::MissingExport { .. } => "E0023",
+            TypeErrorKind::ImportCycle { .. } => "E0024",
+            TypeErrorKind::NotAClass { .. } => "E0025",
+            TypeErrorKind::JumpOutsideLoop { .. } => "E0026",
+            TypeErrorKind::NotAnEnum { .. } => "E0027",
+            TypeErrorKind::UnknownVariant { .. } => "E0028",
+            TypeErrorKind::NotAStruct { .. } => "E0029",
+            TypeErrorKind::NotATrait { .. } => "E0030",
+            TypeErrorKind::NotImplemented { .. } => "E0031",
+            TypeErrorKind::MissingTraitMethod { .. } => "E0032",
+        }
+    }
+}
+
+impl fmt::Display for TypeErrorKind {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeErrorKind::UnboundIdentifier { name } => write!(fmt, "Can't find value: {name}"),
+            TypeErrorKind::UnboundType { name } => write!(fmt, "Can't find type: {name}"),
+            TypeErrorKind::UnboundNamespace { name } => {
+                write!(fmt, "Can't find namespace: {name}")
+            }
+            TypeErrorKind::TypeMismatch { expected, actual } => {
+                write!(fmt, "Can't unify {actual} with {expected}")
+            }
+            TypeErrorKind::ArityMismatch { expected, actual } => {
+                write!(fmt, "Expected {expected} arguments but got {actual}")
+            }
+            TypeErrorKind::MissingProperty { name, .. } => {
+                write!(fmt, "Object type doesn't contain key {name}.")
+            }
+            TypeErrorKind::TupleIndexOutOfBounds { tuple, index } => {
+                write!(fmt, "{index} is out of bounds for {tuple}")
+            }
+            TypeErrorKind::InvalidKey { obj, key } => {
+                write!(fmt, "{key} is an invalid key for {obj}")
+            }
+            TypeErrorKind::PropertyAccessOnNonObject { t } => {
+                write!(fmt, "Cannot read property on '{t}'")
+            }
+            TypeErrorKind::NotCallable { t } => write!(fmt, "{t} is not a function"),
+            TypeErrorKind::AssignToImmutable { name } => {
+                write!(fmt, "can't assign to non-mutable binder '{name}'")
+            }
+            TypeErrorKind::MutabilityMismatch { expected, actual } => {
+                write!(fmt, "Couldn't unify {actual} and {expected}")
+            }
+            TypeErrorKind::AwaitOutsideAsync => {
+                write!(fmt, "Can't use `await` inside non-async lambda")
+            }
+            TypeErrorKind::DuplicateIdentifier { name } => {
+                write!(fmt, "Duplicate identifier in pattern: {name}")
+            }
+            TypeErrorKind::InfiniteType { t } => write!(fmt, "InfiniteType: {t}"),
+            TypeErrorKind::InvalidSpread { t } => write!(fmt, "spread of type {t} not allowed"),
+            TypeErrorKind::InvalidRestPattern => write!(fmt, "Invalid rest pattern"),
+            TypeErrorKind::NonUndefinedIfConsequent { .. } => write!(
+                fmt,
+                "Consequent for 'if' without 'else' must not return a value"
+            ),
+            TypeErrorKind::MissingTypeAnnotation { name } => {
+                write!(
+                    fmt,
+                    "missing type annotation in declare statement for {name}"
+                )
+            }
+            TypeErrorKind::UndecidableUnification { t1, t2 } => {
+                write!(fmt, "Unification is undecidable: {t1} and {t2}")
+            }
+            TypeErrorKind::Unsupported { msg } => write!(fmt, "{msg}"),
+            TypeErrorKind::UnresolvedModule { specifier } => {
+                write!(fmt, "Can't find module: {specifier}")
+            }
+            TypeErrorKind::MissingExport { specifier, name } => {
+                write!(fmt, "Module {specifier} has no export named {name}")
+            }
+            TypeErrorKind::ImportCycle { cycle } => {
+                write!(fmt, "Import cycle: {}", cycle.join(" -> "))
+            }
+            TypeErrorKind::NotAClass { name } => write!(fmt, "{name} is not a class"),
+            TypeErrorKind::JumpOutsideLoop { keyword } => {
+                write!(fmt, "`{keyword}` can only be used inside of a loop")
+            }
+            TypeErrorKind::NotAnEnum { name } => write!(fmt, "{name} is not an enum"),
+            TypeErrorKind::UnknownVariant { name, variant } => {
+                write!(fmt, "{name} doesn't have a variant named {variant}")
+            }
+            TypeErrorKind::NotAStruct { name } => write!(fmt, "{name} is not a struct"),
+            TypeErrorKind::NotATrait { name } => write!(fmt, "{name} is not a trait"),
+            TypeErrorKind::NotImplemented { t, name } => {
+                write!(fmt, "{t} doesn't implement {name}")
+            }
+            TypeErrorKind::MissingTraitMethod { trait_name, method } => {
+                write!(fmt, "{trait_name} doesn't have a method named {method}")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeError {
+    pub kind: TypeErrorKind,
+    /// Where in the source the error occurred.  This is filled in by
+    /// `infer_expr` for errors that are raised without a span, e.g. from
+    /// `unify`, using the span of the inner most expression being inferred.
+    pub span: Option<Span>,
+}
+
+impl TypeError {
+    pub fn new(kind: TypeErrorKind) -> Self {
+        TypeError { kind, span: None }
+    }
+
+    pub fn with_span(kind: TypeErrorKind, span: &Span) -> Self {
+        TypeError {
+            kind,
+            span: Some(span.to_owned()),
+        }
+    }
+
+    /// A `TypeMismatch` located at wherever `actual` was inferred from.
+    pub fn mismatch(actual: &Type, expected: &Type) -> Self {
+        TypeError {
+            kind: TypeErrorKind::TypeMismatch {
+                expected: expected.to_owned(),
+                actual: actual.to_owned(),
+            },
+            span: span_of_type(actual),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    /// Types involved in the error along with where they came from, used to
+    /// add secondary labels when rendering diagnostics.
+    pub fn related_types(&self) -> Vec<&Type> {
+        match &self.kind {
+            TypeErrorKind::TypeMismatch { expected, actual }
+            | TypeErrorKind::MutabilityMismatch { expected, actual } => vec![expected, actual],
+            TypeErrorKind::UndecidableUnification { t1, t2 } => vec![t1, t2],
+            TypeErrorKind::MissingProperty { obj, .. } | TypeErrorKind::InvalidKey { obj, .. } => {
+                vec![obj]
+            }
+            TypeErrorKind::TupleIndexOutOfBounds { tuple, .. } => vec![tuple],
+            TypeErrorKind::PropertyAccessOnNonObject { t }
+            | TypeErrorKind::NotCallable { t }
+            | TypeErrorKind::InfiniteType { t }
+            | TypeErrorKind::InvalidSpread { t }
+            | TypeErrorKind::NonUndefinedIfConsequent { t } => vec![t],
+            _ => vec![],
+        }
+    }
+}
 
 impl fmt::Display for TypeError {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(fmt, "TypeError")
+        write!(fmt, "TypeError[{}]: {}", self.code(), self.kind)
     }
 }
 
 impl Context for TypeError {}
+
+/// Returns the span of the expression that `t` was inferred from if there
+/// is one.
+pub fn span_of_type(t: &Type) -> Option<Span> {
+    match t.provenance.as_deref() {
+        Some(Provenance::Expr(expr)) => Some(expr.span.to_owned()),
+        Some(Provenance::Type(t)) => span_of_type(t),
+        None => None,
+    }
+}
+
+/// Sets `span` on all of the errors in `report` that don't have a span yet.
+pub fn fill_span(mut report: Report<TypeError>, span: &Span) -> Report<TypeError> {
+    for frame in report.frames_mut() {
+        if let Some(error) = frame.downcast_mut::<TypeError>() {
+            if error.span.is_none() {
+                error.span = Some(span.to_owned());
+            }
+        }
+    }
+    report
+}
@@ -2,19 +2,30 @@ use error_stack::{Report, Result};
 use std::cmp;
 use std::collections::BTreeSet;
 
-use crochet_ast::types::{self as types, TGeneric, TLam, TObjElem, TObject, TVar, Type, TypeKind};
+use crochet_ast::types::{
+    self as types, TGeneric, TLam, TObjElem, TObject, TTrait, TVar, Type, TypeKind,
+};
 use crochet_ast::values::ExprKind;
 use types::TKeyword;
 
 use crate::context::Context;
 use crate::key_of::key_of;
 use crate::substitutable::{Subst, Substitutable};
-use crate::type_error::TypeError;
+use crate::type_error::{TypeError, TypeErrorKind};
 use crate::unify_mut::unify_mut;
 use crate::util::*;
 
 // Returns Ok(substitions) if t2 admits all values from t1 and an Err() otherwise.
 pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
+    let index = ctx.with_trace(|trace| trace.enter_unify(t1, t2));
+    let result = unify_types(t1, t2, ctx);
+    if let Some(index) = index {
+        ctx.with_trace(|trace| trace.exit_unify(index, result.as_ref().ok()));
+    }
+    result
+}
+
+fn unify_types(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
     // All binding must be done first
     match (&t1.kind, &t2.kind) {
         (TypeKind::Var(tv), _) => return bind(tv, t2, Relation::SubType, ctx),
@@ -46,14 +57,24 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
         };
 
         // It's NOT okay to use an immutable in place of a mutable one
-        return Err(Report::new(TypeError)
-            .attach_printable("Cannot use immutable type where a mutable type was expected"));
+        return Err(
+            Report::new(TypeError::new(TypeErrorKind::MutabilityMismatch {
+                expected: t2.to_owned(),
+                actual: t1.to_owned(),
+            }))
+            .attach_printable("Cannot use immutable type where a mutable type was expected"),
+        );
     }
     // It's okay to use a mutable type in place of an immutable one so it's fine
     // to continue with the non-mutable unify() call if t1 is mutable as long as
     // t2 is not.
 
     let result = match (&t1.kind, &t2.kind) {
+        // `never` is a subtype of every type, e.g. a `throw` expression can
+        // be used anywhere.
+        (TypeKind::Keyword(TKeyword::Never), _) => Ok(Subst::new()),
+        // Every type is a subtype of `unknown`.
+        (_, TypeKind::Keyword(TKeyword::Unknown)) => Ok(Subst::new()),
         (TypeKind::Lit(lit), TypeKind::Keyword(keyword)) => {
             let b = matches!(
                 (lit, keyword),
@@ -64,7 +85,8 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
             if b {
                 Ok(Subst::default())
             } else {
-                Err(Report::new(TypeError).attach_printable("Unification failure"))
+                Err(Report::new(TypeError::mismatch(t1, t2))
+                    .attach_printable("Unification failure"))
             }
         }
         (TypeKind::App(app1), TypeKind::App(app2)) => {
@@ -81,7 +103,8 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
                 let s1 = unify(&app1.ret.apply(&s), &app2.ret.apply(&s), ctx)?;
                 Ok(compose_subs(&s, &s1))
             } else {
-                Err(Report::new(TypeError).attach_printable("Couldn't unify function calls"))
+                Err(Report::new(TypeError::mismatch(t1, t2))
+                    .attach_printable("Couldn't unify function calls"))
             }
         }
         (TypeKind::Lam(lam1), TypeKind::Lam(lam2)) => {
@@ -103,7 +126,8 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
                 let s1 = unify(&lam1.ret.apply(&s), &lam2.ret.apply(&s), ctx)?;
                 Ok(compose_subs(&s, &s1))
             } else {
-                Err(Report::new(TypeError).attach_printable("Couldn't unify lambdas"))
+                Err(Report::new(TypeError::mismatch(t1, t2))
+                    .attach_printable("Couldn't unify lambdas"))
             }
         }
         // NOTE: this arm is only hit by the `infer_skk` test case
@@ -135,7 +159,8 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
                 .collect();
 
             if callables.is_empty() {
-                Err(Report::new(TypeError).attach_printable("Couldn't application with object"))
+                Err(Report::new(TypeError::mismatch(t1, t2))
+                    .attach_printable("Couldn't application with object"))
             } else {
                 for callable in callables {
                     let result = unify(t1, &callable, ctx);
@@ -143,7 +168,8 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
                         return result;
                     }
                 }
-                Err(Report::new(TypeError).attach_printable("Couldn't application with object"))
+                Err(Report::new(TypeError::mismatch(t1, t2))
+                    .attach_printable("Couldn't application with object"))
             }
         }
         (TypeKind::App(app), TypeKind::Lam(lam)) => {
@@ -190,8 +216,9 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
                     TypeKind::Rest(spread) => match &spread.as_ref().kind {
                         TypeKind::Tuple(types) => args.append(&mut types.to_owned()),
                         _ => {
-                            return Err(Report::new(TypeError)
-                                .attach_printable(format!("spread of type {spread} not allowed")))
+                            return Err(Report::new(TypeError::new(TypeErrorKind::InvalidSpread {
+                                t: spread.as_ref().to_owned(),
+                            })))
                         }
                     },
                     _ => args.push(arg.to_owned()),
@@ -199,7 +226,8 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
             }
 
             if args.len() < param_count_low_bound {
-                return Err(Report::new(TypeError).attach_printable("Not enough args provided"));
+                return Err(Report::new(TypeError::mismatch(t1, t2))
+                    .attach_printable("Not enough args provided"));
             }
 
             // TODO: Add a `variadic` boolean to the Lambda type as a convenience
@@ -249,7 +277,8 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
                 let s1 = unify(&app.ret.apply(&s), &lam.ret.apply(&s), ctx)?;
                 Ok(compose_subs(&s, &s1))
             } else {
-                Err(Report::new(TypeError).attach_printable("Not enough params provided"))
+                Err(Report::new(TypeError::mismatch(t1, t2))
+                    .attach_printable("Not enough params provided"))
             }
         }
         (TypeKind::App(_), TypeKind::Intersection(types)) => {
@@ -259,63 +288,66 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
                     return result;
                 }
             }
-            Err(Report::new(TypeError).attach_printable("Couldn't unify lambda with intersection"))
+            Err(Report::new(TypeError::mismatch(t1, t2))
+                .attach_printable("Couldn't unify lambda with intersection"))
         }
         (TypeKind::Object(obj1), TypeKind::Object(obj2)) => {
             // Should we be doing something about type_params here?
             // It's okay if t1 has extra properties, but it has to have all of t2's properties.
-            let result: Result<Vec<_>, TypeError> =
-                obj2.elems
-                    .iter()
-                    .map(|e2| {
-                        let mut b = false;
-                        let mut ss = vec![];
-                        for e1 in obj1.elems.iter() {
-                            match (e1, e2) {
-                                (TObjElem::Call(_), TObjElem::Call(_)) => {
-                                    // What to do about Call signatures?
-                                    todo!()
-                                }
-                                (TObjElem::Prop(prop1), TObjElem::Prop(prop2)) => {
-                                    if prop1.name == prop2.name {
-                                        let t1 = get_property_type(prop1);
-                                        let t2 = get_property_type(prop2);
-
-                                        if let Ok(s) = unify(&t1, &t2, ctx) {
-                                            b = true;
-                                            ss.push(s);
-                                        }
+            let result: Result<Vec<_>, TypeError> = obj2
+                .elems
+                .iter()
+                .map(|e2| {
+                    let mut b = false;
+                    let mut ss = vec![];
+                    for e1 in obj1.elems.iter() {
+                        match (e1, e2) {
+                            (TObjElem::Call(_), TObjElem::Call(_)) => {
+                                // What to do about Call signatures?
+                                todo!()
+                            }
+                            (TObjElem::Prop(prop1), TObjElem::Prop(prop2)) => {
+                                if prop1.name == prop2.name {
+                                    let t1 = get_property_type(prop1);
+                                    let t2 = get_property_type(prop2);
+
+                                    if let Ok(s) = unify(&t1, &t2, ctx) {
+                                        b = true;
+                                        ss.push(s);
                                     }
                                 }
-                                // skip pairs that aren't the same
-                                _ => (),
                             }
+                            // skip pairs that aren't the same
+                            _ => (),
                         }
+                    }
 
-                        match b {
-                            true => Ok(compose_many_subs(&ss)),
-                            false => {
-                                match e2 {
-                                    TObjElem::Call(_) => Err(Report::new(TypeError)
-                                        .attach_printable("Unification failure")),
-                                    TObjElem::Constructor(_) => Err(Report::new(TypeError)
-                                        .attach_printable("Unification failure")),
-                                    TObjElem::Index(_) => Err(Report::new(TypeError)
-                                        .attach_printable("Unification failure")),
-                                    TObjElem::Prop(prop2) => {
-                                        // Will all optional properties to be missing
-                                        if prop2.optional {
-                                            Ok(Subst::default())
-                                        } else {
-                                            Err(Report::new(TypeError)
-                                                .attach_printable("Unification failure"))
-                                        }
+                    match b {
+                        true => Ok(compose_many_subs(&ss)),
+                        false => {
+                            match e2 {
+                                TObjElem::Call(_) => Err(Report::new(TypeError::mismatch(t1, t2))
+                                    .attach_printable("Unification failure")),
+                                TObjElem::Constructor(_) => {
+                                    Err(Report::new(TypeError::mismatch(t1, t2))
+                                        .attach_printable("Unification failure"))
+                                }
+                                TObjElem::Index(_) => Err(Report::new(TypeError::mismatch(t1, t2))
+                                    .attach_printable("Unification failure")),
+                                TObjElem::Prop(prop2) => {
+                                    // Will all optional properties to be missing
+                                    if prop2.optional {
+                                        Ok(Subst::default())
+                                    } else {
+                                        Err(Report::new(TypeError::mismatch(t1, t2))
+                                            .attach_printable("Unification failure"))
                                     }
                                 }
                             }
                         }
-                    })
-                    .collect();
+                    }
+                })
+                .collect();
 
             let ss = result?;
             Ok(compose_many_subs(&ss))
@@ -329,8 +361,10 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
                 match &t.kind {
                     TypeKind::Rest(rest_type) => {
                         if maybe_rest2.is_some() {
-                            return Err(Report::new(TypeError)
-                                .attach_printable("Only one rest pattern is allowed in a tuple"));
+                            return Err(Report::new(TypeError::new(
+                                TypeErrorKind::InvalidRestPattern,
+                            ))
+                            .attach_printable("Only one rest pattern is allowed in a tuple"));
                         }
                         maybe_rest2 = Some(rest_type.as_ref().to_owned());
                     }
@@ -347,9 +381,8 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
             // If it doesn't, we return an error.
             if types1.len() < min_len {
                 // TODO: include the types in the error message
-                return Err(
-                    Report::new(TypeError).attach_printable("not enough elements to unpack")
-                );
+                return Err(Report::new(TypeError::mismatch(t1, t2))
+                    .attach_printable("not enough elements to unpack"));
             }
 
             let mut types1 = types1.to_owned();
@@ -412,7 +445,8 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
 
             match b {
                 true => Ok(compose_many_subs(&ss)),
-                false => Err(Report::new(TypeError).attach_printable("Unification failure")),
+                false => Err(Report::new(TypeError::mismatch(t1, t2))
+                    .attach_printable("Unification failure")),
             }
         }
         (TypeKind::Object(obj), TypeKind::Intersection(types)) => {
@@ -465,7 +499,12 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
                     let s = compose_subs(&s2, &s1);
                     Ok(s)
                 }
-                _ => Err(Report::new(TypeError).attach_printable("Unification is undecidable")),
+                _ => Err(Report::new(TypeError::new(
+                    TypeErrorKind::UndecidableUnification {
+                        t1: t1.to_owned(),
+                        t2: t2.to_owned(),
+                    },
+                ))),
             }
         }
         (TypeKind::Intersection(types), TypeKind::Object(obj)) => {
@@ -518,9 +557,63 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
                     let s = compose_subs(&s_rest, &s_obj);
                     Ok(s)
                 }
-                _ => Err(Report::new(TypeError).attach_printable("Unification is undecidable")),
+                _ => Err(Report::new(TypeError::new(
+                    TypeErrorKind::UndecidableUnification {
+                        t1: t1.to_owned(),
+                        t2: t2.to_owned(),
+                    },
+                ))),
             }
         }
+        (TypeKind::Enum(e1), TypeKind::Enum(e2)) => {
+            if e1.name != e2.name {
+                return Err(Report::new(TypeError::mismatch(t1, t2)));
+            }
+            let mut ss: Vec<Subst> = vec![];
+            if let (Some(args1), Some(args2)) = (&e1.type_args, &e2.type_args) {
+                for (arg1, arg2) in args1.iter().zip(args2.iter()) {
+                    ss.push(unify(arg1, arg2, ctx)?);
+                }
+            }
+            for v1 in &e1.variants {
+                let v2 = e2.variants.iter().find(|v2| v2.name == v1.name);
+                match (&v1.payload, v2.and_then(|v2| v2.payload.as_ref())) {
+                    (Some(p1), Some(p2)) => {
+                        let s = compose_many_subs_with_context(&ss);
+                        ss.push(unify(&p1.apply(&s), &p2.apply(&s), ctx)?);
+                    }
+                    (None, None) => (),
+                    _ => {
+                        return Err(Report::new(TypeError::mismatch(t1, t2)).attach_printable(
+                            format!("{}.{} payloads don't match", e1.name, v1.name),
+                        ))
+                    }
+                }
+            }
+            Ok(compose_many_subs_with_context(&ss))
+        }
+        // Traits are only satisfied by classes and structs that have an
+        // `impl` of the trait, having the same methods isn't enough.
+        (TypeKind::Trait(tr1), TypeKind::Trait(tr2)) if tr1.name == tr2.name => Ok(Subst::new()),
+        (TypeKind::Ref(alias), TypeKind::Trait(tr)) if ctx.implements(&alias.name, &tr.name) => {
+            Ok(Subst::new())
+        }
+        // Type aliases are expanded since they could be aliases of a class or
+        // struct that has an `impl` of the trait.
+        (TypeKind::Ref(alias), TypeKind::Trait(_)) if !ctx.is_class(&alias.name) => {
+            let alias_t = ctx.lookup_ref_and_instantiate(alias)?;
+            unify(&alias_t, t2, ctx)
+        }
+        (_, TypeKind::Trait(tr)) => {
+            Err(Report::new(TypeError::new(TypeErrorKind::NotImplemented {
+                t: t1.to_owned(),
+                name: tr.name.to_owned(),
+            })))
+        }
+        (TypeKind::Trait(TTrait { methods, .. }), _) => {
+            let elems = methods.iter().cloned().map(TObjElem::Prop).collect();
+            unify(&Type::from(TypeKind::Object(TObject { elems })), t2, ctx)
+        }
         (TypeKind::Ref(alias1), TypeKind::Ref(alias2)) => {
             if alias1.name == alias2.name {
                 match (&alias1.type_args, &alias2.type_args) {
@@ -534,13 +627,35 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
                         Ok(compose_many_subs_with_context(&ss))
                     }
                     (None, None) => Ok(Subst::default()),
-                    _ => Err(Report::new(TypeError).attach_printable("Alias type mismatch")),
+                    _ => Err(Report::new(TypeError::mismatch(t1, t2))
+                        .attach_printable("Alias type mismatch")),
                 }
+            } else if ctx.is_class(&alias1.name) && ctx.is_class(&alias2.name) {
+                // Classes are nominal, instances of a subclass have all of the
+                // members of the class it extends.
+                match ctx.is_subclass(&alias1.name, &alias2.name) {
+                    true => Ok(Subst::default()),
+                    false => Err(Report::new(TypeError::mismatch(t1, t2)).attach_printable(
+                        format!("{} doesn't extend {}", alias1.name, alias2.name),
+                    )),
+                }
+            } else if ctx.is_class(&alias1.name) {
+                let alias_t = ctx.lookup_ref_and_instantiate(alias2)?;
+                unify(t1, &alias_t, ctx)
             } else {
-                todo!("unify(): handle aliases that point to another alias")
+                let alias_t = ctx.lookup_ref_and_instantiate(alias1)?;
+                unify(&alias_t, t2, ctx)
             }
         }
         (_, TypeKind::Ref(alias)) => {
+            if ctx.is_class(&alias.name) {
+                return Err(
+                    Report::new(TypeError::mismatch(t1, t2)).attach_printable(format!(
+                        "only instances of {} can be used here",
+                        alias.name
+                    )),
+                );
+            }
             let alias_t = ctx.lookup_ref_and_instantiate(alias)?;
             unify(t1, &alias_t, ctx)
         }
@@ -559,23 +674,17 @@ pub fn unify(t1: &Type, t2: &Type, ctx: &Context) -> Result<Subst, TypeError> {
             (TKeyword::Null, TKeyword::Null) => Ok(Subst::new()),
             (TKeyword::Symbol, TKeyword::Symbol) => Ok(Subst::new()),
             (TKeyword::Undefined, TKeyword::Undefined) => Ok(Subst::new()),
-            // Is 'never' a subtype of all types?
-            (TKeyword::Never, TKeyword::Null) => Ok(Subst::new()),
-            _ => {
-                Err(Report::new(TypeError).attach_printable(format!("Can't unify {t1} with {t2}")))
-            }
+            _ => Err(Report::new(TypeError::mismatch(t1, t2))),
         },
         (v1, v2) => {
             if v1 == v2 {
                 Ok(Subst::new())
             } else {
-                Err(Report::new(TypeError).attach_printable("Unification failure"))
+                Err(Report::new(TypeError::mismatch(t1, t2))
+                    .attach_printable("Unification failure"))
             }
         }
     };
-    if result.is_err() {
-        println!("Can't unify {t1} with {t2}");
-    }
     result
 }
 
@@ -590,10 +699,7 @@ fn bind(tv: &TVar, t: &Type, rel: Relation, ctx: &Context) -> Result<Subst, Type
     // | occursCheck a t = throwError $ InfiniteType a t
     // | otherwise       = return $ Map.singleton a t
     match &t.kind {
-        TypeKind::Var(other_tv) if other_tv == tv => {
-            println!("other_tv = {other_tv:#?}, tv = {tv:#?}");
-            Ok(Subst::default())
-        }
+        TypeKind::Var(other_tv) if other_tv == tv => Ok(Subst::default()),
         _ => {
             if occurs_check(tv, t) {
                 // Union types are a special case since `t1` unifies trivially with `t1 | t2 | ... tn`
@@ -625,7 +731,9 @@ fn bind(tv: &TVar, t: &Type, rel: Relation, ctx: &Context) -> Result<Subst, Type
                     }
                 }
 
-                Err(Report::new(TypeError).attach_printable("InfiniteType"))
+                Err(Report::new(TypeError::new(TypeErrorKind::InfiniteType {
+                    t: t.to_owned(),
+                })))
             } else {
                 if let Some(c) = &tv.constraint {
                     // We only care whether the `unify()` call fails or not.  If it succeeds,
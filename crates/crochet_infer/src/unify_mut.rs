@@ -3,13 +3,17 @@ use error_stack::{Report, Result};
 
 use crate::context::Context;
 use crate::substitutable::Subst;
-use crate::type_error::TypeError;
+use crate::type_error::{TypeError, TypeErrorKind};
 
 pub fn unify_mut(t1: &Type, t2: &Type, _ctx: &Context) -> Result<Subst, TypeError> {
     if t1 == t2 {
-        println!("unify_mut: {t1} == {t2}");
         Ok(Subst::new())
     } else {
-        Err(Report::new(TypeError).attach_printable(format!("Couldn't unify {t1} and {t2}")))
+        Err(Report::new(TypeError::new(
+            TypeErrorKind::MutabilityMismatch {
+                expected: t2.to_owned(),
+                actual: t1.to_owned(),
+            },
+        )))
     }
 }
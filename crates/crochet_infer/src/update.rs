@@ -58,6 +58,20 @@ pub fn update_pattern(pattern: &mut Pattern, s: &Subst) {
         // TODO: update BindingIdent to have an optional .inferred_type property
         PatternKind::Is(IsPat { ident: _, is_id: _ }) => (),
         PatternKind::Wildcard(_) => (), // leaf node (also has no binding)
+        PatternKind::EnumVariant(EnumVariantPat { payload, .. }) => {
+            if let Some(payload) = payload {
+                update_pattern(payload, s);
+            }
+        }
+        PatternKind::Struct(StructPat { props, .. }) => {
+            props.iter_mut().for_each(|prop| match prop {
+                ObjectPatProp::KeyValue(KeyValuePatProp { value, .. }) => {
+                    update_pattern(value, s);
+                }
+                ObjectPatProp::Shorthand(_) => (),
+                ObjectPatProp::Rest(RestPat { arg }) => update_pattern(arg, s),
+            })
+        }
     }
 }
 
@@ -78,6 +92,21 @@ pub fn update_expr(expr: &mut Expr, s: &Subst) {
                 update_expr(&mut arg_or_spread.expr, s);
             });
         }
+        ExprKind::Pipe(Pipe {
+            arg, func, args, ..
+        }) => {
+            update_expr(arg, s);
+            update_expr(func, s);
+            args.iter_mut().for_each(|arg_or_spread| {
+                update_expr(&mut arg_or_spread.expr, s);
+            });
+        }
+        ExprKind::New(New { expr, args }) => {
+            update_expr(expr, s);
+            args.iter_mut().for_each(|arg_or_spread| {
+                update_expr(&mut arg_or_spread.expr, s);
+            });
+        }
         ExprKind::Fix(Fix { expr }) => {
             update_expr(expr, s);
         }
@@ -93,6 +122,39 @@ pub fn update_expr(expr: &mut Expr, s: &Subst) {
                 update_expr(alternate, s);
             }
         }
+        ExprKind::Do(Do { body }) => update_expr(body, s),
+        ExprKind::Try(Try {
+            body,
+            catch_param,
+            catch_body,
+            finally,
+        }) => {
+            update_expr(body, s);
+            if let Some(catch_param) = catch_param {
+                update_fn_param_pat(&mut catch_param.pat, s);
+            }
+            if let Some(catch_body) = catch_body {
+                update_expr(catch_body, s);
+            }
+            if let Some(finally) = finally {
+                update_expr(finally, s);
+            }
+        }
+        ExprKind::Throw(Throw { arg }) => update_expr(arg, s),
+        ExprKind::ForOf(ForOf {
+            pattern,
+            expr,
+            body,
+        }) => {
+            update_pattern(pattern, s);
+            update_expr(expr, s);
+            update_expr(body, s);
+        }
+        ExprKind::While(While { cond, body }) => {
+            update_expr(cond, s);
+            update_expr(body, s);
+        }
+        ExprKind::Break | ExprKind::Continue => (), // leaf node
         ExprKind::JSXElement(JSXElement {
             span: _,
             name: _,
@@ -179,6 +241,8 @@ pub fn update_expr(expr: &mut Expr, s: &Subst) {
             update_expr(obj, s);
             update_member_prop(prop, s);
         }
+        ExprKind::This => (),  // leaf node
+        ExprKind::Super => (), // leaf node
         ExprKind::Empty => (), // leaf node
         ExprKind::TemplateLiteral(TemplateLiteral { exprs, quasis: _ }) => {
             // NOTE: we don't bother with quasis because they're just another
@@ -254,6 +318,10 @@ fn update_fn_param_pat(pat: &mut Pattern, s: &Subst) {
         PatternKind::Lit(_) => panic!("literal patterns are not allowed in function params"),
         PatternKind::Is(_) => panic!("'is' patterns are not allowed in function params"),
         PatternKind::Wildcard(_) => panic!("wildcard patterns are not allowed in function params"),
+        PatternKind::EnumVariant(_) => {
+            panic!("enum variant patterns are not allowed in function params")
+        }
+        PatternKind::Struct(_) => panic!("struct patterns are not allowed in function params"),
     }
 }
 
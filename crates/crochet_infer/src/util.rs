@@ -41,7 +41,10 @@ fn get_mapping(t: &Type) -> HashMap<i32, Type> {
 
 pub fn close_over(s: &Subst, t: &Type, ctx: &Context) -> Type {
     let empty_env = Env::default();
-    normalize(&generalize(&empty_env, &t.to_owned().apply(s)), ctx)
+    let t = t.to_owned().apply(s);
+    let result = normalize(&generalize(&empty_env, &t), ctx);
+    ctx.with_trace(|trace| trace.generalize(&t, &result));
+    result
 }
 
 pub fn normalize(t: &Type, ctx: &Context) -> Type {
@@ -185,6 +188,43 @@ pub fn normalize(t: &Type, ctx: &Context) -> Type {
                     type_args,
                 })
             }
+            TypeKind::Enum(TEnum {
+                name,
+                type_args,
+                variants,
+            }) => {
+                let type_args = type_args
+                    .clone()
+                    .map(|params| params.iter().map(|t| norm_type(t, mapping, _ctx)).collect());
+                let variants = variants
+                    .iter()
+                    .map(|variant| TEnumVariant {
+                        name: variant.name.to_owned(),
+                        payload: variant
+                            .payload
+                            .as_ref()
+                            .map(|payload| norm_type(payload, mapping, _ctx)),
+                    })
+                    .collect();
+                TypeKind::Enum(TEnum {
+                    name: name.to_owned(),
+                    type_args,
+                    variants,
+                })
+            }
+            TypeKind::Trait(TTrait { name, methods }) => {
+                let methods = methods
+                    .iter()
+                    .map(|method| TProp {
+                        t: norm_type(&method.t, mapping, _ctx),
+                        ..method.to_owned()
+                    })
+                    .collect();
+                TypeKind::Trait(TTrait {
+                    name: name.to_owned(),
+                    methods,
+                })
+            }
             TypeKind::Tuple(types) => {
                 let types = types.iter().map(|t| norm_type(t, mapping, _ctx)).collect();
                 TypeKind::Tuple(types)
@@ -318,6 +358,14 @@ pub fn union_types(t1: &Type, t2: &Type) -> Type {
 pub fn union_many_types(ts: &[Type]) -> Type {
     let types: Vec<_> = ts.iter().flat_map(flatten_types).collect();
 
+    // `never` doesn't contribute anything to a union unless it's the only
+    // type, e.g. `if (c) { 5 } else { throw error }` is a `5`.
+    let is_never = |t: &Type| matches!(&t.kind, TypeKind::Keyword(TKeyword::Never));
+    let types: Vec<_> = match types.iter().all(is_never) {
+        true => types,
+        false => types.into_iter().filter(|t| !is_never(t)).collect(),
+    };
+
     let types_set: BTreeSet<_> = types.iter().cloned().collect();
 
     let keyword_types: BTreeSet<_> = types_set
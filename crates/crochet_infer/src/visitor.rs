@@ -40,6 +40,25 @@ pub trait Visitor {
             TypeKind::Generic(gen) => {
                 self.visit_children(&mut gen.t);
             }
+            TypeKind::Enum(TEnum {
+                type_args,
+                variants,
+                ..
+            }) => {
+                type_args
+                    .iter_mut()
+                    .flatten()
+                    .for_each(|t| self.visit_children(t));
+                variants
+                    .iter_mut()
+                    .filter_map(|TEnumVariant { payload, .. }| payload.as_mut())
+                    .for_each(|t| self.visit_children(t));
+            }
+            TypeKind::Trait(TTrait { methods, .. }) => {
+                methods
+                    .iter_mut()
+                    .for_each(|TProp { t, .. }| self.visit_children(t));
+            }
         }
 
         self.visit_type(t);
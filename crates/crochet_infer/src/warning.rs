@@ -0,0 +1,63 @@
+use crochet_ast::values::Span;
+use std::fmt;
+
+/// Problems that don't prevent a program from being type checked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WarningKind {
+    IrrefutableIfLet,
+    UnreachableMatchArm,
+    UnusedBinding { name: String },
+    ShadowedBinding { name: String, previous: Span },
+}
+
+impl WarningKind {
+    /// A stable identifier for this kind of warning that tools can match on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            WarningKind::IrrefutableIfLet => "W0001",
+            WarningKind::UnreachableMatchArm => "W0002",
+            WarningKind::UnusedBinding { .. } => "W0003",
+            WarningKind::ShadowedBinding { .. } => "W0004",
+        }
+    }
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarningKind::IrrefutableIfLet => {
+                write!(fmt, "irrefutable `if let` pattern, it will always match")
+            }
+            WarningKind::UnreachableMatchArm => write!(fmt, "unreachable match arm"),
+            WarningKind::UnusedBinding { name } => write!(fmt, "unused variable: `{name}`"),
+            WarningKind::ShadowedBinding { name, .. } => {
+                write!(fmt, "`{name}` shadows an earlier binding")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub span: Span,
+}
+
+impl Warning {
+    pub fn new(kind: WarningKind, span: &Span) -> Self {
+        Warning {
+            kind,
+            span: span.to_owned(),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "Warning[{}]: {}", self.code(), self.kind)
+    }
+}
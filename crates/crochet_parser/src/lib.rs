@@ -1,74 +1,137 @@
-use error_stack::{Report, Result};
+use error_stack::{AttachmentKind, FrameKind, Report, Result};
 use itertools::free::join;
-#[cfg(target_family = "wasm")]
-use std::ffi::CString;
-use std::os::raw::c_char;
 use unescape::unescape;
 
 use crochet_ast::values::*;
 
 mod parse_error;
+mod syntax_error;
 
 pub use parse_error::ParseError;
+use syntax_error::expected_for;
+pub use syntax_error::{SyntaxError, SyntaxErrorKind};
 
-#[link(wasm_import_module = "my_custom_module")]
-extern "C" {
-    fn _log(wasm_str: WasmString);
-}
-
-#[repr(C)]
-pub struct WasmString {
-    pub offset: *const c_char,
-    pub length: u32,
-}
-
-#[cfg(target_family = "wasm")]
-unsafe fn string_to_wasm_string(input: &str) -> WasmString {
-    let length = input.len() as u32;
-    let offset = CString::new(input).unwrap().into_raw();
-    WasmString { offset, length }
-}
-
-#[cfg(target_family = "wasm")]
-fn log(str: &str) {
-    unsafe {
-        _log(string_to_wasm_string(str));
+/// Parses `src` and fails if there are any syntax errors, see `parse_partial`
+/// for getting back the statements that could be parsed.  The report contains
+/// a `SyntaxError` for each error that was found.
+pub fn parse(src: &str) -> Result<Program, ParseError> {
+    let (program, errors) = parse_partial(src);
+
+    let mut errors = errors.into_iter();
+    match errors.next() {
+        None => Ok(program),
+        Some(error) => {
+            let mut report = Report::new(error).change_context(ParseError);
+            for error in errors {
+                report.extend_one(Report::new(error).change_context(ParseError));
+            }
+            Err(report)
+        }
     }
 }
 
-#[cfg(target_family = "unix")]
-fn log(str: &str) {
-    println!("{}", str);
-}
-
-pub fn parse(src: &str) -> Result<Program, ParseError> {
+/// Parses all of the statements in `src` that don't contain syntax errors.
+/// Every `ERROR` and `MISSING` node in the tree is reported, along with any
+/// statements that we don't know how to convert to the AST yet.
+pub fn parse_partial(src: &str) -> (Program, Vec<SyntaxError>) {
     let mut parser = tree_sitter::Parser::new();
     parser
         .set_language(tree_sitter_crochet::language())
         .expect("Error loading crochet language");
 
-    log("hello, world!");
-
     let tree = parser.parse(src, None).unwrap();
 
     let root = tree.root_node();
 
-    let kind = root.kind();
-    if kind == "program" {
+    let mut errors: Vec<SyntaxError> = vec![];
+    collect_syntax_errors(&root, src, &mut errors);
+
+    let mut body: Vec<Statement> = vec![];
+    if root.kind() == "program" {
         let mut cursor = root.walk();
 
         let children = root.children(&mut cursor);
 
-        let mut body: Vec<Statement> = vec![];
         for child in children {
-            println!("child.kind = {}", child.kind());
-            let mut stmts = parse_statement(&child, src)?;
-            body.append(&mut stmts);
+            if child.has_error() {
+                // The errors have already been collected above.
+                continue;
+            }
+            match parse_statement(&child, src) {
+                Ok(mut stmts) => body.append(&mut stmts),
+                Err(report) => errors.push(unsupported_node(&child, &report)),
+            }
         }
-
-        Ok(Program { body })
     } else {
-        Err(Report::new(ParseError).attach_printable("not implemented yet"))
+        errors.push(SyntaxError {
+            kind: SyntaxErrorKind::Unsupported {
+                message: format!("expected a program, found {}", root.kind()),
+            },
+            span: root.byte_range(),
+            hint: None,
+        });
+    }
+
+    errors.sort_by_key(|error| error.span.start);
+    (Program { body }, errors)
+}
+
+fn collect_syntax_errors(node: &tree_sitter::Node, src: &str, errors: &mut Vec<SyntaxError>) {
+    let hint = node.parent().map(|parent| expected_for(parent.kind()));
+
+    if node.is_error() {
+        let text = src.get(node.byte_range()).unwrap_or_default();
+        // Only the first line is shown since ERROR nodes can be large.
+        let text = text.lines().next().unwrap_or_default().trim().to_owned();
+        errors.push(SyntaxError {
+            kind: SyntaxErrorKind::Unexpected { text },
+            span: node.byte_range(),
+            hint,
+        });
+        // Any errors inside of an ERROR node are part of the same problem.
+        return;
+    }
+
+    if node.is_missing() {
+        errors.push(SyntaxError {
+            kind: SyntaxErrorKind::Missing {
+                token: node.kind().to_owned(),
+            },
+            span: node.byte_range(),
+            hint,
+        });
+        return;
+    }
+
+    if node.has_error() {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            collect_syntax_errors(&child, src, errors);
+        }
+    }
+}
+
+fn unsupported_node(node: &tree_sitter::Node, report: &Report<ParseError>) -> SyntaxError {
+    // Errors for nested nodes carry their own `SyntaxError` with a more
+    // precise span.
+    if let Some(error) = report.downcast_ref::<SyntaxError>() {
+        return error.to_owned();
+    }
+
+    let message = report
+        .frames()
+        .find_map(|frame| match frame.kind() {
+            FrameKind::Attachment(AttachmentKind::Printable(attachment)) => {
+                Some(attachment.to_string())
+            }
+            _ => None,
+        })
+        .unwrap_or_else(|| format!("unsupported {}", node.kind()));
+
+    SyntaxError {
+        kind: SyntaxErrorKind::Unsupported { message },
+        span: node.byte_range(),
+        hint: None,
     }
 }
 
@@ -106,6 +169,50 @@ fn parse_statement(node: &tree_sitter::Node, src: &str) -> Result<Vec<Statement>
                 type_params,
             }])
         }
+        "class_declaration" => {
+            let class = parse_class_declaration(node, src)?;
+            Ok(vec![Statement::ClassDecl {
+                span: node.byte_range(),
+                class,
+            }])
+        }
+        "enum_declaration" => {
+            let r#enum = parse_enum_declaration(node, src)?;
+            Ok(vec![Statement::EnumDecl {
+                span: node.byte_range(),
+                r#enum,
+            }])
+        }
+        "struct_declaration" => {
+            let r#struct = parse_struct_declaration(node, src)?;
+            Ok(vec![Statement::StructDecl {
+                span: node.byte_range(),
+                r#struct,
+            }])
+        }
+        "trait_declaration" => {
+            let r#trait = parse_trait_declaration(node, src)?;
+            Ok(vec![Statement::TraitDecl {
+                span: node.byte_range(),
+                r#trait,
+            }])
+        }
+        "impl_declaration" => {
+            let r#impl = parse_impl_declaration(node, src)?;
+            Ok(vec![Statement::ImplDecl {
+                span: node.byte_range(),
+                r#impl,
+            }])
+        }
+        "for_in_statement" | "while_statement" | "break_statement" | "continue_statement" => {
+            let expr = parse_loop_statement(node, src)?;
+            Ok(vec![Statement::Expr {
+                span: node.byte_range(),
+                expr: Box::from(expr),
+            }])
+        }
+        "import_statement" => parse_import_statement(node, src),
+        "export_statement" => parse_export_statement(node, src),
         "comment" => {
             Ok(vec![]) // ignore comments
         }
@@ -114,8 +221,6 @@ fn parse_statement(node: &tree_sitter::Node, src: &str) -> Result<Vec<Statement>
         _ => Err(Report::new(ParseError).attach_printable(format!("unhandled: {:#?}", node))),
     }
 
-    // $.export_statement,
-    // $.import_statement,
     // $.debugger_statement,
     // $.expression_statement,
     // $.declaration,
@@ -223,6 +328,466 @@ fn parse_declaration(
     Ok(vec![stmt])
 }
 
+fn ident_for_node(node: &tree_sitter::Node, src: &str) -> Result<Ident, ParseError> {
+    Ok(Ident {
+        span: node.byte_range(),
+        name: text_for_node(node, src)?,
+    })
+}
+
+fn parse_str(node: &tree_sitter::Node, src: &str) -> Result<Str, ParseError> {
+    match parse_literal(node, src)? {
+        Lit::Str(s) => Ok(s),
+        _ => Err(Report::new(ParseError).attach_printable("expected a string")),
+    }
+}
+
+/// Returns true if `node` has an anonymous child for `keyword`, e.g. the
+/// `type` in `import type {Foo} from "./foo";`.
+fn has_keyword(node: &tree_sitter::Node, keyword: &str) -> bool {
+    let mut cursor = node.walk();
+    let result = node
+        .children(&mut cursor)
+        .any(|child| !child.is_named() && child.kind() == keyword);
+    result
+}
+
+fn parse_import_statement(
+    node: &tree_sitter::Node,
+    src: &str,
+) -> Result<Vec<Statement>, ParseError> {
+    let source = node.child_by_field_name("source").unwrap();
+    let source = parse_str(&source, src)?;
+    let type_only = has_keyword(node, "type");
+
+    let mut specifiers: Vec<ImportSpecifier> = vec![];
+    let mut cursor = node.walk();
+    for clause in node.named_children(&mut cursor) {
+        match clause.kind() {
+            "import_clause" => (),
+            "string" | "comment" => continue,
+            kind => {
+                return Err(
+                    Report::new(ParseError).attach_printable(format!("unsupported import: {kind}"))
+                )
+            }
+        }
+
+        let mut cursor = clause.walk();
+        for child in clause.named_children(&mut cursor) {
+            match child.kind() {
+                "namespace_import" => {
+                    let local = child.named_child(0).unwrap();
+                    specifiers.push(ImportSpecifier::Namespace {
+                        span: child.byte_range(),
+                        local: ident_for_node(&local, src)?,
+                        is_type: type_only,
+                    });
+                }
+                "named_imports" => {
+                    let mut cursor = child.walk();
+                    for spec in child.named_children(&mut cursor) {
+                        if spec.kind() != "import_specifier" {
+                            continue;
+                        }
+                        let name = spec.child_by_field_name("name").unwrap();
+                        let name = ident_for_node(&name, src)?;
+                        let is_type = type_only || has_keyword(&spec, "type");
+                        let specifier = match spec.child_by_field_name("alias") {
+                            Some(alias) => ImportSpecifier::Named {
+                                span: spec.byte_range(),
+                                local: ident_for_node(&alias, src)?,
+                                imported: Some(name),
+                                is_type,
+                            },
+                            None => ImportSpecifier::Named {
+                                span: spec.byte_range(),
+                                local: name,
+                                imported: None,
+                                is_type,
+                            },
+                        };
+                        specifiers.push(specifier);
+                    }
+                }
+                "identifier" => {
+                    return Err(Report::new(ParseError)
+                        .attach_printable("default imports aren't supported"))
+                }
+                kind => {
+                    return Err(Report::new(ParseError)
+                        .attach_printable(format!("unsupported import clause: {kind}")))
+                }
+            }
+        }
+    }
+
+    Ok(vec![Statement::Import {
+        span: node.byte_range(),
+        specifiers,
+        source,
+    }])
+}
+
+fn parse_export_statement(
+    node: &tree_sitter::Node,
+    src: &str,
+) -> Result<Vec<Statement>, ParseError> {
+    if let Some(decl) = node.child_by_field_name("declaration") {
+        if has_keyword(node, "default") {
+            return Err(
+                Report::new(ParseError).attach_printable("default exports aren't supported")
+            );
+        }
+        return match decl.kind() {
+            "lexical_declaration"
+            | "type_alias_declaration"
+            | "ambient_declaration"
+            | "class_declaration"
+            | "enum_declaration"
+            | "struct_declaration"
+            | "trait_declaration" => {
+                let stmts = parse_statement(&decl, src)?;
+                Ok(stmts
+                    .into_iter()
+                    .map(|stmt| Statement::ExportDecl {
+                        span: node.byte_range(),
+                        decl: Box::from(stmt),
+                    })
+                    .collect())
+            }
+            kind => Err(Report::new(ParseError)
+                .attach_printable(format!("exporting a {kind} isn't supported"))),
+        };
+    }
+
+    if node.child_by_field_name("value").is_some() {
+        return Err(Report::new(ParseError).attach_printable("default exports aren't supported"));
+    }
+
+    let source = match node.child_by_field_name("source") {
+        Some(source) => Some(parse_str(&source, src)?),
+        None => None,
+    };
+    let type_only = has_keyword(node, "type");
+
+    let mut specifiers: Vec<ExportSpecifier> = vec![];
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "export_clause" => {
+                let mut cursor = child.walk();
+                for spec in child.named_children(&mut cursor) {
+                    if spec.kind() != "export_specifier" {
+                        continue;
+                    }
+                    let name = spec.child_by_field_name("name").unwrap();
+                    let exported = match spec.child_by_field_name("alias") {
+                        Some(alias) => Some(ident_for_node(&alias, src)?),
+                        None => None,
+                    };
+                    specifiers.push(ExportSpecifier::Named {
+                        span: spec.byte_range(),
+                        local: ident_for_node(&name, src)?,
+                        exported,
+                        is_type: type_only || has_keyword(&spec, "type"),
+                    });
+                }
+            }
+            // `export * as foo from "./foo";`
+            "namespace_export" => {
+                let exported = child.named_child(0).unwrap();
+                specifiers.push(ExportSpecifier::All {
+                    span: child.byte_range(),
+                    exported: Some(ident_for_node(&exported, src)?),
+                });
+            }
+            // `export * from "./foo";`
+            "*" => specifiers.push(ExportSpecifier::All {
+                span: child.byte_range(),
+                exported: None,
+            }),
+            "=" | "as" => {
+                return Err(Report::new(ParseError).attach_printable(format!(
+                    "unsupported export: '{}'",
+                    text_for_node(node, src)?
+                )))
+            }
+            _ => (),
+        }
+    }
+
+    Ok(vec![Statement::Export {
+        span: node.byte_range(),
+        specifiers,
+        source,
+    }])
+}
+
+fn parse_class_declaration(node: &tree_sitter::Node, src: &str) -> Result<Class, ParseError> {
+    if node.child_by_field_name("type_parameters").is_some() {
+        return Err(Report::new(ParseError).attach_printable("generic classes aren't supported"));
+    }
+
+    let name = node.child_by_field_name("name").unwrap();
+
+    let mut super_class: Option<Ident> = None;
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        if child.kind() != "class_heritage" {
+            continue;
+        }
+        let mut cursor = child.walk();
+        for clause in child.named_children(&mut cursor) {
+            if clause.kind() != "extends_clause" {
+                return Err(Report::new(ParseError)
+                    .attach_printable("`implements` clauses aren't supported"));
+            }
+            let mut cursor = clause.walk();
+            let values: Vec<_> = clause
+                .children_by_field_name("value", &mut cursor)
+                .collect();
+            match values.as_slice() {
+                [value] if value.kind() == "identifier" => {
+                    super_class = Some(ident_for_node(value, src)?);
+                }
+                _ => {
+                    return Err(Report::new(ParseError)
+                        .attach_printable("classes can only extend a single class by name"))
+                }
+            }
+        }
+    }
+
+    let body = node.child_by_field_name("body").unwrap();
+    let mut cursor = body.walk();
+    let body = body
+        .named_children(&mut cursor)
+        .filter(|member| member.kind() != "comment")
+        .map(|member| parse_class_member(&member, src))
+        .collect::<Result<Vec<_>, ParseError>>()?;
+
+    Ok(Class {
+        span: node.byte_range(),
+        ident: ident_for_node(&name, src)?,
+        super_class,
+        body,
+    })
+}
+
+fn parse_enum_declaration(node: &tree_sitter::Node, src: &str) -> Result<Enum, ParseError> {
+    let name = node.child_by_field_name("name").unwrap();
+    let type_params = parse_type_params_for_node(node, src)?;
+
+    let body = node.child_by_field_name("body").unwrap();
+    let mut cursor = body.walk();
+    let variants = body
+        .named_children(&mut cursor)
+        .filter(|variant| variant.kind() != "comment")
+        .map(|variant| {
+            let name = variant.child_by_field_name("name").unwrap();
+            let payload = match variant.child_by_field_name("payload") {
+                Some(payload) => Some(parse_type_ann(&payload, src)?),
+                None => None,
+            };
+            Ok(EnumVariant {
+                span: variant.byte_range(),
+                ident: ident_for_node(&name, src)?,
+                payload,
+            })
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+
+    Ok(Enum {
+        span: node.byte_range(),
+        ident: ident_for_node(&name, src)?,
+        type_params,
+        variants,
+    })
+}
+
+fn parse_struct_declaration(node: &tree_sitter::Node, src: &str) -> Result<Struct, ParseError> {
+    let name = node.child_by_field_name("name").unwrap();
+
+    let body = node.child_by_field_name("body").unwrap();
+    let mut cursor = body.walk();
+    let fields = body
+        .named_children(&mut cursor)
+        .filter(|field| field.kind() != "comment")
+        .map(|field| {
+            let key = field.child_by_field_name("name").unwrap();
+            let type_ann = field.child_by_field_name("type").unwrap();
+            Ok(StructField {
+                span: field.byte_range(),
+                key: ident_for_node(&key, src)?,
+                type_ann: parse_type_ann(&type_ann, src)?,
+                is_mutable: field.child_by_field_name("mut").is_some(),
+            })
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+
+    Ok(Struct {
+        span: node.byte_range(),
+        ident: ident_for_node(&name, src)?,
+        fields,
+    })
+}
+
+fn parse_trait_declaration(node: &tree_sitter::Node, src: &str) -> Result<Trait, ParseError> {
+    let name = node.child_by_field_name("name").unwrap();
+
+    let body = node.child_by_field_name("body").unwrap();
+    let mut cursor = body.walk();
+    let methods = body
+        .named_children(&mut cursor)
+        .filter(|member| member.kind() != "comment")
+        .map(|member| {
+            if has_keyword(&member, "static") {
+                return Err(Report::new(ParseError)
+                    .attach_printable("static trait methods aren't supported"));
+            }
+            if member.child_by_field_name("type_parameters").is_some() {
+                return Err(Report::new(ParseError)
+                    .attach_printable("generic trait methods aren't supported"));
+            }
+            let name = member.child_by_field_name("name").unwrap();
+            let params = member.child_by_field_name("parameters").unwrap();
+            let return_type = match member.child_by_field_name("return_type") {
+                Some(return_type) => parse_type_ann(&return_type, src)?,
+                None => {
+                    return Err(Report::new(ParseError).attach_printable(format!(
+                        "trait method '{}' must have a return type",
+                        text_for_node(&name, src)?
+                    )))
+                }
+            };
+            // Only methods with a default implementation have a body.
+            let body = match member.child_by_field_name("body") {
+                Some(body) => Some(Box::from(parse_block_statement(&body, src)?)),
+                None => None,
+            };
+            Ok(TraitMethod {
+                span: member.byte_range(),
+                key: ident_for_node(&name, src)?,
+                params: parse_formal_parameters(&params, src)?,
+                return_type,
+                body,
+            })
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+
+    Ok(Trait {
+        span: node.byte_range(),
+        ident: ident_for_node(&name, src)?,
+        methods,
+    })
+}
+
+fn parse_impl_declaration(node: &tree_sitter::Node, src: &str) -> Result<Impl, ParseError> {
+    let r#trait = node.child_by_field_name("trait").unwrap();
+    let type_ident = node.child_by_field_name("type").unwrap();
+
+    let body = node.child_by_field_name("body").unwrap();
+    let mut cursor = body.walk();
+    let methods = body
+        .named_children(&mut cursor)
+        .filter(|member| member.kind() != "comment")
+        .map(|member| match parse_class_member(&member, src)? {
+            ClassMember::Method(method) if !method.is_static => Ok(method),
+            _ => Err(Report::new(ParseError)
+                .attach_printable("impls can only contain non-static methods")),
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+
+    Ok(Impl {
+        span: node.byte_range(),
+        r#trait: ident_for_node(&r#trait, src)?,
+        type_ident: ident_for_node(&type_ident, src)?,
+        methods,
+    })
+}
+
+fn parse_class_member(node: &tree_sitter::Node, src: &str) -> Result<ClassMember, ParseError> {
+    if !matches!(node.kind(), "method_definition" | "public_field_definition") {
+        return Err(Report::new(ParseError)
+            .attach_printable(format!("unsupported class member: {}", node.kind())));
+    }
+
+    let is_static = has_keyword(node, "static");
+    let name = node.child_by_field_name("name").unwrap();
+    if name.kind() != "property_identifier" {
+        return Err(Report::new(ParseError).attach_printable(format!(
+            "unsupported class member name: '{}'",
+            text_for_node(&name, src)?
+        )));
+    }
+    let key = ident_for_node(&name, src)?;
+
+    match node.kind() {
+        "method_definition" => {
+            if ["get", "set", "*"]
+                .iter()
+                .any(|keyword| has_keyword(node, keyword))
+            {
+                return Err(Report::new(ParseError)
+                    .attach_printable("getters, setters, and generators aren't supported"));
+            }
+
+            let params = node.child_by_field_name("parameters").unwrap();
+            let params = parse_formal_parameters(&params, src)?;
+            let body = node.child_by_field_name("body").unwrap();
+            let body = Box::from(parse_block_statement(&body, src)?);
+
+            if key.name == "constructor" && !is_static {
+                return Ok(ClassMember::Constructor(Constructor {
+                    span: node.byte_range(),
+                    params,
+                    body,
+                }));
+            }
+
+            let return_type = match node.child_by_field_name("return_type") {
+                Some(return_type) => Some(parse_type_ann(&return_type, src)?),
+                None => None,
+            };
+
+            Ok(ClassMember::Method(ClassMethod {
+                span: node.byte_range(),
+                key,
+                lambda: Lambda {
+                    params,
+                    body,
+                    is_async: has_keyword(node, "async"),
+                    return_type,
+                    type_params: parse_type_params_for_node(node, src)?,
+                },
+                is_static,
+            }))
+        }
+        "public_field_definition" => {
+            let type_ann = match node.child_by_field_name("type") {
+                Some(type_ann) => Some(parse_type_ann(&type_ann, src)?),
+                None => None,
+            };
+            let value = match node.child_by_field_name("value") {
+                Some(value) => Some(Box::from(parse_expression(&value, src)?)),
+                None => None,
+            };
+
+            Ok(ClassMember::Prop(ClassProp {
+                span: node.byte_range(),
+                key,
+                value,
+                type_ann,
+                is_static,
+                is_mutable: has_keyword(node, "mut"),
+                is_optional: has_keyword(node, "?"),
+            }))
+        }
+        _ => unreachable!("other class members were rejected above"),
+    }
+}
+
 fn parse_pattern(node: &tree_sitter::Node, src: &str) -> Result<Pattern, ParseError> {
     if node.has_error() {
         // TODO: get actual error node so that we can report where the error is
@@ -437,6 +1002,27 @@ fn parse_block_statement(node: &tree_sitter::Node, src: &str) -> Result<Expr, Pa
     let mut stmts: Vec<Statement> = vec![];
 
     for (i, child) in node.named_children(&mut cursor).into_iter().enumerate() {
+        let decl = match child.kind() {
+            "import_statement" | "export_statement" => Some("imports and exports"),
+            "class_declaration" => Some("classes"),
+            "enum_declaration" => Some("enums"),
+            "struct_declaration" => Some("structs"),
+            "trait_declaration" | "impl_declaration" => Some("traits and impls"),
+            _ => None,
+        };
+        if let Some(decl) = decl {
+            let message = format!("{decl} are only allowed at the top level");
+            return Err(Report::new(ParseError)
+                .attach(SyntaxError {
+                    kind: SyntaxErrorKind::Unsupported {
+                        message: message.to_owned(),
+                    },
+                    span: child.byte_range(),
+                    hint: None,
+                })
+                .attach_printable(message));
+        }
+
         let is_last = i == child_count - 1;
         if is_last {
             // This is the only place where a named `expression` node
@@ -478,6 +1064,16 @@ fn parse_block_statement(node: &tree_sitter::Node, src: &str) -> Result<Expr, Pa
                 todo!("decide how to handle type decls within BlockStatements")
             }
             Statement::Expr { expr, .. } => *expr.to_owned(),
+            Statement::ClassDecl { .. }
+            | Statement::EnumDecl { .. }
+            | Statement::StructDecl { .. }
+            | Statement::TraitDecl { .. }
+            | Statement::ImplDecl { .. }
+            | Statement::Import { .. }
+            | Statement::Export { .. }
+            | Statement::ExportDecl { .. } => {
+                unreachable!("declarations and module statements were rejected above")
+            }
         },
         None => Expr {
             span: 0..0,
@@ -524,6 +1120,16 @@ fn parse_block_statement(node: &tree_sitter::Node, src: &str) -> Result<Expr, Pa
                     inferred_type: None,
                 }
             }
+            Statement::ClassDecl { .. }
+            | Statement::EnumDecl { .. }
+            | Statement::StructDecl { .. }
+            | Statement::TraitDecl { .. }
+            | Statement::ImplDecl { .. }
+            | Statement::Import { .. }
+            | Statement::Export { .. }
+            | Statement::ExportDecl { .. } => {
+                unreachable!("declarations and module statements were rejected above")
+            }
         }
     });
 
@@ -674,19 +1280,68 @@ fn parse_expression(node: &tree_sitter::Node, src: &str) -> Result<Expr, ParseEr
 
             ExprKind::BinaryExpr(BinaryExpr { left, op, right })
         }
+        "pipeline_expression" => {
+            let arg = node.child_by_field_name("left").unwrap();
+            let arg = Box::from(parse_expression(&arg, src)?);
+            let func = node.child_by_field_name("right").unwrap();
+            let func = parse_expression(&func, src)?;
+
+            let is_placeholder = |arg: &ExprOrSpread| match &arg.expr.kind {
+                ExprKind::Ident(ident) => ident.name == "_",
+                _ => false,
+            };
+
+            match func {
+                // `x |> f(a, _)` passes `x` in place of the `_` placeholder
+                // instead of partially applying `f`.
+                Expr {
+                    kind: ExprKind::App(App { lam, mut args }),
+                    ..
+                } if args.iter().any(is_placeholder) => {
+                    if args.iter().filter(|arg| is_placeholder(arg)).count() > 1 {
+                        return Err(Report::new(ParseError)
+                            .attach_printable("pipelines can only have one `_` placeholder"));
+                    }
+                    let placeholder = args.iter().position(is_placeholder).unwrap();
+                    if args.remove(placeholder).spread.is_some() {
+                        return Err(Report::new(ParseError)
+                            .attach_printable("the `_` placeholder in pipelines can't be spread"));
+                    }
+
+                    ExprKind::Pipe(Pipe {
+                        arg,
+                        func: lam,
+                        args,
+                        placeholder,
+                    })
+                }
+                func => ExprKind::Pipe(Pipe {
+                    arg,
+                    func: Box::from(func),
+                    args: vec![],
+                    placeholder: 0,
+                }),
+            }
+        }
         "unary_expression" => {
             let operator = node.child_by_field_name("operator").unwrap();
             let operator = text_for_node(&operator, src)?;
             let arg = node.child_by_field_name("argument").unwrap();
             let arg = Box::from(parse_expression(&arg, src)?);
 
-            // choice("!", "~", "-", "+", "typeof", "void", "delete")
-            let op = match operator.as_str() {
-                "-" => UnaryOp::Minus,
-                _ => todo!("Unhandle operator: {operator}"),
-            };
+            // `throw` is parsed as a unary operator so that it can be used
+            // anywhere an expression is expected.
+            if operator == "throw" {
+                ExprKind::Throw(Throw { arg })
+            } else {
+                // choice("!", "~", "-", "+", "typeof", "void", "delete")
+                let op = match operator.as_str() {
+                    "-" => UnaryOp::Minus,
+                    _ => todo!("Unhandle operator: {operator}"),
+                };
 
-            ExprKind::UnaryExpr(UnaryExpr { arg, op })
+                ExprKind::UnaryExpr(UnaryExpr { arg, op })
+            }
         }
         "parenthesized_expression" => {
             let expr = node.child(1).unwrap();
@@ -723,26 +1378,7 @@ fn parse_expression(node: &tree_sitter::Node, src: &str) -> Result<Expr, ParseEr
                 }
             }
 
-            let args = args
-                .into_iter()
-                .map(|arg| {
-                    if arg.kind() == "spread_element" {
-                        let spread = arg.child(0).unwrap();
-                        let arg = arg.child(1).unwrap();
-                        let expr = parse_expression(&arg, src)?;
-                        Ok(ExprOrSpread {
-                            spread: Some(spread.byte_range()),
-                            expr: Box::from(expr),
-                        })
-                    } else {
-                        let expr = parse_expression(&arg, src)?;
-                        Ok(ExprOrSpread {
-                            spread: None,
-                            expr: Box::from(expr),
-                        })
-                    }
-                })
-                .collect::<Result<Vec<_>, ParseError>>()?;
+            let args = parse_arguments(args, src)?;
 
             // TODO: handle template string
             ExprKind::App(App {
@@ -750,6 +1386,31 @@ fn parse_expression(node: &tree_sitter::Node, src: &str) -> Result<Expr, ParseEr
                 args,
             })
         }
+        "new_expression" => {
+            if node.child_by_field_name("type_arguments").is_some() {
+                return Err(Report::new(ParseError)
+                    .attach_printable("type arguments in `new` expressions aren't supported"));
+            }
+            let expr = node.child_by_field_name("constructor").unwrap();
+            let expr = parse_expression(&expr, src)?;
+
+            // The parens are optional, e.g. `new Foo`
+            let args = match node.child_by_field_name("arguments") {
+                Some(args) => {
+                    let mut cursor = args.walk();
+                    let args = args.named_children(&mut cursor);
+                    parse_arguments(args, src)?
+                }
+                None => vec![],
+            };
+
+            ExprKind::New(New {
+                expr: Box::from(expr),
+                args,
+            })
+        }
+        "this" => ExprKind::This,
+        "super" => ExprKind::Super,
         "identifier" => {
             let span = node.byte_range();
             let name = src.get(span.clone()).unwrap().to_owned();
@@ -884,6 +1545,9 @@ fn parse_expression(node: &tree_sitter::Node, src: &str) -> Result<Expr, ParseEr
         "if_expression" => {
             return parse_if_expression(node, src);
         }
+        "try_statement" => {
+            return parse_try_expression(node, src);
+        }
         "let_expression" => {
             let pat = node.child_by_field_name("name").unwrap();
             let pat = parse_refutable_pattern(&pat, src)?;
@@ -896,8 +1560,11 @@ fn parse_expression(node: &tree_sitter::Node, src: &str) -> Result<Expr, ParseEr
             })
         }
         "do_expression" => {
-            let child = node.named_child(0).unwrap();
-            return parse_block_statement(&child, src);
+            let body = node.named_child(0).unwrap();
+            let body = parse_block_statement(&body, src)?;
+            ExprKind::Do(Do {
+                body: Box::from(body),
+            })
         }
         "match_expression" => {
             let expr = node.child_by_field_name("expression").unwrap();
@@ -973,6 +1640,30 @@ fn parse_expression(node: &tree_sitter::Node, src: &str) -> Result<Expr, ParseEr
     // $.call_expression
 }
 
+fn parse_arguments<'a>(
+    args: impl Iterator<Item = tree_sitter::Node<'a>>,
+    src: &str,
+) -> Result<Vec<ExprOrSpread>, ParseError> {
+    args.map(|arg| {
+        if arg.kind() == "spread_element" {
+            let spread = arg.child(0).unwrap();
+            let arg = arg.child(1).unwrap();
+            let expr = parse_expression(&arg, src)?;
+            Ok(ExprOrSpread {
+                spread: Some(spread.byte_range()),
+                expr: Box::from(expr),
+            })
+        } else {
+            let expr = parse_expression(&arg, src)?;
+            Ok(ExprOrSpread {
+                spread: None,
+                expr: Box::from(expr),
+            })
+        }
+    })
+    .collect()
+}
+
 fn parse_arm(node: &tree_sitter::Node, src: &str) -> Result<Arm, ParseError> {
     let pat = node.child_by_field_name("pattern").unwrap();
     let body = node.child_by_field_name("value").unwrap();
@@ -1100,6 +1791,33 @@ fn parse_refutable_pattern(node: &tree_sitter::Node, src: &str) -> Result<Patter
                 arg: Box::from(arg),
             })
         }
+        "refutable_enum_variant_pattern" => {
+            let r#enum = child.child_by_field_name("enum").unwrap();
+            let variant = child.child_by_field_name("variant").unwrap();
+            let payload = match child.child_by_field_name("payload") {
+                Some(payload) => Some(Box::from(parse_refutable_pattern(&payload, src)?)),
+                None => None,
+            };
+
+            PatternKind::EnumVariant(EnumVariantPat {
+                r#enum: ident_for_node(&r#enum, src)?,
+                variant: ident_for_node(&variant, src)?,
+                payload,
+            })
+        }
+        "refutable_struct_pattern" => {
+            let ident = child.child_by_field_name("struct").unwrap();
+            let body = child.child_by_field_name("body").unwrap();
+            let props = match parse_refutable_pattern(&body, src)?.kind {
+                PatternKind::Object(ObjectPat { props, .. }) => props,
+                _ => unreachable!("the body of a struct pattern is always an object pattern"),
+            };
+
+            PatternKind::Struct(StructPat {
+                ident: ident_for_node(&ident, src)?,
+                props,
+            })
+        }
         "refutable_is_pattern" => {
             let left = child.named_child(0).unwrap();
             let right = child.named_child(1).unwrap();
@@ -1164,6 +1882,119 @@ fn parse_if_expression(node: &tree_sitter::Node, src: &str) -> Result<Expr, Pars
     })
 }
 
+fn parse_try_expression(node: &tree_sitter::Node, src: &str) -> Result<Expr, ParseError> {
+    assert_eq!(node.kind(), "try_statement");
+
+    let body = node.child_by_field_name("body").unwrap();
+    let body = parse_block_statement(&body, src)?;
+
+    let (catch_param, catch_body) = match node.child_by_field_name("handler") {
+        Some(handler) => {
+            let catch_param = match handler.child_by_field_name("parameter") {
+                Some(param) => {
+                    let type_ann = match handler.child_by_field_name("type") {
+                        Some(type_ann) => Some(parse_type_ann(&type_ann, src)?),
+                        None => None,
+                    };
+                    Some(EFnParam {
+                        pat: parse_pattern(&param, src)?,
+                        type_ann,
+                        optional: false,
+                        mutable: false,
+                    })
+                }
+                None => None,
+            };
+            let catch_body = handler.child_by_field_name("body").unwrap();
+            let catch_body = parse_block_statement(&catch_body, src)?;
+            (catch_param, Some(Box::from(catch_body)))
+        }
+        None => (None, None),
+    };
+
+    let finally = match node.child_by_field_name("finalizer") {
+        Some(finalizer) => {
+            let body = finalizer.child_by_field_name("body").unwrap();
+            Some(Box::from(parse_block_statement(&body, src)?))
+        }
+        None => None,
+    };
+
+    let kind = ExprKind::Try(Try {
+        body: Box::from(body),
+        catch_param,
+        catch_body,
+        finally,
+    });
+
+    Ok(Expr {
+        span: node.byte_range(),
+        kind,
+        inferred_type: None,
+    })
+}
+
+fn parse_loop_statement(node: &tree_sitter::Node, src: &str) -> Result<Expr, ParseError> {
+    let kind = match node.kind() {
+        "for_in_statement" => {
+            if has_keyword(node, "await") {
+                return Err(
+                    Report::new(ParseError).attach_printable("`for await` loops aren't supported")
+                );
+            }
+            let operator = node.child_by_field_name("operator").unwrap();
+            if operator.kind() != "of" {
+                return Err(Report::new(ParseError)
+                    .attach_printable("`for ... in` loops aren't supported, use `for ... of`"));
+            }
+            if node.child_by_field_name("kind").is_none() {
+                return Err(Report::new(ParseError)
+                    .attach_printable("`for ... of` loops must declare their binding with `let`"));
+            }
+
+            let pattern = node.child_by_field_name("left").unwrap();
+            let pattern = parse_pattern(&pattern, src)?;
+            let expr = node.child_by_field_name("right").unwrap();
+            let expr = parse_expression(&expr, src)?;
+            let body = node.child_by_field_name("body").unwrap();
+            let body = parse_block_statement(&body, src)?;
+
+            ExprKind::ForOf(ForOf {
+                pattern,
+                expr: Box::from(expr),
+                body: Box::from(body),
+            })
+        }
+        "while_statement" => {
+            let cond = node.child_by_field_name("condition").unwrap();
+            let cond = parse_expression(&cond, src)?;
+            let body = node.child_by_field_name("body").unwrap();
+            let body = parse_block_statement(&body, src)?;
+
+            ExprKind::While(While {
+                cond: Box::from(cond),
+                body: Box::from(body),
+            })
+        }
+        "break_statement" | "continue_statement" => {
+            if node.child_by_field_name("label").is_some() {
+                return Err(Report::new(ParseError).attach_printable("labels aren't supported"));
+            }
+            match node.kind() {
+                "break_statement" => ExprKind::Break,
+                _ => ExprKind::Continue,
+            }
+        }
+        kind => panic!("Unexpected loop statement kind: '{kind}'"),
+    };
+
+    Ok(Expr {
+        span: node.byte_range(),
+        kind,
+        inferred_type: None,
+    })
+}
+
 fn parse_type_ann(node: &tree_sitter::Node, src: &str) -> Result<TypeAnn, ParseError> {
     if node.has_error() {
         // TODO: get actual error node so that we can report where the error is
@@ -1344,10 +2175,7 @@ fn parse_type_ann(node: &tree_sitter::Node, src: &str) -> Result<TypeAnn, ParseE
             let types = node
                 .named_children(&mut cursor)
                 .into_iter()
-                .map(|elem| {
-                    println!("parsing elem: {elem:#?}");
-                    parse_type_ann(&elem, src)
-                })
+                .map(|elem| parse_type_ann(&elem, src))
                 .collect::<Result<Vec<_>, ParseError>>()?;
 
             TypeAnnKind::Tuple(TupleType {
@@ -1812,18 +2640,44 @@ mod tests {
         match parse("(...a, ...b) => true") {
             Ok(_) => panic!("expected test parse() to return an error"),
             Err(report) => {
-                assert_eq!(
-                    messages(&report),
-                    vec![
-                        "failed to parse: '(...a, ...b) => true'",
-                        "Location",
-                        "ParseError"
-                    ]
-                );
+                // `parse()` changes the context of each `SyntaxError` to
+                // `ParseError`, each of those contexts has its own location.
+                let messages = messages(&report);
+                assert_eq!(messages.len(), 4);
+                assert_eq!(messages[..3], vec!["Location", "ParseError", "Location"]);
+                assert!(messages[3].starts_with("SyntaxError: unexpected"));
             }
         }
     }
 
+    #[test]
+    fn report_all_syntax_errors() {
+        let src = "let a = 5;\nlet b = ;\nlet c = 10;\nlet d = (;\n";
+        let (program, errors) = parse_partial(src);
+
+        // Statements without errors are still returned.
+        assert_eq!(program.body.len(), 2);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].span.start >= 11 && errors[0].span.end <= 21);
+        assert!(errors[1].span.start >= 33);
+        assert!(errors.iter().all(|error| error.hint.is_some()));
+    }
+
+    #[test]
+    fn report_missing_tokens() {
+        let (_, errors) = parse_partial("let x = (5 + 10;");
+
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| error.kind.to_owned())
+                .collect::<Vec<_>>(),
+            vec![SyntaxErrorKind::Missing {
+                token: String::from(")")
+            }]
+        );
+    }
+
     // #[test]
     // #[ignore]
     // fn optional_params_must_appear_last() {
@@ -2124,6 +2978,527 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn imports() {
+        let src = r#"
+        import {a, b as c, type T} from "./foo";
+        import type {U} from "./types";
+        import * as bar from "./bar";
+        "#;
+        let program = parse(src).unwrap();
+
+        let imports: Vec<_> = program
+            .body
+            .iter()
+            .map(|stmt| match stmt {
+                Statement::Import {
+                    specifiers, source, ..
+                } => (
+                    source.value.as_str(),
+                    specifiers
+                        .iter()
+                        .map(|specifier| (specifier.local().name.as_str(), specifier.is_type()))
+                        .collect::<Vec<_>>(),
+                ),
+                stmt => panic!("expected an import, found {stmt:?}"),
+            })
+            .collect();
+
+        assert_eq!(
+            imports,
+            vec![
+                ("./foo", vec![("a", false), ("c", false), ("T", true)]),
+                ("./types", vec![("U", true)]),
+                ("./bar", vec![("bar", false)]),
+            ]
+        );
+        assert!(matches!(
+            &program.body[0],
+            Statement::Import { specifiers, .. } if matches!(
+                &specifiers[1],
+                ImportSpecifier::Named { imported: Some(Ident { name, .. }), .. } if name == "b"
+            )
+        ));
+    }
+
+    #[test]
+    fn exports() {
+        let src = r#"
+        export let x = 5;
+        export type Point = {x: number, y: number};
+        export {x as y, type Point as P};
+        export {a} from "./foo";
+        export * from "./bar";
+        export * as baz from "./baz";
+        "#;
+        let program = parse(src).unwrap();
+
+        assert!(matches!(
+            &program.body[0],
+            Statement::ExportDecl { decl, .. } if matches!(decl.as_ref(), Statement::VarDecl { .. })
+        ));
+        assert!(matches!(
+            &program.body[1],
+            Statement::ExportDecl { decl, .. } if matches!(decl.as_ref(), Statement::TypeDecl { .. })
+        ));
+        assert_eq!(
+            program.exported_names(),
+            vec![
+                (String::from("x"), String::from("x")),
+                (String::from("Point"), String::from("Point")),
+                (String::from("y"), String::from("x")),
+                (String::from("P"), String::from("Point")),
+            ]
+        );
+
+        let sources: Vec<_> = program.body[3..]
+            .iter()
+            .map(|stmt| match stmt {
+                Statement::Export {
+                    specifiers,
+                    source: Some(source),
+                    ..
+                } => (source.value.as_str(), specifiers.len()),
+                stmt => panic!("expected a re-export, found {stmt:?}"),
+            })
+            .collect();
+        assert_eq!(sources, vec![("./foo", 1), ("./bar", 1), ("./baz", 1)]);
+        assert!(matches!(
+            &program.body[5],
+            Statement::Export { specifiers, .. } if matches!(
+                &specifiers[0],
+                ExportSpecifier::All { exported: Some(Ident { name, .. }), .. } if name == "baz"
+            )
+        ));
+        assert!(!program.exports_all());
+    }
+
+    #[test]
+    fn imports_are_only_allowed_at_the_top_level() {
+        let (_, errors) = parse_partial(r#"let f = () => { import {a} from "./a"; a };"#);
+
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn classes() {
+        let src = r#"
+        class Point extends Shape {
+            x: number;
+            mut y: number = 0;
+            static origin?: Point;
+            constructor(x: number) {
+                super();
+                this.x = x;
+            }
+            async length(): Promise<number> {
+                this.x
+            }
+        }
+        let p = new Point(5);
+        export class Empty {}
+        "#;
+        let program = parse(src).unwrap();
+
+        let class = match &program.body[0] {
+            Statement::ClassDecl { class, .. } => class,
+            stmt => panic!("expected a class, found {stmt:?}"),
+        };
+        assert_eq!(class.ident.name, "Point");
+        assert_eq!(
+            class.super_class.as_ref().map(|ident| ident.name.as_str()),
+            Some("Shape")
+        );
+
+        let props: Vec<_> = class
+            .body
+            .iter()
+            .filter_map(|member| match member {
+                ClassMember::Prop(prop) => Some((
+                    prop.key.name.as_str(),
+                    prop.is_mutable,
+                    prop.is_static,
+                    prop.is_optional,
+                    prop.value.is_some(),
+                )),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            props,
+            vec![
+                ("x", false, false, false, false),
+                ("y", true, false, false, true),
+                ("origin", false, true, true, false),
+            ]
+        );
+
+        assert_eq!(class.constructor().unwrap().params.len(), 1);
+        assert!(class.body.iter().any(|member| matches!(
+            member,
+            ClassMember::Method(ClassMethod { key, lambda, is_static: false, .. })
+                if key.name == "length" && lambda.is_async && lambda.return_type.is_some()
+        )));
+
+        assert!(matches!(
+            &program.body[1],
+            Statement::VarDecl { init: Some(init), .. } if matches!(
+                &init.kind,
+                ExprKind::New(New { args, .. }) if args.len() == 1
+            )
+        ));
+        assert_eq!(
+            program.exported_names(),
+            vec![(String::from("Empty"), String::from("Empty"))]
+        );
+    }
+
+    #[test]
+    fn classes_are_only_allowed_at_the_top_level() {
+        let (_, errors) = parse_partial("let f = () => { class Foo {} 5 };");
+
+        assert_eq!(
+            errors,
+            vec![SyntaxError {
+                kind: SyntaxErrorKind::Unsupported {
+                    message: String::from("classes are only allowed at the top level"),
+                },
+                span: 16..28,
+                hint: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn generic_classes_are_unsupported() {
+        let (_, errors) = parse_partial("class Box<T> { value: T; }");
+
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn try_catch() {
+        let src = r#"
+        let result = try {
+            parse(input)
+        } catch (e: string) {
+            0
+        } finally {
+            cleanup()
+        };
+        let without_param = try { parse(input) } catch { 0 };
+        "#;
+        let program = parse(src).unwrap();
+
+        let init = |stmt: &Statement| match stmt {
+            Statement::VarDecl {
+                init: Some(init), ..
+            } => init.kind.to_owned(),
+            stmt => panic!("expected a declaration, found {stmt:?}"),
+        };
+
+        match init(&program.body[0]) {
+            ExprKind::Try(Try {
+                catch_param: Some(catch_param),
+                catch_body: Some(_),
+                finally: Some(_),
+                ..
+            }) => {
+                assert!(matches!(
+                    catch_param.pat.kind,
+                    PatternKind::Ident(BindingIdent { ref name, .. }) if name == "e"
+                ));
+                assert!(catch_param.type_ann.is_some());
+            }
+            kind => panic!("expected a try expression, found {kind:?}"),
+        }
+        assert!(matches!(
+            init(&program.body[1]),
+            ExprKind::Try(Try {
+                catch_param: None,
+                catch_body: Some(_),
+                finally: None,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn throw_expressions() {
+        let program = parse("let fail = (msg) => throw msg;").unwrap();
+
+        let body = match &program.body[0] {
+            Statement::VarDecl {
+                init: Some(init), ..
+            } => match &init.kind {
+                ExprKind::Lambda(Lambda { body, .. }) => body.kind.to_owned(),
+                kind => panic!("expected a lambda, found {kind:?}"),
+            },
+            stmt => panic!("expected a declaration, found {stmt:?}"),
+        };
+        assert!(matches!(body, ExprKind::Throw(_)));
+    }
+
+    #[test]
+    fn loops() {
+        let src = r#"
+        for (let mut x of xs) {
+            if (x > 10) { break; };
+            x = 0;
+        }
+        while (cond) {
+            continue;
+        }
+        "#;
+        let program = parse(src).unwrap();
+
+        let expr = |stmt: &Statement| match stmt {
+            Statement::Expr { expr, .. } => expr.kind.to_owned(),
+            stmt => panic!("expected an expression, found {stmt:?}"),
+        };
+
+        match expr(&program.body[0]) {
+            ExprKind::ForOf(ForOf { pattern, .. }) => assert!(matches!(
+                pattern.kind,
+                PatternKind::Ident(BindingIdent { ref name, mutable: true, .. }) if name == "x"
+            )),
+            kind => panic!("expected a for-of loop, found {kind:?}"),
+        }
+        match expr(&program.body[1]) {
+            ExprKind::While(While { body, .. }) => assert!(matches!(
+                body.kind,
+                ExprKind::Let(Let { ref init, .. }) if init.kind == ExprKind::Continue
+            )),
+            kind => panic!("expected a while loop, found {kind:?}"),
+        }
+    }
+
+    #[test]
+    fn unsupported_loops() {
+        assert!(parse("for (let key in obj) {}").is_err());
+        assert!(parse("for (x of xs) {}").is_err());
+        assert!(parse("while (true) { break outer; }").is_err());
+    }
+
+    #[test]
+    fn enums() {
+        let src = r#"
+        export enum Option<T> {
+            Some(T),
+            None,
+        }
+        let value = match (opt) {
+            Option.Some(x) -> x,
+            Option.None -> 0
+        };
+        "#;
+        let program = parse(src).unwrap();
+
+        let r#enum = match &program.body[0] {
+            Statement::ExportDecl { decl, .. } => match decl.as_ref() {
+                Statement::EnumDecl { r#enum, .. } => r#enum,
+                stmt => panic!("expected an enum, found {stmt:?}"),
+            },
+            stmt => panic!("expected an export, found {stmt:?}"),
+        };
+        assert_eq!(r#enum.ident.name, "Option");
+        assert_eq!(
+            r#enum.type_params.as_ref().map(|params| params.len()),
+            Some(1)
+        );
+        let variants: Vec<_> = r#enum
+            .variants
+            .iter()
+            .map(|variant| (variant.ident.name.as_str(), variant.payload.is_some()))
+            .collect();
+        assert_eq!(variants, vec![("Some", true), ("None", false)]);
+
+        let arms = match &program.body[1] {
+            Statement::VarDecl {
+                init: Some(init), ..
+            } => match &init.kind {
+                ExprKind::Match(Match { arms, .. }) => arms.to_owned(),
+                kind => panic!("expected a match, found {kind:?}"),
+            },
+            stmt => panic!("expected a variable declaration, found {stmt:?}"),
+        };
+        assert!(matches!(
+            &arms[0].pattern.kind,
+            PatternKind::EnumVariant(EnumVariantPat { r#enum, variant, payload: Some(payload) })
+                if r#enum.name == "Option"
+                    && variant.name == "Some"
+                    && matches!(payload.kind, PatternKind::Ident(_))
+        ));
+        assert!(matches!(
+            &arms[1].pattern.kind,
+            PatternKind::EnumVariant(EnumVariantPat { variant, payload: None, .. })
+                if variant.name == "None"
+        ));
+        assert_eq!(
+            program.exported_names(),
+            vec![(String::from("Option"), String::from("Option"))]
+        );
+    }
+
+    #[test]
+    fn enums_are_only_allowed_at_the_top_level() {
+        let (_, errors) = parse_partial("let f = () => { enum Foo { Bar } 5 };");
+
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn structs() {
+        let src = r#"
+        struct User {
+            name: string,
+            mut age: number,
+        }
+        let name = match (user) {
+            User {name, age: 30} -> name,
+            _ -> "someone else"
+        };
+        "#;
+        let program = parse(src).unwrap();
+
+        let r#struct = match &program.body[0] {
+            Statement::StructDecl { r#struct, .. } => r#struct,
+            stmt => panic!("expected a struct, found {stmt:?}"),
+        };
+        assert_eq!(r#struct.ident.name, "User");
+        let fields: Vec<_> = r#struct
+            .fields
+            .iter()
+            .map(|field| (field.key.name.as_str(), field.is_mutable))
+            .collect();
+        assert_eq!(fields, vec![("name", false), ("age", true)]);
+
+        let arms = match &program.body[1] {
+            Statement::VarDecl {
+                init: Some(init), ..
+            } => match &init.kind {
+                ExprKind::Match(Match { arms, .. }) => arms.to_owned(),
+                kind => panic!("expected a match, found {kind:?}"),
+            },
+            stmt => panic!("expected a variable declaration, found {stmt:?}"),
+        };
+        assert!(matches!(
+            &arms[0].pattern.kind,
+            PatternKind::Struct(StructPat { ident, props })
+                if ident.name == "User" && props.len() == 2
+        ));
+    }
+
+    #[test]
+    fn structs_are_only_allowed_at_the_top_level() {
+        let (_, errors) = parse_partial("let f = () => { struct Foo { bar: number } 5 };");
+
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn traits() {
+        let src = r#"
+        trait Greet {
+            name(): string;
+            greet(): string {
+                "Hello"
+            }
+        }
+        impl Greet for User {
+            name() {
+                this.first
+            }
+        }
+        "#;
+        let program = parse(src).unwrap();
+
+        let r#trait = match &program.body[0] {
+            Statement::TraitDecl { r#trait, .. } => r#trait,
+            stmt => panic!("expected a trait, found {stmt:?}"),
+        };
+        assert_eq!(r#trait.ident.name, "Greet");
+        let methods: Vec<_> = r#trait
+            .methods
+            .iter()
+            .map(|method| (method.key.name.as_str(), method.body.is_some()))
+            .collect();
+        assert_eq!(methods, vec![("name", false), ("greet", true)]);
+
+        let r#impl = match &program.body[1] {
+            Statement::ImplDecl { r#impl, .. } => r#impl,
+            stmt => panic!("expected an impl, found {stmt:?}"),
+        };
+        assert_eq!(r#impl.r#trait.name, "Greet");
+        assert_eq!(r#impl.type_ident.name, "User");
+        assert_eq!(r#impl.methods.len(), 1);
+    }
+
+    #[test]
+    fn traits_and_impls_are_only_allowed_at_the_top_level() {
+        let (_, errors) = parse_partial("let f = () => { trait Foo { bar(): number; } 5 };");
+
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn pipelines() {
+        let program = parse("let result = x |> f |> g(a, _);").unwrap();
+
+        let init = match &program.body[0] {
+            Statement::VarDecl { init, .. } => init.as_ref().unwrap(),
+            stmt => panic!("expected a variable declaration, found {stmt:?}"),
+        };
+        // Pipelines are left associative, `g` is applied to `x |> f`
+        let outer = match &init.kind {
+            ExprKind::Pipe(pipe) => pipe,
+            kind => panic!("expected a pipeline, found {kind:?}"),
+        };
+        assert_eq!(outer.args.len(), 1);
+        assert_eq!(outer.placeholder, 1);
+
+        let inner = match &outer.arg.kind {
+            ExprKind::Pipe(pipe) => pipe,
+            kind => panic!("expected a pipeline, found {kind:?}"),
+        };
+        assert!(inner.args.is_empty());
+        assert_eq!(inner.placeholder, 0);
+    }
+
+    #[test]
+    fn pipelines_bind_more_loosely_than_other_operators() {
+        let program = parse(
+            r#"
+            let sum = a + b |> f;
+            let less = a < b |> f;
+            let max = if (c) { x } else { y } |> f;
+            "#,
+        )
+        .unwrap();
+
+        let args: Vec<_> = program
+            .body
+            .iter()
+            .map(|stmt| match stmt {
+                Statement::VarDecl { init, .. } => match &init.as_ref().unwrap().kind {
+                    ExprKind::Pipe(pipe) => &pipe.arg.kind,
+                    kind => panic!("expected a pipeline, found {kind:?}"),
+                },
+                stmt => panic!("expected a variable declaration, found {stmt:?}"),
+            })
+            .collect();
+
+        assert!(matches!(args[0], ExprKind::BinaryExpr(_)));
+        assert!(matches!(args[1], ExprKind::BinaryExpr(_)));
+        assert!(matches!(args[2], ExprKind::IfElse(_)));
+    }
+
+    #[test]
+    fn pipelines_can_only_have_one_placeholder() {
+        assert!(parse("x |> f(_, _);").is_err());
+        assert!(parse("x |> f(..._);").is_err());
+    }
+
     // #[test]
     // fn top_level_parse_error() {
     //     let result = parse(
@@ -0,0 +1,64 @@
+use crochet_ast::values::Span;
+use error_stack::Context;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SyntaxErrorKind {
+    /// Text that doesn't fit the grammar, from an `ERROR` node.
+    Unexpected { text: String },
+    /// A token that tree-sitter inserted to recover, from a `MISSING` node.
+    Missing { token: String },
+    /// A node that parsed but that we don't know how to convert to the AST
+    /// yet.
+    Unsupported { message: String },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub kind: SyntaxErrorKind,
+    pub span: Span,
+    /// Describes the construct that was being parsed when the error occurred,
+    /// e.g. "expected an expression".
+    pub hint: Option<String>,
+}
+
+impl fmt::Display for SyntaxErrorKind {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyntaxErrorKind::Unexpected { text } => write!(fmt, "unexpected `{text}`"),
+            SyntaxErrorKind::Missing { token } => write!(fmt, "missing `{token}`"),
+            SyntaxErrorKind::Unsupported { message } => write!(fmt, "{message}"),
+        }
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "SyntaxError: {}", self.kind)
+    }
+}
+
+impl Context for SyntaxError {}
+
+/// Returns a hint describing what was expected in place of an error found
+/// while parsing a node of kind `parent`.
+pub fn expected_for(parent: &str) -> String {
+    match parent {
+        "program" | "statement_block" => String::from("expected a statement"),
+        "arguments"
+        | "array"
+        | "parenthesized_expression"
+        | "binary_expression"
+        | "pipeline_expression"
+        | "unary_expression"
+        | "template_substitution"
+        | "jsx_expression" => String::from("expected an expression"),
+        "formal_parameters" => String::from("expected a parameter"),
+        "object" => String::from("expected a property"),
+        "object_pattern" | "array_pattern" => String::from("expected a pattern"),
+        "type_annotation" | "type_arguments" | "union_type" | "intersection_type" => {
+            String::from("expected a type")
+        }
+        kind => format!("expected a valid {}", kind.replace('_', " ")),
+    }
+}
@@ -12,6 +12,21 @@ const replaceField = (prev, name, replacement) => {
   return seq(...members);
 };
 
+// Replaces every "readonly" keyword in a rule with "mut", no matter how
+// deeply it's nested in choices and sequences.
+const replaceReadonly = (node) => {
+  switch (node.type) {
+    case "STRING":
+      return node.value === "readonly" ? "mut" : node;
+    case "SEQ":
+      return seq(...node.members.map(replaceReadonly));
+    case "CHOICE":
+      return choice(...node.members.map(replaceReadonly));
+    default:
+      return node;
+  }
+};
+
 const dropLastMember = (node) => {
   return node.type === "SEQ" ? seq(...node.members.slice(0, -1)) : node;
 };
@@ -40,6 +55,25 @@ module.exports = grammar(tsx, {
       [$.export_statement, $.binding_identifier],
     ]),
 
+  // Adds `pipeline` to the binary operators' precedences right after
+  // `ternary`, which is the loosest of them since it's also used by `??`.
+  // This way `|>` binds more loosely than any of the other binary operators,
+  // e.g. `a + b |> f` is the same as `f(a + b)`.
+  precedences: ($, previous) =>
+    previous.map((list) => {
+      const index = list.findIndex(
+        (entry) => entry === "ternary" || entry.value === "ternary"
+      );
+      if (index === -1) {
+        return list;
+      }
+      return [
+        ...list.slice(0, index + 1),
+        "pipeline",
+        ...list.slice(index + 1),
+      ];
+    }),
+
   rules: {
     // Removes sequence expression and optional flow-style type assertion
     parenthesized_expression: ($, prev) => seq("(", $.expression, ")"),
@@ -92,7 +126,6 @@ module.exports = grammar(tsx, {
     },
 
     // Replaces `optional("readonly")` in sequence with `optional("mut")`
-    // TODO: Do the same for public_field_definition
     // TODO: Create a function that can replace all nodes of a certain type
     // in a rule declaration.
     method_definition: ($, prev) => {
@@ -109,6 +142,10 @@ module.exports = grammar(tsx, {
       return prec.left(seq(...members));
     },
 
+    // Replaces `readonly` with `mut` in the field's modifiers, fields are
+    // immutable unless they're marked as `mut`.
+    public_field_definition: ($, prev) => replaceReadonly(prev),
+
     expression: ($, prev) => {
       // Removes ternary expression
       const choices = prev.members.filter(
@@ -119,6 +156,7 @@ module.exports = grammar(tsx, {
       choices.push($.if_expression);
       choices.push($.match_expression);
       choices.push($.try_statement);
+      choices.push($.pipeline_expression);
 
       return choice(...choices);
     },
@@ -330,12 +368,85 @@ module.exports = grammar(tsx, {
         field("body", $.statement_block)
       ),
 
+    declaration: ($, prev) =>
+      choice(
+        prev,
+        $.struct_declaration,
+        $.trait_declaration,
+        $.impl_declaration
+      ),
+
+    // Structs are classes whose constructor takes a single object with all
+    // of the struct's fields, e.g.
+    // struct User { name: string, mut age: number }
+    struct_declaration: ($) =>
+      seq("struct", field("name", $.identifier), field("body", $.struct_body)),
+
+    struct_body: ($) =>
+      seq("{", commaSep($.struct_field), optional(","), "}"),
+
+    struct_field: ($) =>
+      seq(
+        optional(field("mut", "mut")),
+        field("name", $.property_identifier),
+        field("type", $.type_annotation)
+      ),
+
+    // Methods with a body are default implementations, e.g.
+    // trait Greet { name(): string; greet(): string { `Hello, ${this.name()}` } }
+    trait_declaration: ($) =>
+      seq("trait", field("name", $.identifier), field("body", $.trait_body)),
+
+    trait_body: ($) =>
+      seq(
+        "{",
+        repeat(
+          choice(seq($.method_signature, $._semicolon), $.method_definition)
+        ),
+        "}"
+      ),
+
+    // e.g. impl Greet for User { name(): string { this.name } }
+    impl_declaration: ($) =>
+      seq(
+        "impl",
+        field("trait", $.identifier),
+        "for",
+        field("type", $.identifier),
+        field("body", $.class_body)
+      ),
+
+    // Enum variants can have a payload, e.g.
+    // enum IpAddr { V4(string), V6(string) }
+    enum_declaration: ($) =>
+      seq(
+        "enum",
+        field("name", $.identifier),
+        optional(field("type_parameters", $.type_parameters)),
+        field("body", $.enum_body)
+      ),
+
+    enum_body: ($) => seq("{", commaSep($.enum_variant), optional(","), "}"),
+
+    enum_variant: ($) =>
+      seq(
+        field("name", $.identifier),
+        optional(seq("(", field("payload", $._type), ")"))
+      ),
+
     //
     // New expressions
     //
 
     do_expression: ($) => seq("do", $.statement_block),
 
+    // `x |> f` or `x |> f(a, _)`
+    pipeline_expression: ($) =>
+      prec.left(
+        "pipeline",
+        seq(field("left", $.expression), "|>", field("right", $.expression))
+      ),
+
     let_expression: ($) =>
       seq("let", field("name", $.refutable_pattern), $._initializer),
 
@@ -384,8 +495,10 @@ module.exports = grammar(tsx, {
         $.binding_identifier, // identifiers + undefined
 
         $.refutable_array_pattern,
+        $.refutable_enum_variant_pattern,
         $.refutable_is_pattern,
-        $.refutable_object_pattern
+        $.refutable_object_pattern,
+        $.refutable_struct_pattern
       ),
 
     refutable_array_pattern: ($) =>
@@ -395,6 +508,21 @@ module.exports = grammar(tsx, {
         "]"
       ),
 
+    refutable_enum_variant_pattern: ($) =>
+      seq(
+        field("enum", $.identifier),
+        ".",
+        field("variant", $.identifier),
+        optional(seq("(", field("payload", $.refutable_pattern), ")"))
+      ),
+
+    // e.g. User {name, age: 18}
+    refutable_struct_pattern: ($) =>
+      seq(
+        field("struct", $.identifier),
+        field("body", $.refutable_object_pattern)
+      ),
+
     refutable_is_pattern: ($) => seq($.binding_identifier, "is", $.identifier),
 
     refutable_object_pattern: ($) =>
@@ -4,8 +4,14 @@ import libPath from "../node_modules/typescript/lib/lib.es5.d.ts";
 import crochetWasmPath from "../target/wasm32-wasi/release/crochet.wasm";
 
 import Dropdown from "./dropdown";
-import { getPermalinkHref } from "./util";
-import { loadWasm, Compiler, CompilerResult } from "./wasm";
+import { getFilesPermalinkHref, MAIN_FILE, parsePermalink } from "./util";
+import {
+  loadWasm,
+  formatDiagnostic,
+  Compiler,
+  CompilerResult,
+  FilesResult,
+} from "./wasm";
 
 const DEFAULT_CODE = `
 // Welcome to the Crochet Playground!
@@ -15,11 +21,14 @@ let sum = add5(10);
 `;
 
 export const App = () => {
-  let [source, setSource] = React.useState(() => {
+  let [files, setFiles] = React.useState<Record<string, string>>(() => {
     const url = new URL(window.location.href);
     const hash = url.hash.slice(1);
-    return hash ? window.atob(hash) : DEFAULT_CODE.trim();
+    return hash ? parsePermalink(hash) : { [MAIN_FILE]: DEFAULT_CODE.trim() };
   });
+  let [activeFile, setActiveFile] = React.useState(
+    () => Object.keys(files)[0]
+  );
   let [crochet, setCrochet] = React.useState<Compiler | null>(null);
   let [outputTab, setOutputTab] = React.useState<"js" | "dts">("js");
 
@@ -38,8 +47,9 @@ export const App = () => {
     const listener = (e: HashChangeEvent) => {
       const url = new URL(e.newURL);
       const hash = url.hash.slice(1);
-      const source = window.atob(hash);
-      setSource(source);
+      const files = parsePermalink(hash);
+      setFiles(files);
+      setActiveFile(Object.keys(files)[0]);
     };
 
     window.addEventListener("hashchange", listener);
@@ -49,20 +59,64 @@ export const App = () => {
     };
   }, []);
 
-  let output = React.useMemo<CompilerResult>(() => {
+  let result = React.useMemo<FilesResult>(() => {
     try {
       if (crochet) {
-        return crochet.compile(source);
+        return crochet.compileFiles(files);
       } else {
-        return { type: "ok", data: { js: "", dts: "" } };
+        return { diagnostics: [], modules: {} };
       }
     } catch (e) {
-      return { type: "err", error: (e as Error).message };
+      const error = (e as Error).message;
+      return {
+        diagnostics: [],
+        modules: { [activeFile]: { type: "err", error, diagnostics: [] } },
+      };
     }
-  }, [source, crochet]);
+  }, [files, crochet]);
+
+  // Problems with the .d.ts files stop every module from being compiled so
+  // they're shown in place of the output no matter which file is active.
+  const libErrors = result.diagnostics.filter(
+    (diagnostic) => diagnostic.severity === "error"
+  );
+  const output: CompilerResult =
+    libErrors.length > 0
+      ? {
+          type: "err",
+          error: libErrors.map(formatDiagnostic).join("\n\n"),
+          diagnostics: result.diagnostics,
+        }
+      : result.modules[activeFile] ?? {
+          type: "ok",
+          data: { js: "", dts: "" },
+          diagnostics: [],
+        };
+
+  // Errors are already shown in place of the output, warnings don't stop
+  // the output from being generated so they're listed separately.
+  const warnings = Object.values(result.modules)
+    .flatMap((module) => module.diagnostics)
+    .filter((diagnostic) => diagnostic.severity === "warning");
 
   const updateSource = (e: React.ChangeEvent<HTMLTextAreaElement>) => {
-    setSource(e.target.value);
+    setFiles({ ...files, [activeFile]: e.target.value });
+  };
+
+  const addFile = () => {
+    const path = window.prompt("File name", "module.crochet");
+    if (path && !(path in files)) {
+      setFiles({ ...files, [path]: "" });
+      setActiveFile(path);
+    }
+  };
+
+  const removeFile = (path: string) => {
+    const { [path]: _, ...rest } = files;
+    setFiles(rest);
+    if (path === activeFile) {
+      setActiveFile(Object.keys(rest)[0]);
+    }
   };
 
   const styles = {
@@ -70,7 +124,8 @@ export const App = () => {
       display: "grid",
       gridTemplateColumns: "1fr 1fr",
       gridTemplateRows: "min-content 1fr",
-      height: "100%",
+      flex: 1,
+      minHeight: 0,
     },
     label: {
       fontFamily: "sans-serif",
@@ -91,6 +146,27 @@ export const App = () => {
       marginRight: 24,
       color: "var(--text-color)",
     },
+    timings: {
+      fontFamily: "sans-serif",
+      fontSize: 12,
+      marginLeft: "auto",
+      marginRight: 8,
+      alignSelf: "center",
+    },
+    files: {
+      display: "flex",
+      flexDirection: "column" as const,
+      minHeight: 0,
+      borderRight: "solid 1px var(--menu)",
+    },
+    warnings: {
+      fontFamily: "monospace",
+      fontSize: 14,
+      margin: 0,
+      padding: "4px 8px",
+      listStyle: "none",
+      borderTop: "solid 1px var(--menu)",
+    },
   };
 
   const activeTabStyle = {
@@ -127,7 +203,7 @@ export const App = () => {
       <div style={styles.grid}>
         <div className="menu-bar">
           <Dropdown />
-          <a className="menu-link" href={getPermalinkHref(source)}>
+          <a className="menu-link" href={getFilesPermalinkHref(files)}>
             Permalink
           </a>
         </div>
@@ -146,18 +222,71 @@ export const App = () => {
           >
             .d.ts
           </button>
+          {output.timings && (
+            <span
+              style={styles.timings}
+              title={Object.entries(output.timings)
+                .map(([phase, ms]) => `${phase}: ${ms.toFixed(1)}ms`)
+                .join("\n")}
+            >
+              {Object.values(output.timings)
+                .reduce((total, ms) => total + ms, 0)
+                .toFixed(1)}
+              ms
+            </span>
+          )}
+        </div>
+        <div style={styles.files}>
+          <div className="menu-bar">
+            {Object.keys(files).map((path) => (
+              <button
+                key={path}
+                style={path === activeFile ? activeTabStyle : {}}
+                className="button-reset tab"
+                onClick={() => setActiveFile(path)}
+              >
+                {path}
+                {Object.keys(files).length > 1 && (
+                  <span
+                    title={`Remove ${path}`}
+                    style={{ marginLeft: 6 }}
+                    onClick={(e) => {
+                      e.stopPropagation();
+                      removeFile(path);
+                    }}
+                  >
+                    {"\u00D7"}
+                  </span>
+                )}
+              </button>
+            ))}
+            <button
+              title="Add a file"
+              className="button-reset tab"
+              onClick={addFile}
+            >
+              +
+            </button>
+          </div>
+          <textarea
+            style={{ ...styles.editor, flex: 1 }}
+            value={files[activeFile] ?? ""}
+            onChange={updateSource}
+          />
         </div>
-        <textarea
-          style={{ ...styles.editor, borderRight: "solid 1px var(--menu)" }}
-          value={source}
-          onChange={updateSource}
-        />
         <textarea
           style={styles.editor}
           value={output.type === "ok" ? output.data[outputTab] : output.error}
           readOnly={true}
         />
       </div>
+      {warnings.length > 0 && (
+        <ul style={styles.warnings}>
+          {warnings.map((warning, index) => (
+            <li key={index}>{formatDiagnostic(warning)}</li>
+          ))}
+        </ul>
+      )}
     </div>
   );
 };
@@ -1,7 +1,7 @@
 import * as React from "react";
 
 import * as examples from "./examples";
-import { getPermalinkHref } from "./util";
+import { getFilesPermalinkHref, getPermalinkHref } from "./util";
 
 const Dropdown = () => {
   const [open, setOpen] = React.useState(false);
@@ -60,6 +60,9 @@ const Dropdown = () => {
         <a href={getPermalinkHref(examples.disjointUnionPatternMatching)}>
           Disjoint Union Pattern Matching
         </a>
+        <a href={getFilesPermalinkHref(examples.multipleFiles)}>
+          Multiple Files
+        </a>
       </div>
     </button>
   );
@@ -105,3 +105,23 @@ let result = match (event) {
     {type: "keydown", key} -> \`keydown: \${key}\`
 };
 `;
+
+// Each .crochet file is compiled separately, the declarations in the .d.ts
+// files are available to all of them.
+export const multipleFiles = {
+  "main.crochet": `import {origin, distance} from "./shapes";
+
+let greeting = greet("world");
+let point: Point = {x: 5, y: 10};
+let d = distance(point) + distance(origin);
+`,
+  "shapes.crochet": `export let origin: Point = {x: 0, y: 0};
+export let distance = (p: Point) => p.x * p.x + p.y * p.y;
+`,
+  "globals.d.ts": `interface Point {
+  x: number;
+  y: number;
+}
+declare var greet: (name: string) => string;
+`,
+};
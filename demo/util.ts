@@ -1,3 +1,26 @@
+// The name used for the source when there's only one file.
+export const MAIN_FILE = "main.crochet";
+
 export const getPermalinkHref = (code: string): string => {
   return `#${window.btoa(code.trim())}`;
 };
+
+// Several files are stored as a JSON object mapping paths to contents.
+export const getFilesPermalinkHref = (
+  files: Record<string, string>
+): string => {
+  return `#${window.btoa(JSON.stringify(files))}`;
+};
+
+export const parsePermalink = (hash: string): Record<string, string> => {
+  const text = window.atob(hash);
+  try {
+    const files = JSON.parse(text);
+    if (files && typeof files === "object" && !Array.isArray(files)) {
+      return files;
+    }
+  } catch (e) {
+    // It's a single source
+  }
+  return { [MAIN_FILE]: text };
+};
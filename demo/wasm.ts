@@ -37,10 +37,98 @@ export type Result<TData, TError> =
   | { type: "ok"; data: TData }
   | { type: "err"; error: TError };
 
-export type CompilerResult = Result<{ js: string; dts: string }, string>;
+export interface Position {
+  line: number;
+  column: number;
+}
+
+export interface Span {
+  start: number;
+  end: number;
+}
+
+export interface Range {
+  start: Position;
+  end: Position;
+}
+
+export interface Diagnostic {
+  file: string;
+  severity: "error" | "warning";
+  code: string | null;
+  message: string;
+  span: Span | null;
+  range: Range | null;
+  related: { file: string; message: string; span: Span; range: Range }[];
+  notes: string[];
+}
+
+// How long each phase of compilation took, in milliseconds.
+export interface Timings {
+  parse_lib: number;
+  parse: number;
+  infer: number;
+  codegen: number;
+}
+
+// The JSON payload returned by the `compile` export, `js` and `dts` are null
+// if there were any errors.
+interface CompilePayload {
+  js: string | null;
+  dts: string | null;
+  diagnostics: Diagnostic[];
+  timings: Timings;
+}
+
+// The JSON payload returned by the `compile_files` export, `diagnostics` has
+// the problems that aren't specific to one module, e.g. a lib that fails to
+// parse.
+interface VfsPayload {
+  diagnostics: Diagnostic[];
+  modules: Record<string, CompilePayload>;
+}
+
+export type CompilerResult = Result<
+  { js: string; dts: string },
+  string
+> & { diagnostics: Diagnostic[]; timings?: Timings };
+
+export const formatDiagnostic = (diagnostic: Diagnostic): string => {
+  const code = diagnostic.code ? `[${diagnostic.code}]` : "";
+  const location = diagnostic.range
+    ? ` (${diagnostic.file}:${diagnostic.range.start.line}:${diagnostic.range.start.column})`
+    : ` (${diagnostic.file})`;
+  const notes = diagnostic.notes.map((note) => `\nnote: ${note}`).join("");
+  return `${diagnostic.severity}${code}: ${diagnostic.message}${location}${notes}`;
+};
+
+export interface FilesResult {
+  diagnostics: Diagnostic[];
+  modules: Record<string, CompilerResult>;
+}
+
+const toCompilerResult = ({
+  js,
+  dts,
+  diagnostics,
+  timings,
+}: CompilePayload): CompilerResult => {
+  if (js === null || dts === null) {
+    const error = diagnostics
+      .filter((diagnostic) => diagnostic.severity === "error")
+      .map(formatDiagnostic)
+      .join("\n\n");
+    return { type: "err", error, diagnostics, timings };
+  }
+
+  return { type: "ok", data: { js, dts }, diagnostics, timings };
+};
 
 export interface Compiler {
   compile(input: string): CompilerResult;
+  // Compiles every .crochet file in `files`, which maps paths to contents,
+  // against the .d.ts files in `files` and the lib.
+  compileFiles(files: Record<string, string>): FilesResult;
 }
 
 export const loadWasm = async (
@@ -56,26 +144,25 @@ export const loadWasm = async (
   const wasm = await WebAssembly.compile(buffer);
 
   const imports = wasi.getImports(wasm);
-  const instance = await WebAssembly.instantiate(wasm, {
-    ...imports,
-    my_custom_module: {
-      _log: (value: any) => {
-        console.log(decodeString(memory, value));
-      },
-    },
-  });
+  const instance = await WebAssembly.instantiate(wasm, imports);
   wasi.start(instance);
 
   const memory = instance.exports.memory as WebAssembly.Memory;
   const allocate = instance.exports.allocate as (size: number) => number;
-  const deallocate = instance.exports.allocate as (
+  const deallocate = instance.exports.deallocate as (
     ptr: number,
     size: number
   ) => void;
+  const freeString = instance.exports.free_string as (ptr: number) => void;
+  const freeCompileResult = instance.exports.free_compile_result as (
+    ptr: number
+  ) => void;
 
   const parse = instance.exports.parse as (inputPtr: number) => number;
   const input = stringToCString(memory, allocate, "const x = 1 * 2 + 5 - 4");
-  const output = decodeString(memory, parse(input.ptr));
+  const outputPtr = parse(input.ptr);
+  const output = decodeString(memory, outputPtr);
+  freeString(outputPtr);
   console.log(`parse("const x = 1 * 2 + 5 - 4") = ${output}`);
   deallocate(input.ptr, input.size);
 
@@ -83,25 +170,47 @@ export const loadWasm = async (
     inputPtr: number,
     libPtr: number
   ) => number;
+  const compileFiles = instance.exports.compile_files as (
+    filesPtr: number,
+    libPtr: number
+  ) => number;
 
   const lib = stringToCString(memory, allocate, libCode);
 
   return {
     compile: (code) => {
       const input = stringToCString(memory, allocate, code);
-      const outPtr = compile(input.ptr, lib.ptr);
+      const resultPtr = compile(input.ptr, lib.ptr);
 
-      const js = decodeString(memory, outPtr);
-      const dts = decodeString(memory, outPtr + 8);
-      const error = decodeString(memory, outPtr + 16);
+      const payload: CompilePayload = JSON.parse(
+        decodeString(memory, resultPtr)
+      );
 
+      freeCompileResult(resultPtr);
       deallocate(input.ptr, input.size);
 
-      if (error) {
-        return { type: "err", error };
-      }
+      return toCompilerResult(payload);
+    },
+    compileFiles: (files) => {
+      const input = stringToCString(memory, allocate, JSON.stringify(files));
+      const resultPtr = compileFiles(input.ptr, lib.ptr);
+
+      const { diagnostics, modules }: VfsPayload = JSON.parse(
+        decodeString(memory, resultPtr)
+      );
+
+      freeCompileResult(resultPtr);
+      deallocate(input.ptr, input.size);
 
-      return { type: "ok", data: { js, dts } };
+      return {
+        diagnostics,
+        modules: Object.fromEntries(
+          Object.entries(modules).map(([path, payload]) => [
+            path,
+            toCompilerResult(payload),
+          ])
+        ),
+      };
     },
   };
 };